@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Goals module.
+//!
+//! Word targets for a writing session, a day and the whole project, with an optional deadline
+//! for the project target.
+
+use crate::history::History;
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
+
+/// The word targets of a project. A target of [`None`] is not tracked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Goals {
+    pub session: Option<usize>,
+    pub daily: Option<usize>,
+    pub project: Option<usize>,
+    /// The day the project target should be reached by.
+    pub deadline: Option<Date>,
+}
+
+/// Words written towards a target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub written: usize,
+    pub target: usize,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Progress {
+    /// Returns how much of the target is written, from `0.0` up to `1.0`.
+    pub fn fraction(&self) -> f64 {
+        if self.target == 0_usize {
+            return 1_f64;
+        }
+        (self.written as f64 / self.target as f64).min(1_f64)
+    }
+
+    pub fn is_met(&self) -> bool {
+        self.written >= self.target
+    }
+
+    /// Returns the words left to reach the target.
+    pub fn remaining(&self) -> usize {
+        self.target.saturating_sub(self.written)
+    }
+}
+
+impl Goals {
+    /// Returns the progress of a session where `written` words were added.
+    pub fn session_progress(&self, written: i64) -> Option<Progress> {
+        Some(Progress {
+            written: written.max(0_i64) as usize,
+            target: self.session?,
+        })
+    }
+
+    /// Returns the progress of `today` from the words recorded in `history`.
+    pub fn daily_progress(&self, history: &History, today: Date) -> Option<Progress> {
+        Some(Progress {
+            written: history.words_on(today).max(0_i64) as usize,
+            target: self.daily?,
+        })
+    }
+
+    /// Returns the progress of the project that holds `total` words.
+    pub fn project_progress(&self, total: usize) -> Option<Progress> {
+        Some(Progress {
+            written: total,
+            target: self.project?,
+        })
+    }
+
+    /// Returns the words to write every day from `today` on to reach the project target by the
+    /// deadline, counting today. Returns [`None`] without a project target or deadline, or once
+    /// the deadline has passed.
+    pub fn words_per_day(&self, total: usize, today: Date) -> Option<usize> {
+        let remaining = self.project_progress(total)?.remaining();
+        let days = (self.deadline? - today).whole_days() + 1_i64;
+        if days <= 0_i64 {
+            return None;
+        }
+        Some(remaining.div_ceil(days as usize))
+    }
+}
+
+/// Returns the date written as `YYYY-MM-DD`, or [`None`] if it is not a valid date.
+pub fn parse_date(text: &str) -> Option<Date> {
+    let mut parts = text.trim().splitn(3_usize, '-');
+    let year = parts.next()?.parse::<i32>().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let day = parts.next()?.parse::<u8>().ok()?;
+
+    Date::from_calendar_date(year, month, day).ok()
+}
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! History module.
+//!
+//! What was written on every day of a project: words added and removed, the time spent typing
+//! and how much each document grew.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use time::{Date, Duration};
+
+/// What was written on a single day.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Day {
+    /// Words added minus words removed.
+    pub words: i64,
+    #[serde(default)]
+    pub added: u64,
+    #[serde(default)]
+    pub removed: u64,
+    /// Milliseconds spent typing.
+    #[serde(default)]
+    pub typing: u64,
+    /// Net words written in every document that was touched, by title.
+    #[serde(default)]
+    pub documents: BTreeMap<String, i64>,
+}
+
+/// The [`Day`]s of a project in order.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct History {
+    days: BTreeMap<Date, Day>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl History {
+    /// To record that `words` were written in `document` on `date`. Removed words are negative.
+    pub fn add_words(&mut self, date: Date, document: &str, words: i64) {
+        let day = self.days.entry(date).or_default();
+        day.words += words;
+        match words.is_negative() {
+            true => day.removed += words.unsigned_abs(),
+            false => day.added += words.unsigned_abs(),
+        }
+        *day.documents.entry(document.to_string()).or_default() += words;
+    }
+
+    /// To add `millis` milliseconds to the time spent typing on `date`.
+    pub fn add_typing(&mut self, date: Date, millis: u64) {
+        self.days.entry(date).or_default().typing += millis;
+    }
+
+    /// Returns the words written on `date`.
+    pub fn words_on(&self, date: Date) -> i64 {
+        self.day(date).map_or(0_i64, |day| day.words)
+    }
+
+    pub fn day(&self, date: Date) -> Option<&Day> {
+        self.days.get(&date)
+    }
+
+    /// Returns every recorded day from the oldest to the newest.
+    pub fn days(&self) -> impl Iterator<Item = (Date, &Day)> {
+        self.days.iter().map(|(date, day)| (*date, day))
+    }
+
+    /// Returns the number of days in a row, up to `today`, on which words were written. A streak
+    /// is not broken until the end of the day, so it may end yesterday.
+    pub fn streak(&self, today: Date) -> usize {
+        let mut date = match self.words_on(today) > 0_i64 {
+            true => today,
+            false => today - Duration::DAY,
+        };
+        let mut streak = 0_usize;
+        while self.words_on(date) > 0_i64 {
+            streak += 1_usize;
+            date -= Duration::DAY;
+        }
+        streak
+    }
+
+    /// Returns the longest run of days in a row on which words were written.
+    pub fn longest_streak(&self) -> usize {
+        let (mut longest, mut current) = (0_usize, 0_usize);
+        let mut previous: Option<Date> = None;
+
+        for (date, _) in self.days().filter(|(_, day)| day.words > 0_i64) {
+            current = match previous.and_then(Date::next_day) == Some(date) {
+                true => current + 1_usize,
+                false => 1_usize,
+            };
+            longest = longest.max(current);
+            previous = Some(date);
+        }
+        longest
+    }
+
+    /// Returns the net words written in every document from `since` on, by title.
+    pub fn growth(&self, since: Date) -> BTreeMap<String, i64> {
+        let mut growth = BTreeMap::new();
+        for (_, day) in self.days.range(since..) {
+            for (document, words) in day.documents.iter() {
+                *growth.entry(document.clone()).or_default() += *words;
+            }
+        }
+        growth
+    }
+}
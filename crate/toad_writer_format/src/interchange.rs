@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Interchange module.
+//!
+//! Moves lore in and out of a project. JSON keeps every entity as it is and follows
+//! [`LORE_SCHEMA`]. CSV holds a single kind of entity per file, one row each, for spreadsheets:
+//! the columns `id`, `name`, `aliases`, `description` and `image`, then one column per attribute.
+//! Both read into [`Incoming`] entities to be merged with [`lore::Registry::merge`].
+
+use lore::{Entity, EntityId, EntityKind, Incoming, Registry};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// The JSON Schema of the files written by [`lore_to_json`].
+pub const LORE_SCHEMA: &str = include_str!("../schema/lore.schema.json");
+/// Version of the JSON format written.
+const VERSION: u32 = 1_u32;
+/// Columns every CSV file has, before the attributes.
+const COLUMNS: [&str; 5_usize] = ["id", "name", "aliases", "description", "image"];
+/// How aliases are joined in a CSV cell.
+const ALIAS_SEPARATOR: &str = "; ";
+
+/// Errors that can occur while reading or writing lore in another format.
+#[derive(Debug, Error)]
+pub enum InterchangeError {
+    #[error("The JSON lore is malformed: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The CSV lore is malformed: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("The CSV lore has no {0:?} column.")]
+    MissingColumn(&'static str),
+    #[error("{0:?} on line {1} is not an id.")]
+    InvalidId(String, u64),
+    #[error("Version {0} of the JSON lore is not supported.")]
+    UnsupportedVersion(u32),
+}
+
+/// The lore as written to JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct LoreFile {
+    version: u32,
+    entities: Vec<Entity>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// Returns every entity of `registry` as pretty printed JSON.
+pub fn lore_to_json(registry: &Registry) -> Result<String, InterchangeError> {
+    let file = LoreFile {
+        version: VERSION,
+        entities: registry.iter().cloned().collect(),
+    };
+    Ok(serde_json::to_string_pretty(&file)?)
+}
+
+/// Reads the entities of JSON written by [`lore_to_json`].
+pub fn lore_from_json(text: &str) -> Result<Vec<Incoming>, InterchangeError> {
+    let file = serde_json::from_str::<LoreFile>(text)?;
+    if file.version != VERSION {
+        return Err(InterchangeError::UnsupportedVersion(file.version));
+    }
+    Ok(file.entities.into_iter().map(Incoming::from).collect())
+}
+
+/// Returns the entities of `kind` in `registry` as CSV, with a column for every attribute any of
+/// them has.
+pub fn lore_to_csv(registry: &Registry, kind: EntityKind) -> Result<String, InterchangeError> {
+    let keys = registry
+        .of_kind(kind)
+        .flat_map(|entity| entity.attributes.keys())
+        .filter(|key| !COLUMNS.contains(&key.as_str()))
+        .map(String::as_str)
+        .collect::<BTreeSet<&str>>();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(COLUMNS.into_iter().chain(keys.iter().copied()))?;
+    for entity in registry.of_kind(kind) {
+        let image = entity
+            .image
+            .as_ref()
+            .map(|image| image.display().to_string())
+            .unwrap_or_default();
+        let mut record = vec![
+            entity.id().value().to_string(),
+            entity.name.clone(),
+            entity.aliases.join(ALIAS_SEPARATOR),
+            entity.description.clone(),
+            image,
+        ];
+        record.extend(
+            keys.iter()
+                .map(|key| entity.attributes.get(*key).cloned().unwrap_or_default()),
+        );
+        writer.write_record(record)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(err.into_error()))?;
+    Ok(String::from_utf8_lossy(bytes.as_slice()).into_owned())
+}
+
+/// Reads the rows of CSV as entities of `kind`. Only the `name` column is needed, the others may be
+/// missing or in any order. Empty cells and rows without a name are skipped, and columns that are
+/// not known are attributes.
+pub fn lore_from_csv(text: &str, kind: EntityKind) -> Result<Vec<Incoming>, InterchangeError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers = reader
+        .headers()?
+        .iter()
+        .map(str::to_string)
+        .collect::<Vec<String>>();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+    };
+    let Some(name) = column("name") else {
+        return Err(InterchangeError::MissingColumn("name"));
+    };
+    let (id, aliases, description, image) = (
+        column("id"),
+        column("aliases"),
+        column("description"),
+        column("image"),
+    );
+
+    let mut entities = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .filter(|cell| !cell.is_empty())
+        };
+        let Some(name) = cell(Some(name)) else {
+            continue;
+        };
+        let id = match cell(id) {
+            Some(id) => match id.trim_start_matches('@').parse::<u32>() {
+                Ok(id) => Some(EntityId::from_value(id)),
+                Err(_) => {
+                    let line = record.position().map(|at| at.line()).unwrap_or_default();
+                    return Err(InterchangeError::InvalidId(id.to_string(), line));
+                }
+            },
+            None => None,
+        };
+
+        let mut item = Incoming::new(id, kind, name);
+        let entity = &mut item.entity;
+        if let Some(aliases) = cell(aliases) {
+            entity.aliases = aliases
+                .split(ALIAS_SEPARATOR.trim())
+                .map(str::trim)
+                .filter(|alias| !alias.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(description) = cell(description) {
+            entity.description = description.to_string();
+        }
+        entity.image = cell(image).map(PathBuf::from);
+        for (index, key) in headers.iter().enumerate() {
+            let known = COLUMNS
+                .iter()
+                .any(|column| key.eq_ignore_ascii_case(column));
+            if known || key.is_empty() {
+                continue;
+            }
+            if let Some(value) = cell(Some(index)) {
+                entity.attributes.insert(key.clone(), value.to_string());
+            }
+        }
+        entities.push(item);
+    }
+    Ok(entities)
+}
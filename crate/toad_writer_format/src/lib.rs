@@ -2,3 +2,10 @@
 // Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
 
 //! Library for handling the format of Toad Writer documents.
+
+pub mod goals;
+pub mod history;
+pub mod interchange;
+pub mod project;
+pub mod scenes;
+pub mod series;
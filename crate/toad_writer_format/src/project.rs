@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Project module.
+//!
+//! The project file that keeps the documents, [`Goals`], [`History`], dictionary, lint
+//! settings, calendars and lore of a manuscript as TOML. The lore of a book of a series is kept
+//! in the series as well.
+
+use crate::{goals::Goals, history::History, series::SeriesLink};
+use lore::{Calendar, Registry};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fs, io, path::Path};
+use syntax::lint::LintConfig;
+use thiserror::Error;
+
+/// Errors that can occur while reading or writing a [`Project`] file.
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    #[error("Cannot access the project file: {0}")]
+    Io(#[from] io::Error),
+    #[error("The project file is malformed: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Cannot write the project file: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// A document of the manuscript. Its text keeps the annotations, and with them the metadata of
+/// its scenes.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Document {
+    pub title: String,
+    #[serde(default)]
+    pub text: String,
+}
+
+/// Everything kept about a manuscript.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Project {
+    #[serde(default)]
+    pub goals: Goals,
+    #[serde(default)]
+    pub history: History,
+    /// Words the writer accepted as correctly spelled.
+    #[serde(default)]
+    pub dictionary: BTreeSet<String>,
+    /// Rules of the prose linter that do not use their defaults.
+    #[serde(default)]
+    pub lint: LintConfig,
+    /// Calendars of the setting, used to read the dates written in the text.
+    #[serde(default)]
+    pub calendars: Vec<Calendar>,
+    /// The series the manuscript is a book of, whose lore replaces the one of the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<SeriesLink>,
+    /// The documents of the manuscript in tab order.
+    #[serde(default)]
+    pub documents: Vec<Document>,
+    /// Every lore entity, kept last as entities are tables. In a series, the lore of the book
+    /// when it was last saved.
+    #[serde(default)]
+    pub lore: Registry,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Project {
+    /// Reads the project at `path`. A missing file is an empty project.
+    pub fn load(path: &Path) -> Result<Self, ProjectError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_toml(text.as_str()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes the project to `path`, creating the parent directories if needed.
+    pub fn save(&self, path: &Path) -> Result<(), ProjectError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, ProjectError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ProjectError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Reads a single calendar written as TOML, such as one edited on its own.
+    pub fn calendar_from_toml(text: &str) -> Result<Calendar, ProjectError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn calendar_to_toml(calendar: &Calendar) -> Result<String, ProjectError> {
+        Ok(toml::to_string(calendar)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lore::EntityKind;
+
+    #[test]
+    fn removed_ids_are_not_given_out_again() {
+        let mut project = Project::default();
+        project.lore.add(EntityKind::Character, "Mara").unwrap();
+        let tom = project.lore.add(EntityKind::Character, "Tom").unwrap();
+        project.lore.remove(tom).unwrap();
+
+        let mut loaded = Project::from_toml(project.to_toml().unwrap().as_str()).unwrap();
+        assert_eq!(loaded.lore, project.lore);
+        assert_ne!(loaded.lore.add(EntityKind::Item, "Key").unwrap(), tom);
+    }
+}
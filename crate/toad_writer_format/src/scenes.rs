@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Scenes module.
+//!
+//! Keeps the [`SceneMetadata`] of a scene in its own text, as a scene annotation on its first
+//! line, so that it moves with the scene and is saved with the manuscript. [`scenes_to_csv`]
+//! reports the metadata of some scenes for spreadsheets.
+
+use std::ops::Range;
+use syntax::annotation::{annotations, scene_annotation, strip_annotations, AnnotationKind};
+use syntax::statistics::Statistics;
+use tree::{DocumentTree, NodeId, SceneMetadata, TextEdit};
+
+/// Columns of the scene report.
+const COLUMNS: [&str; 7_usize] = [
+    "chapter", "scene", "pov", "date", "location", "status", "words",
+];
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// To give every scene of `tree` the metadata of the first scene annotation in its text.
+pub fn read_scene_metadata(tree: &mut DocumentTree) {
+    for scene in tree.scenes() {
+        let Some(node) = tree.node(scene) else {
+            continue;
+        };
+        let metadata = scene_metadata(node.text.as_str())
+            .map(|(_, metadata)| metadata)
+            .unwrap_or_default();
+        let _ = tree.set_metadata(scene, metadata);
+    }
+}
+
+/// Returns the edit that writes the metadata of `scene` of `tree` to its text: the scene
+/// annotation is replaced, added as the first line or removed along with its line break if the
+/// metadata is empty. Returns [`None`] if the text already holds the metadata.
+pub fn write_scene_metadata(tree: &DocumentTree, scene: NodeId) -> Option<TextEdit> {
+    let node = tree.node(scene)?;
+    let text = node.text.as_str();
+    let (range, new) = match (scene_metadata(text), node.metadata.is_empty()) {
+        (Some((_, metadata)), _) if metadata == node.metadata => return None,
+        (Some((range, _)), true) => {
+            let end = match text[range.end..].starts_with('\n') {
+                true => range.end + 1_usize,
+                false => range.end,
+            };
+            (range.start..end, String::new())
+        }
+        (Some((range, _)), false) => (range, scene_annotation(&node.metadata)),
+        (None, true) => return None,
+        (None, false) => (
+            0_usize..0_usize,
+            format!("{}\n", scene_annotation(&node.metadata)),
+        ),
+    };
+    Some(TextEdit {
+        node: scene,
+        old: text[range.clone()].to_string(),
+        range,
+        new,
+    })
+}
+
+/// Returns the byte range and the metadata of the first scene annotation of `text`.
+fn scene_metadata(text: &str) -> Option<(Range<usize>, SceneMetadata)> {
+    annotations(text)
+        .into_iter()
+        .find_map(|annotation| match annotation.kind {
+            AnnotationKind::Scene(metadata) => Some((annotation.range, metadata)),
+            _ => None,
+        })
+}
+
+/// Returns the metadata and the word count of `scenes` of `tree` as CSV, one row each.
+pub fn scenes_to_csv(tree: &DocumentTree, scenes: &[NodeId]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(COLUMNS)?;
+    for scene in scenes {
+        let Some(node) = tree.node(*scene) else {
+            continue;
+        };
+        let chapter = tree
+            .chapter_of(*scene)
+            .and_then(|chapter| tree.node(chapter))
+            .map(|chapter| chapter.title.clone())
+            .unwrap_or_default();
+        let metadata = &node.metadata;
+        writer.write_record([
+            chapter,
+            node.title.clone(),
+            metadata.pov.clone(),
+            metadata.date.clone(),
+            metadata.location.clone(),
+            metadata.status.to_string(),
+            Statistics::new(strip_annotations(node.text.as_str()).as_str())
+                .words
+                .to_string(),
+        ])?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(err.into_error()))?;
+    Ok(String::from_utf8_lossy(bytes.as_slice()).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Document, Project};
+    use std::{env, fs};
+    use tree::SceneStatus;
+
+    const CHAPTER: &str = "First scene.\n***\nSecond scene.\n";
+
+    fn tree(text: &str) -> DocumentTree {
+        let mut tree = DocumentTree::new("Book");
+        tree.add_chapter_text("Untitled 1", text);
+        read_scene_metadata(&mut tree);
+        tree
+    }
+
+    fn write(text: &str, scene: usize, metadata: SceneMetadata) -> String {
+        let mut tree = tree(text);
+        let scene = tree.scenes()[scene];
+        tree.set_metadata(scene, metadata).unwrap();
+        let edit = write_scene_metadata(&tree, scene).unwrap();
+        let start = tree.source_offset(scene, edit.range.start).unwrap();
+        let end = tree.source_offset(scene, edit.range.end).unwrap();
+        let mut text = text.chars().collect::<Vec<char>>();
+        text.splice(start..end, edit.new.chars());
+        text.into_iter().collect()
+    }
+
+    fn final_draft(pov: &str) -> SceneMetadata {
+        SceneMetadata {
+            pov: pov.to_string(),
+            status: SceneStatus::Final,
+            ..SceneMetadata::default()
+        }
+    }
+
+    #[test]
+    fn metadata_is_kept_with_its_scene() {
+        let text = write(CHAPTER, 1_usize, final_draft("Mara"));
+        assert_eq!(
+            text,
+            "First scene.\n***\n{{scene: pov = Mara | status = Final}}\nSecond scene.\n"
+        );
+
+        // A scene break before it does not move the metadata to another scene.
+        let tree = tree(format!("New scene.\n***\n{text}").as_str());
+        let metadata = tree
+            .scenes()
+            .into_iter()
+            .map(|scene| tree.node(scene).unwrap().metadata.clone())
+            .collect::<Vec<SceneMetadata>>();
+        assert_eq!(
+            metadata,
+            vec![
+                SceneMetadata::default(),
+                SceneMetadata::default(),
+                final_draft("Mara")
+            ]
+        );
+    }
+
+    #[test]
+    fn metadata_is_replaced_and_removed() {
+        let text = write(CHAPTER, 1_usize, final_draft("Mara"));
+        let text = write(text.as_str(), 1_usize, final_draft("Tom"));
+        assert_eq!(
+            text,
+            "First scene.\n***\n{{scene: pov = Tom | status = Final}}\nSecond scene.\n"
+        );
+        assert_eq!(
+            write(text.as_str(), 1_usize, SceneMetadata::default()),
+            CHAPTER
+        );
+    }
+
+    #[test]
+    fn metadata_is_kept_when_the_project_is_saved() {
+        let text = write(CHAPTER, 1_usize, final_draft("Mara"));
+        let project = Project {
+            documents: vec![Document {
+                title: "Untitled 1".to_string(),
+                text,
+            }],
+            ..Project::default()
+        };
+        let path = env::temp_dir().join(format!("toad_writer_scenes_{}.toml", std::process::id()));
+        project.save(&path).unwrap();
+        let loaded = Project::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        let document = &loaded.unwrap().documents[0_usize];
+        let tree = tree(document.text.as_str());
+        let scene = tree.scenes()[1_usize];
+        assert_eq!(tree.node(scene).unwrap().metadata, final_draft("Mara"));
+    }
+}
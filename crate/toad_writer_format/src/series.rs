@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Series module.
+//!
+//! The series bible, a TOML file that keeps the lore shared by the books of a series and what each
+//! book keeps for itself. A [`crate::project::Project`] joins a series with a [`SeriesLink`].
+
+use lore::{check, AbsorbReport, Calendar, EntityOverride, Inconsistency, Registry};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path, path::PathBuf};
+use thiserror::Error;
+
+/// Errors that can occur while reading or writing a [`Series`] file.
+#[derive(Debug, Error)]
+pub enum SeriesError {
+    #[error("Cannot access the series file: {0}")]
+    Io(#[from] io::Error),
+    #[error("The series file is malformed: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Cannot write the series file: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Where the series of a project is kept, and which of its books the project is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesLink {
+    pub path: PathBuf,
+    pub book: String,
+}
+
+/// A book of a series.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Book {
+    pub name: String,
+    /// Values of shared entities that only hold in this book.
+    #[serde(default)]
+    pub overrides: Vec<EntityOverride>,
+}
+
+/// The lore of a series and its books.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Series {
+    pub name: String,
+    #[serde(default)]
+    pub books: Vec<Book>,
+    /// Every shared lore entity, kept last as entities are tables.
+    #[serde(default)]
+    pub lore: Registry,
+}
+
+/// A problem in the lore of some books of a series.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeriesIssue {
+    /// The books with the problem, every book if empty.
+    pub books: Vec<String>,
+    pub inconsistency: Inconsistency,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Book {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl Series {
+    /// Returns a series called `name` without books or lore.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            books: Vec::new(),
+            lore: Registry::new(),
+        }
+    }
+
+    /// Reads the series at `path`.
+    pub fn load(path: &Path) -> Result<Self, SeriesError> {
+        Self::from_toml(fs::read_to_string(path)?.as_str())
+    }
+
+    /// Writes the series to `path`, creating the parent directories if needed.
+    pub fn save(&self, path: &Path) -> Result<(), SeriesError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, SeriesError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, SeriesError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Returns the book called `name`, adding it if the series does not have it.
+    pub fn book_mut(&mut self, name: &str) -> &mut Book {
+        let index = match self.books.iter().position(|book| book.name == name) {
+            Some(index) => index,
+            None => {
+                self.books.push(Book::new(name));
+                self.books.len() - 1_usize
+            }
+        };
+        &mut self.books[index]
+    }
+
+    /// Returns what the book called `name` keeps for itself.
+    pub fn overrides(&self, name: &str) -> &[EntityOverride] {
+        self.books
+            .iter()
+            .find(|book| book.name == name)
+            .map(|book| book.overrides.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns the lore as the book called `name` sees it.
+    pub fn lore_of(&self, name: &str) -> Registry {
+        self.lore.overridden(self.overrides(name))
+    }
+
+    /// To take in the lore of the book called `name`, sharing every change it made since it was
+    /// `loaded` to the values it does not keep for itself. The book has to read its lore again
+    /// with [`Series::lore_of`] afterwards, as the entities it added get new ids.
+    pub fn absorb(&mut self, name: &str, lore: &Registry, loaded: &Registry) -> AbsorbReport {
+        let mut overrides = std::mem::take(&mut self.book_mut(name).overrides);
+        let report = self.lore.absorb(lore, loaded, &mut overrides);
+        self.book_mut(name).overrides = overrides;
+        report
+    }
+
+    /// Returns the problems in the lore of every book, with births read with `calendar`. A problem
+    /// found in every book is only listed once, with no books.
+    pub fn check(&self, calendar: Option<&Calendar>) -> Vec<SeriesIssue> {
+        let mut issues = Vec::<SeriesIssue>::new();
+        if self.books.is_empty() {
+            return check(&self.lore, calendar)
+                .into_iter()
+                .map(|inconsistency| SeriesIssue {
+                    books: Vec::new(),
+                    inconsistency,
+                })
+                .collect();
+        }
+        for book in self.books.iter() {
+            for inconsistency in check(&self.lore_of(book.name.as_str()), calendar) {
+                match issues
+                    .iter_mut()
+                    .find(|issue| issue.inconsistency == inconsistency)
+                {
+                    Some(issue) => issue.books.push(book.name.clone()),
+                    None => issues.push(SeriesIssue {
+                        books: vec![book.name.clone()],
+                        inconsistency,
+                    }),
+                }
+            }
+        }
+        for issue in issues.iter_mut() {
+            if issue.books.len() == self.books.len() {
+                issue.books.clear();
+            }
+        }
+        issues
+    }
+}
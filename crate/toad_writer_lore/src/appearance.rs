@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Appearance module.
+//!
+//! Where every lore entity is mentioned, counted per chapter, so that forgotten characters and
+//! places stand out. A gap is a run of chapters without a mention between the first and the last
+//! appearance of an entity.
+
+use crate::{
+    entity::{EntityId, EntityKind},
+    mention::MentionFinder,
+    registry::Registry,
+};
+use std::ops::Range;
+use syntax::readability::csv_field;
+use tree::{DocumentTree, NodeId};
+
+/// The mentions of a single entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Appearances {
+    pub entity: EntityId,
+    pub kind: EntityKind,
+    pub name: String,
+    /// Number of mentions in every chapter, in the order of [`AppearanceReport::chapters`].
+    pub counts: Vec<usize>,
+}
+
+/// The [`Appearances`] of every entity across the chapters of a [`DocumentTree`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppearanceReport {
+    /// Every chapter with its title, in reading order.
+    pub chapters: Vec<(NodeId, String)>,
+    /// A row for every entity, ordered by kind and then by name.
+    pub rows: Vec<Appearances>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Appearances {
+    /// Returns the total number of mentions.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the index of the first chapter with a mention.
+    pub fn first(&self) -> Option<usize> {
+        self.counts.iter().position(|count| *count > 0_usize)
+    }
+
+    /// Returns the index of the last chapter with a mention.
+    pub fn last(&self) -> Option<usize> {
+        self.counts.iter().rposition(|count| *count > 0_usize)
+    }
+
+    /// Returns the ranges of chapter indices without a mention between the first and the last
+    /// appearance.
+    pub fn gaps(&self) -> Vec<Range<usize>> {
+        let (Some(first), Some(last)) = (self.first(), self.last()) else {
+            return Vec::new();
+        };
+        let mut gaps = Vec::new();
+        let mut start = None;
+
+        for index in first..=last {
+            match (self.counts[index] == 0_usize, start) {
+                (true, None) => start = Some(index),
+                (false, Some(gap)) => {
+                    gaps.push(gap..index);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        gaps
+    }
+
+    /// Returns the number of chapters in the longest gap.
+    pub fn longest_gap(&self) -> usize {
+        self.gaps()
+            .iter()
+            .map(|gap| gap.len())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl AppearanceReport {
+    /// Counts the mentions of every entity of `kinds` in `registry` in each chapter of `tree`.
+    pub fn new(registry: &Registry, tree: &DocumentTree, kinds: &[EntityKind]) -> Self {
+        let finder = MentionFinder::new(registry);
+        let chapters = tree
+            .chapters()
+            .into_iter()
+            .filter_map(|chapter| Some((chapter, tree.node(chapter)?.title.clone())))
+            .collect::<Vec<(NodeId, String)>>();
+
+        let mut rows = registry
+            .iter()
+            .filter(|entity| kinds.contains(&entity.kind))
+            .map(|entity| Appearances {
+                entity: entity.id(),
+                kind: entity.kind,
+                name: entity.name.clone(),
+                counts: vec![0_usize; chapters.len()],
+            })
+            .collect::<Vec<Appearances>>();
+
+        for (index, (chapter, _)) in chapters.iter().enumerate() {
+            for scene in tree.children(*chapter) {
+                let Some(node) = tree.node(*scene) else {
+                    continue;
+                };
+                for mention in finder.find(node.text.as_str()) {
+                    let row = rows.iter_mut().find(|row| row.entity == mention.entity);
+                    if let Some(row) = row {
+                        row.counts[index] += 1_usize;
+                    }
+                }
+            }
+        }
+        rows.sort_by(|lhs, rhs| lhs.kind.cmp(&rhs.kind).then(lhs.name.cmp(&rhs.name)));
+
+        Self { chapters, rows }
+    }
+
+    /// Returns the report as CSV with a column for every chapter, followed by the first and last
+    /// chapter with a mention and the longest gap.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["kind".to_string(), "name".to_string()];
+        header.extend(self.chapters.iter().map(|(_, title)| csv_field(title)));
+        header.extend(["first", "last", "longest_gap"].map(String::from));
+
+        let mut csv = header.join(",");
+        for row in self.rows.iter() {
+            let title = |index: Option<usize>| {
+                index
+                    .and_then(|index| self.chapters.get(index))
+                    .map(|(_, title)| csv_field(title))
+                    .unwrap_or_default()
+            };
+            let mut fields = vec![row.kind.to_string(), csv_field(row.name.as_str())];
+            fields.extend(row.counts.iter().map(usize::to_string));
+            fields.push(title(row.first()));
+            fields.push(title(row.last()));
+            fields.push(row.longest_gap().to_string());
+
+            csv.push('\n');
+            csv.push_str(fields.join(",").as_str());
+        }
+        csv.push('\n');
+
+        csv
+    }
+}
@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Attribute module.
+//!
+//! Attributes such as hair colour or a title may change during the story. An [`AttributeChange`]
+//! gives an attribute a new value from a point in story time on, and [`check_attributes`] flags
+//! scenes that state a value the attribute does not have at that point, or a wrong age, in prose
+//! or with a fact annotation.
+
+use crate::{
+    calendar::Calendar,
+    entity::{EntityId, EntityKind},
+    mention::{whole_words, MentionFinder},
+    registry::Registry,
+    relation::StoryTime,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use syntax::annotation::{annotations, hide_annotations, AnnotationKind};
+use tree::{DocumentTree, NodeId};
+
+/// Name of the attribute an age is reported under. It is inferred from the birth, not stored.
+pub const AGE: &str = "age";
+/// Characters that end a sentence.
+const SENTENCE_ENDS: [char; 4_usize] = ['.', '!', '?', '\n'];
+/// Words that follow a number of years to state an age, such as `12 years old`.
+const AGE_AFTER: [&str; 3_usize] = [" years old", " year old", "-year-old"];
+/// Words that come before a number of years to state an age, such as `aged 12`.
+const AGE_BEFORE: &str = "aged ";
+
+/// A value an attribute takes from `since` on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeChange {
+    pub key: String,
+    pub value: String,
+    pub since: StoryTime,
+}
+
+/// A scene that states an attribute of an entity with a value it does not have at that point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contradiction {
+    pub entity: EntityId,
+    /// The scene and the byte range in its text of the stated value.
+    pub scene: NodeId,
+    pub range: Range<usize>,
+    pub key: String,
+    pub stated: String,
+    /// The value the attribute has at the date of the scene.
+    pub expected: String,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl AttributeChange {
+    pub fn new(key: &str, value: &str, since: StoryTime) -> Self {
+        Self {
+            key: key.to_string(),
+            value: value.to_string(),
+            since,
+        }
+    }
+}
+
+impl Contradiction {
+    /// Returns a sentence explaining the problem with the name of the entity in `registry`.
+    pub fn describe(&self, registry: &Registry) -> String {
+        let name = registry
+            .get(self.entity)
+            .map(|entity| entity.name.clone())
+            .unwrap_or_else(|| self.entity.to_string());
+        match self.key.as_str() {
+            AGE => format!(
+                "{name} is {} years old here, but would be {} by then.",
+                self.stated, self.expected
+            ),
+            key => format!(
+                "The {key} of {name} is {:?} here, but {:?} by then.",
+                self.stated, self.expected
+            ),
+        }
+    }
+}
+
+/// Returns the contradictions in the scenes of `tree` dated with `calendar`. A scene is dated as
+/// in [`Calendar::node_day`]. Only sentences that mention a single entity are checked, for
+/// values of attributes that change and for ages of characters with a birth. A value is only a
+/// contradiction if the sentence does not also hold the expected value. Fact annotations are
+/// checked against every attribute and the age.
+pub fn check_attributes(
+    registry: &Registry,
+    calendar: &Calendar,
+    tree: &DocumentTree,
+) -> Vec<Contradiction> {
+    let finder = MentionFinder::new(registry);
+    let mut found = Vec::new();
+
+    for scene in tree.scenes() {
+        let Some(node) = tree.node(scene) else {
+            continue;
+        };
+        let Some((_, time)) = calendar.node_day(node) else {
+            continue;
+        };
+        let text = node.text.as_str();
+        let mentions = finder.find(text);
+        found.extend(check_facts(registry, calendar, &finder, scene, text, time));
+        let text = hide_annotations(text);
+        let text = text.as_str();
+
+        for sentence in sentences(text) {
+            let mut entities = mentions
+                .iter()
+                .filter(|mention| sentence.start <= mention.range.start)
+                .filter(|mention| mention.range.end <= sentence.end)
+                .map(|mention| mention.entity)
+                .collect::<Vec<EntityId>>();
+            entities.sort();
+            entities.dedup();
+            let [entity] = entities.as_slice() else {
+                continue;
+            };
+            let (entity, Some(other)) = (*entity, registry.get(*entity)) else {
+                continue;
+            };
+            // Lowercasing may change the length of some letters, and so the byte ranges.
+            let words = match text[sentence.clone()].to_lowercase() {
+                lower if lower.len() == sentence.len() => lower,
+                _ => text[sentence.clone()].to_string(),
+            };
+            let at = |range: Range<usize>| sentence.start + range.start..sentence.start + range.end;
+
+            let mut keys = other
+                .changes
+                .iter()
+                .map(|change| change.key.as_str())
+                .collect::<Vec<&str>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let Some(expected) = other.attribute_at(key, time) else {
+                    continue;
+                };
+                let lower = expected.to_lowercase();
+                if !whole_words(&words, &lower).is_empty() {
+                    continue;
+                }
+                let stated = other
+                    .attribute_values(key)
+                    .into_iter()
+                    .filter(|value| value.to_lowercase() != lower)
+                    .find_map(|value| {
+                        let range = whole_words(&words, &value.to_lowercase()).pop()?;
+                        Some((value.to_string(), range))
+                    });
+                if let Some((stated, range)) = stated {
+                    found.push(Contradiction {
+                        entity,
+                        scene,
+                        range: at(range),
+                        key: key.to_string(),
+                        stated,
+                        expected: expected.to_string(),
+                    });
+                }
+            }
+
+            if other.kind != EntityKind::Character {
+                continue;
+            }
+            let Some(age) = other.age_at(calendar, time) else {
+                continue;
+            };
+            for (range, stated) in stated_ages(&words) {
+                if stated != age {
+                    found.push(Contradiction {
+                        entity,
+                        scene,
+                        range: at(range),
+                        key: AGE.to_string(),
+                        stated: stated.to_string(),
+                        expected: age.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Returns the contradictions of the fact annotations in `text`, the text of `scene` at `time`.
+fn check_facts(
+    registry: &Registry,
+    calendar: &Calendar,
+    finder: &MentionFinder,
+    scene: NodeId,
+    text: &str,
+    time: StoryTime,
+) -> Vec<Contradiction> {
+    annotations(text)
+        .into_iter()
+        .filter_map(|annotation| {
+            let AnnotationKind::Fact { entity, key, value } = annotation.kind else {
+                return None;
+            };
+            let entity = finder.entity_named(&entity)?;
+            let age = key.eq_ignore_ascii_case(AGE);
+            let expected = match age {
+                true => registry.get(entity)?.age_at(calendar, time)?.to_string(),
+                false => registry.get(entity)?.attribute_at(&key, time)?.to_string(),
+            };
+            if expected.to_lowercase() == value.to_lowercase() {
+                return None;
+            }
+            Some(Contradiction {
+                entity,
+                scene,
+                range: annotation.range,
+                key: match age {
+                    true => AGE.to_string(),
+                    false => key,
+                },
+                stated: value,
+                expected,
+            })
+        })
+        .collect()
+}
+
+/// Returns the byte range of every sentence of `text`.
+fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0_usize;
+    for (index, char) in text.char_indices() {
+        if SENTENCE_ENDS.contains(&char) {
+            ranges.push(start..index);
+            start = index + char.len_utf8();
+        }
+    }
+    ranges.push(start..text.len());
+    ranges.retain(|range| !text[range.clone()].trim().is_empty());
+    ranges
+}
+
+/// Returns the byte range and the value of every age written in `text` with digits, such as `12
+/// years old` or `aged 12`.
+fn stated_ages(text: &str) -> Vec<(Range<usize>, i64)> {
+    let mut ages = Vec::new();
+    let mut start = None;
+    for (index, char) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (char.is_ascii_digit(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(from)) => {
+                start = None;
+                let Ok(age) = text[from..index].parse::<i64>() else {
+                    continue;
+                };
+                let before = text[..from].ends_with(AGE_BEFORE);
+                let after = AGE_AFTER
+                    .iter()
+                    .any(|words| text[index..].starts_with(words));
+                if before || after {
+                    ages.push((from..index, age));
+                }
+            }
+            _ => {}
+        }
+    }
+    ages
+}
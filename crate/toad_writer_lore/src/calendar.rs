@@ -0,0 +1,650 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Calendar module.
+//!
+//! Calendars of invented settings with their own months, weekdays, leap years, eras and moons.
+//! Dates are turned into a linear count of days, the [`StoryTime`] used by timelines, where day
+//! zero is the first day of the first month of year one. Years before it count down to zero and
+//! below.
+
+use crate::relation::StoryTime;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use syntax::annotation::{annotations, AnnotationKind};
+use thiserror::Error;
+use tree::Node;
+
+/// Names of the phases of a moon, starting from the new moon.
+const PHASES: [&str; 8_usize] = [
+    "New",
+    "Waxing Crescent",
+    "First Quarter",
+    "Waxing Gibbous",
+    "Full",
+    "Waning Gibbous",
+    "Last Quarter",
+    "Waning Crescent",
+];
+/// Suffixes of ordinal day numbers such as `3rd`.
+const ORDINALS: [&str; 4_usize] = ["st", "nd", "rd", "th"];
+/// Words allowed between an ordinal and the month, as in `the 3rd of Frostmoon`.
+const OF: &str = "of";
+
+/// Errors that can occur while checking a [`Calendar`] or converting a date with it.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CalendarError {
+    #[error("A calendar needs a name.")]
+    EmptyName,
+    #[error("Calendar {0:?} has no months.")]
+    NoMonths(String),
+    #[error("Calendar {0:?} has no days outside of leap years.")]
+    EmptyYear(String),
+    #[error("Month {0:?} has no days.")]
+    EmptyMonth(String),
+    #[error("Moon {0:?} needs a cycle of at least one day.")]
+    EmptyCycle(String),
+    #[error("Leap years need to come every one or more years.")]
+    EmptyLeapRule,
+    #[error("Day {day} does not exist in month {month} of year {year}.")]
+    NoSuchDay { year: i64, month: usize, day: u32 },
+}
+
+/// A month and how many days it has.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Month {
+    pub name: String,
+    pub days: u32,
+    /// Days added to the month in leap years.
+    #[serde(default)]
+    pub leap_days: u32,
+}
+
+/// Which years are leap years: every `every` years, except every `except` years, unless it is
+/// also one of every `unless` years. Each is a multiple of the one before, and zero turns the
+/// exception off. The Gregorian rule is 4, 100 and 400.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeapRule {
+    pub every: u32,
+    #[serde(default)]
+    pub except: u32,
+    #[serde(default)]
+    pub unless: u32,
+}
+
+/// A named span of years such as an age or a reign.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Era {
+    pub name: String,
+    /// Short name written after years, such as `AR`.
+    #[serde(default)]
+    pub abbreviation: String,
+    /// The year one of the era counts from, as a year of the calendar.
+    pub start: i64,
+    /// Years of the era count down to its start, as years before Christ do.
+    #[serde(default)]
+    pub backward: bool,
+}
+
+/// A moon that goes through its phases every `cycle` days.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Moon {
+    pub name: String,
+    pub cycle: u32,
+    /// Days into the cycle on day zero, zero being a new moon.
+    #[serde(default)]
+    pub offset: u32,
+}
+
+/// A calendar of a setting. Plain values are kept before tables so that it can be written as
+/// TOML.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Calendar {
+    pub name: String,
+    /// Index in `weekdays` of the weekday of day zero.
+    #[serde(default)]
+    pub first_weekday: usize,
+    #[serde(default)]
+    pub weekdays: Vec<String>,
+    #[serde(default)]
+    pub leap: Option<LeapRule>,
+    pub months: Vec<Month>,
+    #[serde(default)]
+    pub eras: Vec<Era>,
+    #[serde(default)]
+    pub moons: Vec<Moon>,
+}
+
+/// A date of a [`Calendar`]. The year is counted by the calendar rather than an era, the month
+/// is an index and the day starts at one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    pub year: i64,
+    pub month: usize,
+    pub day: u32,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl LeapRule {
+    /// Returns true if `year` is a leap year.
+    pub fn is_leap(&self, year: i64) -> bool {
+        let divides = |every: u32| every > 0_u32 && year.rem_euclid(every as i64) == 0_i64;
+        let exception = divides(self.except) && !divides(self.unless);
+        divides(self.every) && !exception
+    }
+
+    /// Returns the number of leap years from year one up to `year`, negative for years before
+    /// one. The difference of two counts is the number of leap years between them.
+    fn count(&self, year: i64) -> i64 {
+        let every = |every: u32| match every {
+            0_u32 => 0_i64,
+            every => year.div_euclid(every as i64),
+        };
+        let except = match self.except {
+            0_u32 => 0_i64,
+            _ => every(self.except) - every(self.unless),
+        };
+        every(self.every) - except
+    }
+}
+
+impl Moon {
+    /// Returns the name of the phase of the moon on `day`.
+    pub fn phase(&self, day: StoryTime) -> &'static str {
+        let cycle = self.cycle.max(1_u32) as i64;
+        let into = (day + self.offset as i64).rem_euclid(cycle) as f64 / cycle as f64;
+        PHASES[(into * PHASES.len() as f64).round() as usize % PHASES.len()]
+    }
+}
+
+impl Calendar {
+    /// Returns an error if the calendar cannot count days.
+    pub fn validate(&self) -> Result<(), CalendarError> {
+        if self.name.trim().is_empty() {
+            return Err(CalendarError::EmptyName);
+        }
+        if self.months.is_empty() {
+            return Err(CalendarError::NoMonths(self.name.clone()));
+        }
+        // Months that only exist in leap years are fine.
+        let empty = self
+            .months
+            .iter()
+            .find(|month| month.days + month.leap_days == 0_u32);
+        if let Some(month) = empty {
+            return Err(CalendarError::EmptyMonth(month.name.clone()));
+        }
+        if self.months.iter().all(|month| month.days == 0_u32) {
+            return Err(CalendarError::EmptyYear(self.name.clone()));
+        }
+        if let Some(moon) = self.moons.iter().find(|moon| moon.cycle == 0_u32) {
+            return Err(CalendarError::EmptyCycle(moon.name.clone()));
+        }
+        if self.leap.as_ref().is_some_and(|leap| leap.every == 0_u32) {
+            return Err(CalendarError::EmptyLeapRule);
+        }
+        Ok(())
+    }
+
+    pub fn is_leap(&self, year: i64) -> bool {
+        self.leap.as_ref().is_some_and(|leap| leap.is_leap(year))
+    }
+
+    /// Returns the number of days of `month` in `year`.
+    pub fn month_length(&self, year: i64, month: usize) -> u32 {
+        self.months
+            .get(month)
+            .map_or(0_u32, |month| match self.is_leap(year) {
+                true => month.days + month.leap_days,
+                false => month.days,
+            })
+    }
+
+    /// Returns the number of days in `year`.
+    pub fn year_length(&self, year: i64) -> i64 {
+        (0_usize..self.months.len())
+            .map(|month| self.month_length(year, month) as i64)
+            .sum()
+    }
+
+    /// Returns the day that `date` falls on.
+    pub fn to_day(&self, date: CalendarDate) -> Result<StoryTime, CalendarError> {
+        if date.day == 0_u32 || date.day > self.month_length(date.year, date.month) {
+            return Err(CalendarError::NoSuchDay {
+                year: date.year,
+                month: date.month,
+                day: date.day,
+            });
+        }
+        let months = (0_usize..date.month)
+            .map(|month| self.month_length(date.year, month) as i64)
+            .sum::<i64>();
+        Ok(self.first_day(date.year) + months + date.day as i64 - 1_i64)
+    }
+
+    /// Returns the date of `day`.
+    pub fn from_day(&self, day: StoryTime) -> CalendarDate {
+        let common = self
+            .months
+            .iter()
+            .map(|month| month.days as i64)
+            .sum::<i64>();
+        let mut year = day.div_euclid(common.max(1_i64)) + 1_i64;
+        while self.first_day(year) > day {
+            year -= 1_i64;
+        }
+        while self.first_day(year + 1_i64) <= day {
+            year += 1_i64;
+        }
+
+        let mut left = day - self.first_day(year);
+        let mut month = 0_usize;
+        while month + 1_usize < self.months.len() && left >= self.month_length(year, month) as i64 {
+            left -= self.month_length(year, month) as i64;
+            month += 1_usize;
+        }
+        CalendarDate {
+            year,
+            month,
+            day: left as u32 + 1_u32,
+        }
+    }
+
+    /// Returns the number of whole years from `from` to `to`, such as an age.
+    pub fn years_between(&self, from: StoryTime, to: StoryTime) -> i64 {
+        let (from, to) = (self.from_day(from), self.from_day(to));
+        let years = to.year - from.year;
+        match (to.month, to.day) < (from.month, from.day) {
+            true => years - 1_i64,
+            false => years,
+        }
+    }
+
+    /// Returns the name of the weekday of `day`, if the calendar has weeks.
+    pub fn weekday(&self, day: StoryTime) -> Option<&str> {
+        let count = self.weekdays.len() as i64;
+        if count == 0_i64 {
+            return None;
+        }
+        let index = (day + self.first_weekday as i64).rem_euclid(count);
+        self.weekdays.get(index as usize).map(String::as_str)
+    }
+
+    /// Returns every moon with its phase on `day`.
+    pub fn moon_phases(&self, day: StoryTime) -> Vec<(&str, &'static str)> {
+        self.moons
+            .iter()
+            .map(|moon| (moon.name.as_str(), moon.phase(day)))
+            .collect()
+    }
+
+    /// Returns the year of the calendar that is `year` of `era`.
+    pub fn calendar_year(era: &Era, year: i64) -> i64 {
+        match era.backward {
+            true => era.start - year,
+            false => era.start + year - 1_i64,
+        }
+    }
+
+    /// Returns the era `year` belongs to and the year in it. Years count up from the latest era
+    /// that started by then, or down to the earliest backward era after them.
+    pub fn era_year(&self, year: i64) -> (Option<&Era>, i64) {
+        let forward = self
+            .eras
+            .iter()
+            .filter(|era| !era.backward && era.start <= year)
+            .max_by_key(|era| era.start);
+        if let Some(era) = forward {
+            return (Some(era), year - era.start + 1_i64);
+        }
+        let backward = self
+            .eras
+            .iter()
+            .filter(|era| era.backward && era.start > year)
+            .min_by_key(|era| era.start);
+        match backward {
+            Some(era) => (Some(era), era.start - year),
+            None => (None, year),
+        }
+    }
+
+    /// Returns `date` written out such as `3 Frostmoon 1021 AR`.
+    pub fn format(&self, date: CalendarDate) -> String {
+        let month = self
+            .months
+            .get(date.month)
+            .map(|month| month.name.as_str())
+            .unwrap_or_default();
+        match self.era_year(date.year) {
+            (Some(era), year) => {
+                let era = match era.abbreviation.is_empty() {
+                    true => era.name.as_str(),
+                    false => era.abbreviation.as_str(),
+                };
+                format!("{} {month} {year} {era}", date.day)
+            }
+            (None, year) => format!("{} {month} {year}", date.day),
+        }
+    }
+
+    /// Returns the date written in `text`, such as `3 Frostmoon 1021 AR` or `Frostmoon 3rd, 1021`,
+    /// if it holds a single date and nothing else.
+    pub fn parse(&self, text: &str) -> Option<CalendarDate> {
+        let text = text.trim();
+        match self.find_dates(text).as_slice() {
+            [(range, date)] if *range == (0_usize..text.len()) => Some(*date),
+            _ => None,
+        }
+    }
+
+    /// Returns the byte range and the date of every date written in `text`. A date has a day,
+    /// the name of a month and a year, in the order `3 Frostmoon 1021`, `the 3rd of Frostmoon
+    /// 1021` or `Frostmoon 3, 1021`. The year may be followed by an era, else it is a year of the
+    /// latest era.
+    pub fn find_dates(&self, text: &str) -> Vec<(Range<usize>, CalendarDate)> {
+        let words = words(text);
+        let gap = |before: usize, after: usize| {
+            text[words[before].end..words[after].start]
+                .chars()
+                .all(|char| char.is_whitespace() || char == ',')
+        };
+
+        let mut dates = Vec::new();
+        let mut index = 0_usize;
+        while index < words.len() {
+            let Some((month, length)) = self.month_at(text, &words, index) else {
+                index += 1_usize;
+                continue;
+            };
+            let last = index + length - 1_usize;
+
+            // The day comes before the month, maybe followed by `of`, or right after it.
+            let before = match index.checked_sub(1_usize) {
+                Some(of) if text[words[of].clone()].eq_ignore_ascii_case(OF) && of > 0_usize => {
+                    Some(of - 1_usize).filter(|day| gap(*day, of) && gap(of, index))
+                }
+                Some(day) => Some(day).filter(|day| gap(*day, index)),
+                None => None,
+            }
+            .and_then(|day| Some((day, day_number(&text[words[day].clone()])?)));
+            let after = Some(last + 1_usize)
+                .filter(|day| *day < words.len() && gap(last, *day))
+                .and_then(|day| Some((day, day_number(&text[words[day].clone()])?)));
+            let (start, day, year_at) = match (before, after) {
+                (Some((start, day)), _) => (start, day, last + 1_usize),
+                (None, Some((at, day))) => (index, day, at + 1_usize),
+                (None, None) => {
+                    index += length;
+                    continue;
+                }
+            };
+
+            let Some(year) = Some(year_at)
+                .filter(|year| *year < words.len() && gap(year - 1_usize, *year))
+                .and_then(|year| text[words[year].clone()].parse::<i64>().ok())
+            else {
+                index += length;
+                continue;
+            };
+            let era = Some(year_at + 1_usize)
+                .filter(|era| *era < words.len() && gap(year_at, *era))
+                .and_then(|era| Some((era, self.era_named(&text[words[era].clone()])?)));
+            let (end, year) = match era {
+                Some((end, era)) => (end, Self::calendar_year(era, year)),
+                None => match self
+                    .eras
+                    .iter()
+                    .filter(|era| !era.backward)
+                    .max_by_key(|era| era.start)
+                {
+                    Some(era) => (year_at, Self::calendar_year(era, year)),
+                    None => (year_at, year),
+                },
+            };
+
+            let date = CalendarDate { year, month, day };
+            if self.to_day(date).is_ok() {
+                dates.push((words[start].start..words[end].end, date));
+            }
+            index = end + 1_usize;
+        }
+        dates
+    }
+
+    /// Returns the byte range and the day count of the first valid date written in `text`.
+    pub fn find_day(&self, text: &str) -> Option<(Range<usize>, StoryTime)> {
+        self.find_dates(text)
+            .into_iter()
+            .find_map(|(range, date)| Some((range, self.to_day(date).ok()?)))
+    }
+
+    /// Returns the byte range and the day count of the date of the scene `text`. The first date
+    /// annotation wins, written as a date or a day count, else it is the first date written in
+    /// the scene.
+    pub fn scene_day(&self, text: &str) -> Option<(Range<usize>, StoryTime)> {
+        annotations(text)
+            .into_iter()
+            .find_map(|annotation| {
+                let AnnotationKind::Date(date) = annotation.kind else {
+                    return None;
+                };
+                Some((annotation.range, self.day_of(&date)?))
+            })
+            .or_else(|| self.find_day(text))
+    }
+
+    /// Returns the byte range and the day count of the date of `scene`. The date of its metadata
+    /// wins, placed at the start of the text, else it is dated as in [`Calendar::scene_day`].
+    pub fn node_day(&self, scene: &Node) -> Option<(Range<usize>, StoryTime)> {
+        match self.day_of(&scene.metadata.date) {
+            Some(day) => Some((0_usize..0_usize, day)),
+            None => self.scene_day(scene.text.as_str()),
+        }
+    }
+
+    /// Returns the day count of `date`, written as a date or a day count.
+    fn day_of(&self, date: &str) -> Option<StoryTime> {
+        match self.parse(date) {
+            Some(date) => self.to_day(date).ok(),
+            None => date.trim().parse::<StoryTime>().ok(),
+        }
+    }
+
+    /// Returns the day that `year` starts on.
+    fn first_day(&self, year: i64) -> StoryTime {
+        let common = self
+            .months
+            .iter()
+            .map(|month| month.days as i64)
+            .sum::<i64>();
+        let leap = self
+            .months
+            .iter()
+            .map(|month| month.leap_days as i64)
+            .sum::<i64>();
+        let leaps = self
+            .leap
+            .as_ref()
+            .map_or(0_i64, |rule| rule.count(year - 1_i64));
+        (year - 1_i64) * common + leaps * leap
+    }
+
+    /// Returns the month whose name starts at word `index` and the number of words it takes.
+    fn month_at(&self, text: &str, words: &[Range<usize>], index: usize) -> Option<(usize, usize)> {
+        self.months
+            .iter()
+            .enumerate()
+            .find_map(|(month, Month { name, .. })| {
+                let parts = name.split_whitespace().collect::<Vec<&str>>();
+                let found = words.get(index..index + parts.len())?;
+                let matches = !parts.is_empty()
+                    && found
+                        .iter()
+                        .zip(parts.iter())
+                        .all(|(word, part)| text[word.clone()].eq_ignore_ascii_case(part));
+                matches.then_some((month, parts.len()))
+            })
+    }
+
+    /// Returns the era called or abbreviated `word`.
+    fn era_named(&self, word: &str) -> Option<&Era> {
+        self.eras.iter().find(|era| {
+            era.abbreviation == word
+                || (!era.name.is_empty() && era.name.eq_ignore_ascii_case(word))
+        })
+    }
+}
+
+/// Returns the byte ranges of the runs of letters and digits in `text`.
+fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, char) in text.char_indices() {
+        match (char.is_alphanumeric(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(from)) => {
+                words.push(from..index);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        words.push(from..text.len());
+    }
+    words
+}
+
+/// Returns the day written as `word`, such as `3` or `3rd`.
+fn day_number(word: &str) -> Option<u32> {
+    let lower = word.to_lowercase();
+    let digits = ORDINALS
+        .iter()
+        .find_map(|suffix| lower.strip_suffix(suffix))
+        .unwrap_or(lower.as_str());
+    match digits.chars().all(|char| char.is_ascii_digit()) {
+        true => digits.parse().ok(),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONTHS: [(&str, u32); 12_usize] = [
+        ("January", 31_u32),
+        ("February", 28_u32),
+        ("March", 31_u32),
+        ("April", 30_u32),
+        ("May", 31_u32),
+        ("June", 30_u32),
+        ("July", 31_u32),
+        ("August", 31_u32),
+        ("September", 30_u32),
+        ("October", 31_u32),
+        ("November", 30_u32),
+        ("December", 31_u32),
+    ];
+
+    /// The Gregorian calendar with two eras that meet at year 1000.
+    fn gregorian() -> Calendar {
+        let era = |name: &str, abbreviation: &str, backward: bool| Era {
+            name: name.to_string(),
+            abbreviation: abbreviation.to_string(),
+            start: 1000_i64,
+            backward,
+        };
+        Calendar {
+            name: "Gregorian".to_string(),
+            first_weekday: 0_usize,
+            weekdays: Vec::new(),
+            leap: Some(LeapRule {
+                every: 4_u32,
+                except: 100_u32,
+                unless: 400_u32,
+            }),
+            months: MONTHS
+                .iter()
+                .map(|(name, days)| Month {
+                    name: name.to_string(),
+                    days: *days,
+                    leap_days: (*name == "February") as u32,
+                })
+                .collect(),
+            eras: vec![
+                era("After Reckoning", "AR", false),
+                era("Before Reckoning", "BR", true),
+            ],
+            moons: Vec::new(),
+        }
+    }
+
+    fn date(year: i64, month: usize, day: u32) -> CalendarDate {
+        CalendarDate { year, month, day }
+    }
+
+    #[test]
+    fn leap_years_follow_the_exceptions() {
+        let calendar = gregorian();
+        assert!(calendar.is_leap(2000_i64));
+        assert!(calendar.is_leap(2024_i64));
+        assert!(!calendar.is_leap(1900_i64));
+        assert!(!calendar.is_leap(2023_i64));
+        assert!(calendar.is_leap(-4_i64));
+        assert_eq!(calendar.year_length(2000_i64), 366_i64);
+        assert_eq!(calendar.year_length(1900_i64), 365_i64);
+        assert!(calendar.to_day(date(2000_i64, 1_usize, 29_u32)).is_ok());
+        assert!(calendar.to_day(date(1900_i64, 1_usize, 29_u32)).is_err());
+    }
+
+    #[test]
+    fn days_count_from_the_first_day_of_year_one() {
+        let calendar = gregorian();
+        assert_eq!(calendar.to_day(date(1_i64, 0_usize, 1_u32)), Ok(0_i64));
+        assert_eq!(
+            calendar.to_day(date(2000_i64, 0_usize, 1_u32)),
+            Ok(730_119_i64)
+        );
+        assert_eq!(calendar.to_day(date(0_i64, 11_usize, 31_u32)), Ok(-1_i64));
+        for day in (-800_i64..800_i64).map(|day| day * 457_i64) {
+            assert_eq!(calendar.to_day(calendar.from_day(day)), Ok(day));
+        }
+    }
+
+    #[test]
+    fn years_between_count_whole_years() {
+        let calendar = gregorian();
+        let day = |year: i64, day: u32| calendar.to_day(date(year, 1_usize, day)).unwrap();
+        let born = day(1980_i64, 29_u32);
+        assert_eq!(calendar.years_between(born, day(2000_i64, 28_u32)), 19_i64);
+        assert_eq!(calendar.years_between(born, day(2000_i64, 29_u32)), 20_i64);
+    }
+
+    #[test]
+    fn eras_count_up_from_their_start_or_down_to_it() {
+        let calendar = gregorian();
+        assert_eq!(calendar.era_year(1000_i64).1, 1_i64);
+        assert_eq!(calendar.era_year(999_i64).1, 1_i64);
+        assert_eq!(calendar.era_year(990_i64).0.unwrap().abbreviation, "BR");
+        assert_eq!(
+            calendar.format(date(1020_i64, 2_usize, 3_u32)),
+            "3 March 21 AR"
+        );
+        assert_eq!(
+            calendar.format(date(995_i64, 2_usize, 3_u32)),
+            "3 March 5 BR"
+        );
+    }
+
+    #[test]
+    fn dates_are_read_with_or_without_their_era() {
+        let calendar = gregorian();
+        let march = |year: i64| Some(date(year, 2_usize, 3_u32));
+        assert_eq!(calendar.parse("3 March 21 AR"), march(1020_i64));
+        assert_eq!(calendar.parse("3rd of March 5 BR"), march(995_i64));
+        assert_eq!(calendar.parse("March 3, 21"), march(1020_i64));
+        assert_eq!(calendar.parse("30 February 21"), None);
+    }
+}
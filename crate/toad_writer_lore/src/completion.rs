@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Completion module.
+//!
+//! Suggests the names and aliases of lore entities for the start of a word being typed. Entities
+//! completed recently come first, followed by those mentioned closest to the cursor.
+
+use crate::{entity::EntityId, index::MentionIndex, registry::Registry};
+
+/// Number of characters to type before names are suggested.
+pub const MIN_PREFIX: usize = 3_usize;
+/// Number of completions remembered to rank by recency.
+const RECENT: usize = 20_usize;
+
+/// A name or alias that completes the typed prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub entity: EntityId,
+    /// The name or alias to insert, spelled as in the lore.
+    pub text: String,
+    /// The canonical name of the entity, shown along with aliases.
+    pub name: String,
+}
+
+/// Suggests [`Completion`]s and remembers which were chosen.
+#[derive(Debug, Clone, Default)]
+pub struct Completer {
+    /// The entities completed, most recent first.
+    recent: Vec<EntityId>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Completer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns up to `limit` names and aliases of `registry` that start with `prefix`, ignoring
+    /// case. They are ranked by how recently their entity was completed and then by how close to
+    /// `line` it is mentioned in `index`. Nothing is suggested for prefixes shorter than
+    /// [`MIN_PREFIX`] or for a name that is already typed out.
+    pub fn complete(
+        &self,
+        registry: &Registry,
+        prefix: &str,
+        index: &MentionIndex,
+        line: usize,
+        limit: usize,
+    ) -> Vec<Completion> {
+        if prefix.chars().count() < MIN_PREFIX {
+            return Vec::new();
+        }
+        let lowercase = prefix.to_lowercase();
+
+        let mut completions = registry
+            .iter()
+            .flat_map(|entity| {
+                entity
+                    .names()
+                    .filter(|term| term.to_lowercase().starts_with(lowercase.as_str()))
+                    .filter(|term| *term != prefix)
+                    .map(|term| Completion {
+                        entity: entity.id(),
+                        text: term.to_string(),
+                        name: entity.name.clone(),
+                    })
+                    .collect::<Vec<Completion>>()
+            })
+            .collect::<Vec<Completion>>();
+
+        // Entities that were never completed or are never mentioned come last.
+        completions.sort_by_cached_key(|completion| {
+            let recent = self
+                .recent
+                .iter()
+                .position(|entity| *entity == completion.entity);
+            let distance = index.distance(completion.entity, line);
+            (
+                recent.unwrap_or(usize::MAX),
+                distance.unwrap_or(usize::MAX),
+                completion.text.clone(),
+            )
+        });
+        completions.truncate(limit);
+
+        completions
+    }
+
+    /// To remember that `entity` was completed.
+    pub fn accepted(&mut self, entity: EntityId) {
+        self.recent.retain(|recent| *recent != entity);
+        self.recent.insert(0_usize, entity);
+        self.recent.truncate(RECENT);
+    }
+}
+
+/// Returns the part of a name being typed at the end of `text`, such as `Kel-Do` in
+/// `sailed to Kel-Do`.
+pub fn typed_prefix(text: &str) -> &str {
+    let start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, character)| {
+            character.is_alphanumeric() || matches!(character, '-' | '\'' | '’')
+        })
+        .last()
+        .map_or(text.len(), |(position, _)| position);
+
+    text[start..].trim_start_matches(|character: char| !character.is_alphanumeric())
+}
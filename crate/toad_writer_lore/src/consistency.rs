@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Consistency module.
+//!
+//! Checks the relations of a [`Registry`] for ties that cannot hold, such as an entity that is its
+//! own ancestor, a place that lies within itself or a marriage that starts before one of the
+//! spouses was born. Births come from the [`crate::entity::BORN`] attribute.
+
+use crate::{
+    calendar::Calendar,
+    entity::EntityId,
+    registry::Registry,
+    relation::{Relation, RelationKind, StoryTime},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A problem found in the relations of a [`Registry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// Entities that are each a parent of the next, the last being a parent of the first.
+    ParentCycle(Vec<EntityId>),
+    /// Places that each lie within the next, the last lying within the first.
+    ContainmentCycle(Vec<EntityId>),
+    /// A parent born after their child.
+    ParentBornAfterChild { parent: EntityId, child: EntityId },
+    /// A relation of `from` that starts before `entity`, one of its two ends, was born.
+    BeforeBirth {
+        from: EntityId,
+        relation: Relation,
+        entity: EntityId,
+        born: StoryTime,
+    },
+    /// A relation of `from` that ends before it starts.
+    EndsBeforeStart { from: EntityId, relation: Relation },
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Inconsistency {
+    /// Returns the entities involved.
+    pub fn entities(&self) -> Vec<EntityId> {
+        match self {
+            Inconsistency::ParentCycle(cycle) | Inconsistency::ContainmentCycle(cycle) => {
+                cycle.clone()
+            }
+            Inconsistency::ParentBornAfterChild { parent, child } => vec![*parent, *child],
+            Inconsistency::BeforeBirth { from, relation, .. }
+            | Inconsistency::EndsBeforeStart { from, relation } => vec![*from, relation.to],
+        }
+    }
+
+    /// Returns a sentence explaining the problem with the names of the entities in `registry`.
+    pub fn describe(&self, registry: &Registry) -> String {
+        let name = |id: &EntityId| {
+            registry
+                .get(*id)
+                .map(|entity| entity.name.clone())
+                .unwrap_or_else(|| id.to_string())
+        };
+        match self {
+            Inconsistency::ParentCycle(cycle) => {
+                let mut names = cycle.iter().map(name).collect::<Vec<String>>();
+                names.push(name(&cycle[0_usize]));
+                format!("Parentage loops back on itself: {}.", names.join(" → "))
+            }
+            Inconsistency::ContainmentCycle(cycle) => {
+                let mut names = cycle.iter().map(name).collect::<Vec<String>>();
+                names.push(name(&cycle[0_usize]));
+                format!("Places lie within each other: {}.", names.join(" → "))
+            }
+            Inconsistency::ParentBornAfterChild { parent, child } => {
+                format!(
+                    "{} is born after their child {}.",
+                    name(parent),
+                    name(child)
+                )
+            }
+            Inconsistency::BeforeBirth {
+                from,
+                relation,
+                entity,
+                born,
+            } => format!(
+                "{} {} {} starts in {} but {} is born in {born}.",
+                name(from),
+                relation.kind.as_str().to_lowercase(),
+                name(&relation.to),
+                relation.since.unwrap_or_default(),
+                name(entity),
+            ),
+            Inconsistency::EndsBeforeStart { from, relation } => format!(
+                "{} {} {} ends before it starts.",
+                name(from),
+                relation.kind.as_str().to_lowercase(),
+                name(&relation.to),
+            ),
+        }
+    }
+}
+
+/// Returns every inconsistency in the relations of `registry`, loops first. Births are read with
+/// `calendar` as in [`crate::Entity::born`].
+pub fn check(registry: &Registry, calendar: Option<&Calendar>) -> Vec<Inconsistency> {
+    let mut found = cycles(registry, RelationKind::Parent)
+        .into_iter()
+        .map(Inconsistency::ParentCycle)
+        .chain(
+            cycles(registry, RelationKind::PartOf)
+                .into_iter()
+                .map(Inconsistency::ContainmentCycle),
+        )
+        .collect::<Vec<Inconsistency>>();
+    let born = |id: EntityId| registry.get(id).and_then(|entity| entity.born(calendar));
+
+    for (from, relation) in registry.relations() {
+        if let (Some(since), Some(until)) = (relation.since, relation.until) {
+            if until < since {
+                found.push(Inconsistency::EndsBeforeStart {
+                    from,
+                    relation: relation.clone(),
+                });
+            }
+        }
+
+        if relation.kind == RelationKind::Parent {
+            if let (Some(parent), Some(child)) = (born(from), born(relation.to)) {
+                if parent > child {
+                    found.push(Inconsistency::ParentBornAfterChild {
+                        parent: from,
+                        child: relation.to,
+                    });
+                }
+            }
+            // A parent is tied to their child from its birth, which is checked above.
+            continue;
+        }
+
+        let Some(since) = relation.since else {
+            continue;
+        };
+        for entity in [from, relation.to] {
+            match born(entity) {
+                Some(born) if since < born => found.push(Inconsistency::BeforeBirth {
+                    from,
+                    relation: relation.clone(),
+                    entity,
+                    born,
+                }),
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+/// Returns the loops in the relations of `kind`, each starting with its smallest id.
+fn cycles(registry: &Registry, kind: RelationKind) -> Vec<Vec<EntityId>> {
+    let mut children = BTreeMap::<EntityId, Vec<EntityId>>::new();
+    registry
+        .relations()
+        .filter(|(_, relation)| relation.kind == kind)
+        .for_each(|(from, relation)| children.entry(from).or_default().push(relation.to));
+
+    let mut cycles = BTreeSet::new();
+    let mut done = BTreeSet::new();
+    for parent in children.keys() {
+        find_cycles(*parent, &children, &mut Vec::new(), &mut done, &mut cycles);
+    }
+    cycles.into_iter().collect()
+}
+
+/// To walk the entities reached from `id` below `path`, adding the loops that lead back into
+/// `path`.
+/// Entities in `done` were walked already.
+fn find_cycles(
+    id: EntityId,
+    children: &BTreeMap<EntityId, Vec<EntityId>>,
+    path: &mut Vec<EntityId>,
+    done: &mut BTreeSet<EntityId>,
+    cycles: &mut BTreeSet<Vec<EntityId>>,
+) {
+    if let Some(start) = path.iter().position(|other| *other == id) {
+        let mut cycle = path[start..].to_vec();
+        let smallest = (0_usize..cycle.len())
+            .min_by_key(|index| cycle[*index])
+            .unwrap_or_default();
+        cycle.rotate_left(smallest);
+        cycles.insert(cycle);
+        return;
+    }
+    if done.contains(&id) {
+        return;
+    }
+
+    path.push(id);
+    for child in children.get(&id).into_iter().flatten() {
+        find_cycles(*child, children, path, done, cycles);
+    }
+    path.pop();
+    done.insert(id);
+}
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Entity module.
+//!
+//! The characters, places and other things that make up the lore of a story.
+
+use crate::attribute::AttributeChange;
+use crate::calendar::{Calendar, CalendarDate};
+use crate::relation::{Relation, StoryTime};
+use crate::timeline::parse_time;
+use crate::travel::Route;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Unique id of an [`Entity`] in its [`crate::Registry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EntityId(pub(crate) u32);
+
+/// The kind of thing an [`Entity`] stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityKind {
+    Character,
+    Location,
+    Item,
+    Organisation,
+    Event,
+}
+
+/// A single lore entry such as a character or a place.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entity {
+    pub(crate) id: EntityId,
+    pub kind: EntityKind,
+    /// Canonical name used when referring to the entity.
+    pub name: String,
+    /// Other names the entity goes by in the text.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    /// Ties to other entities, such as the children of a character.
+    #[serde(default)]
+    pub relations: Vec<Relation>,
+    /// Ways from a location to others, kept on one of the two ends.
+    #[serde(default)]
+    pub routes: Vec<Route>,
+    /// A picture of the entity such as a portrait or a map.
+    #[serde(default)]
+    pub image: Option<PathBuf>,
+    /// Free form facts about the entity such as `eyes = green`.
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    /// Values the attributes take later in the story, such as a new title.
+    #[serde(default)]
+    pub changes: Vec<AttributeChange>,
+}
+
+/// Attribute that holds when an entity was born or founded, as a date or a year.
+pub const BORN: &str = "born";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl EntityId {
+    /// Returns the id with the raw `value`, such as one passed through a signal.
+    pub fn from_value(value: u32) -> Self {
+        Self(value)
+    }
+
+    /// Returns the raw value of the id.
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Display for EntityId {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "@{}", self.0)
+    }
+}
+
+impl EntityKind {
+    /// Every kind in the order they are listed in the UI.
+    pub const ALL: [EntityKind; 5_usize] = [
+        EntityKind::Character,
+        EntityKind::Location,
+        EntityKind::Item,
+        EntityKind::Organisation,
+        EntityKind::Event,
+    ];
+
+    /// Returns the name of the kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityKind::Character => "Character",
+            EntityKind::Location => "Location",
+            EntityKind::Item => "Item",
+            EntityKind::Organisation => "Organisation",
+            EntityKind::Event => "Event",
+        }
+    }
+}
+
+impl Display for EntityKind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Entity {
+    pub(crate) fn new(id: EntityId, kind: EntityKind, name: &str) -> Self {
+        Self {
+            id,
+            kind,
+            name: name.to_string(),
+            aliases: Vec::new(),
+            description: String::new(),
+            relations: Vec::new(),
+            routes: Vec::new(),
+            image: None,
+            attributes: BTreeMap::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
+    /// Returns the name followed by every alias.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+
+    /// Returns when the entity was born or founded, from its [`BORN`] attribute: a date of
+    /// `calendar` or a year. With a calendar, a year is the first day of that year, so that it
+    /// compares with the dates of the story. Without one, the year itself is the time.
+    pub fn born(&self, calendar: Option<&Calendar>) -> Option<StoryTime> {
+        let text = self.attributes.get(BORN)?.trim();
+        match (text.parse::<i64>(), calendar) {
+            (Ok(year), Some(calendar)) => calendar
+                .to_day(CalendarDate {
+                    year,
+                    month: 0_usize,
+                    day: 1_u32,
+                })
+                .ok(),
+            (Ok(year), None) => Some(year),
+            (Err(_), calendar) => parse_time(text, calendar),
+        }
+    }
+
+    /// Returns the value of the attribute `key` at `time`: the last change made by then, or the
+    /// value it starts with.
+    pub fn attribute_at(&self, key: &str, time: StoryTime) -> Option<&str> {
+        self.changes
+            .iter()
+            .filter(|change| change.key == key && change.since <= time)
+            .max_by_key(|change| change.since)
+            .map(|change| change.value.as_str())
+            .or_else(|| self.attributes.get(key).map(String::as_str))
+    }
+
+    /// Returns every value the attribute `key` takes, the one it starts with first.
+    pub fn attribute_values(&self, key: &str) -> Vec<&str> {
+        let mut changes = self
+            .changes
+            .iter()
+            .filter(|change| change.key == key)
+            .collect::<Vec<&AttributeChange>>();
+        changes.sort_by_key(|change| change.since);
+        self.attributes
+            .get(key)
+            .into_iter()
+            .map(String::as_str)
+            .chain(changes.into_iter().map(|change| change.value.as_str()))
+            .collect()
+    }
+
+    /// Returns the age in years of the entity at `time`, from its [`BORN`] attribute read as in
+    /// [`Entity::born`].
+    pub fn age_at(&self, calendar: &Calendar, time: StoryTime) -> Option<i64> {
+        let born = self.born(Some(calendar))?;
+        (born <= time).then(|| calendar.years_between(born, time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::Month;
+
+    fn calendar() -> Calendar {
+        let month = |name: &str| Month {
+            name: name.to_string(),
+            days: 30_u32,
+            leap_days: 0_u32,
+        };
+        Calendar {
+            name: "Reckoning".to_string(),
+            first_weekday: 0_usize,
+            weekdays: Vec::new(),
+            leap: None,
+            months: vec![month("Thaw"), month("Bloom"), month("Frost")],
+            eras: Vec::new(),
+            moons: Vec::new(),
+        }
+    }
+
+    fn born(value: &str) -> Entity {
+        let mut entity = Entity::new(EntityId(1_u32), EntityKind::Character, "Mara");
+        entity
+            .attributes
+            .insert(BORN.to_string(), value.to_string());
+        entity
+    }
+
+    #[test]
+    fn years_are_births_in_the_calendar() {
+        let calendar = calendar();
+        let day = parse_time("1 Thaw 1020", Some(&calendar)).unwrap();
+        assert_eq!(born("1000").age_at(&calendar, day), Some(20_i64));
+        assert_eq!(born("1000").born(None), Some(1000_i64));
+    }
+
+    #[test]
+    fn dates_are_births_everywhere() {
+        let calendar = calendar();
+        let day = parse_time("1 Thaw 1020", Some(&calendar)).unwrap();
+        assert_eq!(born("2 Thaw 1000").age_at(&calendar, day), Some(19_i64));
+        assert_eq!(
+            born("2 Thaw 1000").born(Some(&calendar)),
+            parse_time("2 Thaw 1000", Some(&calendar))
+        );
+        assert_eq!(born("2 Thaw 1000").born(None), None);
+    }
+}
@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Graph module.
+//!
+//! The [`RelationGraph`] lays out related entities in rows so that the relations can be drawn.
+//! Characters are placed below their parents with their spouses next to them, and every other
+//! kind of entity is kept in the last row.
+
+use crate::{
+    entity::{EntityId, EntityKind},
+    registry::Registry,
+    relation::{RelationKind, StoryTime},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A relation shown in a [`RelationGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub from: EntityId,
+    pub to: EntityId,
+    pub kind: RelationKind,
+}
+
+/// The entities tied by relations, in rows, and the relations between them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelationGraph {
+    /// Rows of entities from the oldest generation down, the last row holding the entities that
+    /// are not characters.
+    pub rows: Vec<Vec<EntityId>>,
+    pub edges: Vec<Edge>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl RelationGraph {
+    /// Returns the graph of the relations of `registry`. Only relations of `kind` are shown if it
+    /// is given, and only those that hold `at` a time if it is given.
+    pub fn new(registry: &Registry, kind: Option<RelationKind>, at: Option<StoryTime>) -> Self {
+        let edges = registry
+            .relations()
+            .filter(|(_, relation)| kind.is_none_or(|kind| relation.kind == kind))
+            .filter(|(_, relation)| at.is_none_or(|at| relation.holds_at(at)))
+            .map(|(from, relation)| Edge {
+                from,
+                to: relation.to,
+                kind: relation.kind,
+            })
+            .collect::<Vec<Edge>>();
+        let shown = edges
+            .iter()
+            .flat_map(|edge| [edge.from, edge.to])
+            .collect::<BTreeSet<EntityId>>();
+        let is_character = |id: &EntityId| {
+            registry
+                .get(*id)
+                .is_some_and(|entity| entity.kind == EntityKind::Character)
+        };
+
+        let generations = Self::generations(&edges, shown.iter().filter(|id| is_character(id)));
+        let mut rows = vec![
+            Vec::new();
+            generations
+                .values()
+                .max()
+                .map_or(0_usize, |max| max + 1_usize)
+        ];
+        for (id, generation) in generations.iter() {
+            rows[*generation].push(*id);
+        }
+        rows.retain(|row| !row.is_empty());
+        let others = shown
+            .iter()
+            .filter(|id| !is_character(id))
+            .copied()
+            .collect::<Vec<EntityId>>();
+        if !others.is_empty() {
+            rows.push(others);
+        }
+
+        let mut graph = Self { rows, edges };
+        graph.order_rows(registry);
+        graph
+    }
+
+    /// Returns the row and the position in it of `id`.
+    pub fn position(&self, id: EntityId) -> Option<(usize, usize)> {
+        self.rows.iter().enumerate().find_map(|(row, ids)| {
+            ids.iter()
+                .position(|other| *other == id)
+                .map(|column| (row, column))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Returns the generation of every one of `characters`, one below their lowest parent.
+    /// Characters without parents take the generation of a spouse or sibling.
+    fn generations<'a>(
+        edges: &[Edge],
+        characters: impl Iterator<Item = &'a EntityId>,
+    ) -> BTreeMap<EntityId, usize> {
+        let mut generations = characters
+            .map(|id| (*id, 0_usize))
+            .collect::<BTreeMap<EntityId, usize>>();
+        let parentage = edges
+            .iter()
+            .filter(|edge| edge.kind == RelationKind::Parent)
+            .filter(|edge| {
+                generations.contains_key(&edge.from) && generations.contains_key(&edge.to)
+            })
+            .collect::<Vec<&Edge>>();
+
+        Self::deepen(&parentage, &mut generations);
+
+        let children = parentage
+            .iter()
+            .map(|edge| edge.to)
+            .collect::<BTreeSet<EntityId>>();
+        for edge in edges {
+            if !matches!(edge.kind, RelationKind::Spouse | RelationKind::Sibling) {
+                continue;
+            }
+            let (Some(from), Some(to)) = (generations.get(&edge.from), generations.get(&edge.to))
+            else {
+                continue;
+            };
+            let generation = *from.max(to);
+            for id in [edge.from, edge.to] {
+                if !children.contains(&id) {
+                    generations.insert(id, generation);
+                }
+            }
+        }
+        // Moving a spouse down can move their children too.
+        Self::deepen(&parentage, &mut generations);
+        generations
+    }
+
+    /// To move every child of `parentage` below its parents in `generations`, parents first.
+    /// Children in a loop of parentage are only moved below the parents outside of it.
+    fn deepen(parentage: &[&Edge], generations: &mut BTreeMap<EntityId, usize>) {
+        let mut parents = BTreeMap::<EntityId, usize>::new();
+        parentage
+            .iter()
+            .for_each(|edge| *parents.entry(edge.to).or_default() += 1_usize);
+        let mut ready = generations
+            .keys()
+            .filter(|id| !parents.contains_key(id))
+            .copied()
+            .collect::<Vec<EntityId>>();
+
+        while let Some(parent) = ready.pop() {
+            for edge in parentage.iter().filter(|edge| edge.from == parent) {
+                let below = generations[&parent] + 1_usize;
+                let child = generations.entry(edge.to).or_default();
+                *child = (*child).max(below);
+
+                let left = parents.entry(edge.to).or_default();
+                *left -= 1_usize;
+                if *left == 0_usize {
+                    ready.push(edge.to);
+                }
+            }
+        }
+    }
+
+    /// To order every row by the mean position of the parents in the rows above, then by name,
+    /// and move spouses next to each other.
+    fn order_rows(&mut self, registry: &Registry) {
+        let name = |id: &EntityId| {
+            registry
+                .get(*id)
+                .map(|entity| entity.name.clone())
+                .unwrap_or_default()
+        };
+
+        for index in 0_usize..self.rows.len() {
+            let placed = self.rows[..index]
+                .iter()
+                .flat_map(|row| row.iter().enumerate().map(|(column, id)| (*id, column)))
+                .collect::<BTreeMap<EntityId, usize>>();
+            let parents_at = |id: &EntityId| {
+                let columns = self
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.kind == RelationKind::Parent && edge.to == *id)
+                    .filter_map(|edge| placed.get(&edge.from))
+                    .collect::<Vec<&usize>>();
+                match columns.is_empty() {
+                    true => usize::MAX,
+                    false => columns.iter().copied().sum::<usize>() * 100_usize / columns.len(),
+                }
+            };
+            let mut row = self.rows[index]
+                .iter()
+                .map(|id| ((parents_at(id), name(id)), *id))
+                .collect::<Vec<_>>();
+            row.sort();
+
+            let mut ordered = Vec::new();
+            for (_, id) in row.iter() {
+                if ordered.contains(id) {
+                    continue;
+                }
+                ordered.push(*id);
+                let spouses = self
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.kind == RelationKind::Spouse)
+                    .filter_map(|edge| match (edge.from, edge.to) {
+                        (from, to) if from == *id => Some(to),
+                        (from, to) if to == *id => Some(from),
+                        _ => None,
+                    });
+                for spouse in spouses {
+                    if self.rows[index].contains(&spouse) && !ordered.contains(&spouse) {
+                        ordered.push(spouse);
+                    }
+                }
+            }
+            self.rows[index] = ordered;
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Index module.
+//!
+//! The [`MentionIndex`] that keeps the mentions and unknown names of every line of a text while
+//! it is typed. Only the edited lines are searched again so that the cost of an edit does not
+//! grow with the size of the text.
+
+use crate::{
+    entity::EntityId,
+    mention::{Mention, MentionFinder},
+};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use syntax::segment::lines;
+
+/// What was recognised in a single line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineMentions {
+    /// Mentions of known entities.
+    pub mentions: Vec<Mention>,
+    /// Byte ranges of capitalised names that are not known.
+    pub unknown: Vec<Range<usize>>,
+}
+
+/// The [`LineMentions`] of every line of a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MentionIndex {
+    lines: Vec<LineMentions>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl LineMentions {
+    /// Returns what `finder` recognises in `line`.
+    pub fn new(finder: &MentionFinder, line: &str) -> Self {
+        Self {
+            mentions: finder.find(line),
+            unknown: finder.unknown_names(line),
+        }
+    }
+}
+
+impl MentionIndex {
+    /// Searches every line of `text` with `finder`.
+    pub fn new(finder: &MentionFinder, text: &str) -> Self {
+        let lines = lines(text)
+            .map(|line| LineMentions::new(finder, line))
+            .collect::<Vec<LineMentions>>();
+
+        Self { lines }
+    }
+
+    /// Returns what was recognised in `line`.
+    pub fn line(&self, line: usize) -> Option<&LineMentions> {
+        self.lines.get(line)
+    }
+
+    /// Returns the number of lines in the text.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Replaces the `removed` lines starting at `first` with `lines` as searched by `finder`.
+    pub fn splice(&mut self, finder: &MentionFinder, first: usize, removed: usize, lines: &[&str]) {
+        let first = first.min(self.lines.len());
+        let last = (first + removed).min(self.lines.len());
+        self.lines.splice(
+            first..last,
+            lines.iter().map(|line| LineMentions::new(finder, line)),
+        );
+    }
+
+    /// Returns how many lines away from `line` the closest mention of `entity` is.
+    pub fn distance(&self, entity: EntityId, line: usize) -> Option<usize> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, found)| {
+                found
+                    .mentions
+                    .iter()
+                    .any(|mention| mention.entity == entity)
+            })
+            .map(|(index, _)| index.abs_diff(line))
+            .min()
+    }
+
+    /// Returns the number of mentions of every entity referenced in the text.
+    pub fn references(&self) -> BTreeMap<EntityId, usize> {
+        let mut references = BTreeMap::new();
+        self.lines
+            .iter()
+            .flat_map(|line| line.mentions.iter())
+            .for_each(|mention| *references.entry(mention.entity).or_insert(0_usize) += 1_usize);
+
+        references
+    }
+}
+
+impl Default for MentionIndex {
+    fn default() -> Self {
+        Self::new(&MentionFinder::default(), "")
+    }
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Library for handling the lore of Toad Writer projects. Holds the characters, places and other
+//! entities of a story and finds where they are mentioned in the document tree.
+
+mod appearance;
+mod attribute;
+mod calendar;
+mod completion;
+mod consistency;
+mod entity;
+mod graph;
+mod index;
+mod mention;
+mod merge;
+mod registry;
+mod relation;
+mod rename;
+mod series;
+mod timeline;
+mod travel;
+
+pub use appearance::{AppearanceReport, Appearances};
+pub use attribute::{check_attributes, AttributeChange, Contradiction, AGE};
+pub use calendar::{Calendar, CalendarDate, CalendarError, Era, LeapRule, Month, Moon};
+pub use completion::{typed_prefix, Completer, Completion, MIN_PREFIX};
+pub use consistency::{check, Inconsistency};
+pub use entity::{Entity, EntityId, EntityKind, BORN};
+pub use graph::{Edge, RelationGraph};
+pub use index::{LineMentions, MentionIndex};
+pub use mention::{whole_words, Mention, MentionFinder};
+pub use merge::{Conflict, Incoming, MergeReport};
+pub use registry::{LoreError, Registry};
+pub use relation::{Relation, RelationKind, StoryTime};
+pub use rename::{RenamePlan, RenamePreview};
+pub use series::{AbsorbReport, EntityOverride, OverrideField};
+pub use timeline::{parse_time, Timeline, TimelineEvent, TimelineSource, DATE};
+pub use travel::{check_travel, Journey, Route, TravelMap, TravelMode};
@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Mention module.
+//!
+//! To find where the names and aliases of lore entities are mentioned in text. Names only match
+//! as whole words so that `Ann` is not found in `Annabel`, but common English inflections such as
+//! possessives are still counted as a mention. Reference annotations such as `{{Mara|the old
+//! woman}}` mention their entity with any words.
+
+use crate::{entity::EntityId, registry::Registry};
+use std::ops::Range;
+use syntax::{
+    annotation::{annotations, Annotation, AnnotationKind},
+    dialogue::NOT_NAMES,
+    segment::word_ranges,
+};
+
+/// Suffixes that can follow a name and still be a mention of it. Longest first.
+const INFLECTIONS: [&str; 6_usize] = ["'s", "’s", "es", "s", "'", "’"];
+/// Possessive endings dropped from unknown names.
+const POSSESSIVES: [&str; 4_usize] = ["'s", "’s", "'", "’"];
+/// Characters after which a capitalised word may only be capitalised because it starts a
+/// sentence or a quote.
+const SENTENCE_BREAKS: [char; 10_usize] = ['.', '!', '?', ':', '"', '“', '‘', '—', '(', '['];
+/// What may separate the words of a single name such as `Old Harbour` or `Kel-Dorath`.
+const NAME_JOINERS: [&str; 2_usize] = ["\x20", "-"];
+
+/// A single mention of an entity in text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mention {
+    pub entity: EntityId,
+    /// The name or alias that was matched, or the words of a reference annotation.
+    pub term: String,
+    /// Byte range of the name in the text, without the inflection.
+    pub range: Range<usize>,
+    /// The inflection that follows the name such as `'s`. Empty if there is none.
+    pub suffix: String,
+}
+
+/// Finds mentions of every name and alias in a [`Registry`].
+#[derive(Debug, Clone, Default)]
+pub struct MentionFinder {
+    /// Every known term with its entity, longest first so that `Mara Venn` wins over `Mara`.
+    terms: Vec<(String, EntityId)>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Mention {
+    /// Returns the byte range of the mention including its inflection.
+    pub fn full_range(&self) -> Range<usize> {
+        self.range.start..self.range.end + self.suffix.len()
+    }
+}
+
+impl MentionFinder {
+    /// Returns a finder for every name and alias in `registry`.
+    pub fn new(registry: &Registry) -> Self {
+        let mut terms = registry
+            .iter()
+            .flat_map(|entity| {
+                entity
+                    .names()
+                    .filter(|name| !name.trim().is_empty())
+                    .map(|name| (name.to_string(), entity.id()))
+                    .collect::<Vec<(String, EntityId)>>()
+            })
+            .collect::<Vec<(String, EntityId)>>();
+        terms.sort_by(|(lhs, _), (rhs, _)| rhs.len().cmp(&lhs.len()).then(lhs.cmp(rhs)));
+        terms.dedup_by(|(lhs, _), (rhs, _)| lhs == rhs);
+
+        Self { terms }
+    }
+
+    /// Returns every mention in `text` in the order they appear. Mentions never overlap; the
+    /// longest name wins where several start at the same place. Names are not looked for inside
+    /// annotations, but the words of a reference to a known entity are a mention of it.
+    pub fn find(&self, text: &str) -> Vec<Mention> {
+        let found = annotations(text);
+        let prose = blank(text, &found);
+        let mut mentions = Vec::new();
+        let mut position = 0_usize;
+
+        while position < prose.len() {
+            if !is_word_start(&prose, position) {
+                position += next_char_len(&prose, position);
+                continue;
+            }
+
+            match self.mention_at(&prose, position) {
+                Some(mention) => {
+                    position = mention.full_range().end;
+                    mentions.push(mention);
+                }
+                None => position += next_char_len(&prose, position),
+            }
+        }
+
+        mentions.extend(found.into_iter().filter_map(|annotation| {
+            let AnnotationKind::Reference { entity, shown } = annotation.kind else {
+                return None;
+            };
+            Some(Mention {
+                entity: self.entity_named(&entity)?,
+                term: text[shown.clone()].to_string(),
+                range: shown,
+                suffix: String::new(),
+            })
+        }));
+        mentions.sort_by_key(|mention| mention.range.start);
+        mentions
+    }
+
+    /// Returns the entity with the name or alias `name`, ignoring case.
+    pub fn entity_named(&self, name: &str) -> Option<EntityId> {
+        let name = name.trim().to_lowercase();
+        self.terms
+            .iter()
+            .find(|(term, _)| term.to_lowercase() == name)
+            .map(|(_, entity)| *entity)
+    }
+
+    /// Returns the mention that starts at `position` if there is one.
+    fn mention_at(&self, text: &str, position: usize) -> Option<Mention> {
+        let rest = &text[position..];
+        self.terms.iter().find_map(|(term, entity)| {
+            if !rest.starts_with(term.as_str()) {
+                return None;
+            }
+            let end = position + term.len();
+            let suffix = inflection_at(text, end)?;
+
+            Some(Mention {
+                entity: *entity,
+                term: term.clone(),
+                range: position..end,
+                suffix: suffix.to_string(),
+            })
+        })
+    }
+
+    /// Returns the byte ranges of capitalised names in `text` that are not known, such as
+    /// `Old Harbour` in `they sailed to Old Harbour's docks`. Words are left out if they may only
+    /// be capitalised because they start a sentence, along with acronyms and common words such
+    /// as months. Annotations are left out as a whole.
+    pub fn unknown_names(&self, text: &str) -> Vec<Range<usize>> {
+        let known = self.find(text);
+        let text = blank(text, &annotations(text));
+        let text = text.as_str();
+        let mut names = Vec::new();
+        let mut run: Option<Range<usize>> = None;
+
+        for word in word_ranges(text) {
+            let name_like = is_name_like(&text[word.clone()]);
+            let joined = run
+                .as_ref()
+                .is_some_and(|run| NAME_JOINERS.contains(&&text[run.end..word.start]));
+
+            match run.as_mut() {
+                Some(run) if name_like && joined => run.end = word.end,
+                _ => {
+                    names.extend(run.take());
+                    if name_like && !starts_sentence(text, word.start) {
+                        run = Some(word);
+                    }
+                }
+            }
+        }
+        names.extend(run);
+
+        names
+            .into_iter()
+            .map(|name| strip_possessive(text, name))
+            .filter(|name| {
+                known.iter().all(|mention| {
+                    let mention = mention.full_range();
+                    mention.end <= name.start || name.end <= mention.start
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns the byte ranges where `word` appears in `text` as a whole word.
+pub fn whole_words(text: &str, word: &str) -> Vec<Range<usize>> {
+    if word.is_empty() {
+        return Vec::new();
+    }
+
+    text.match_indices(word)
+        .map(|(start, _)| start..start + word.len())
+        .filter(|range| is_word_start(text, range.start) && is_word_end(text, range.end))
+        .collect()
+}
+
+/// Returns `text` with every annotation of `found` replaced by spaces, so that byte ranges in it
+/// are ranges in `text` as well.
+fn blank(text: &str, found: &[Annotation]) -> String {
+    let mut blank = text.to_string();
+    for annotation in found.iter().rev() {
+        let range = annotation.range.clone();
+        blank.replace_range(range.clone(), " ".repeat(range.len()).as_str());
+    }
+    blank
+}
+
+/// Returns the inflection at `position` that ends on a word boundary, an empty string if there
+/// is a word boundary without one, or [`None`] if the word goes on.
+fn inflection_at(text: &str, position: usize) -> Option<&'static str> {
+    INFLECTIONS
+        .iter()
+        .find(|suffix| {
+            text[position..].starts_with(**suffix) && is_word_end(text, position + suffix.len())
+        })
+        .copied()
+        .or_else(|| is_word_end(text, position).then_some(""))
+}
+
+/// Whether `word` looks like part of a name: capitalised, not an acronym and not a common word.
+fn is_name_like(word: &str) -> bool {
+    let capitalised = word.chars().next().is_some_and(char::is_uppercase);
+    let acronym = word
+        .chars()
+        .filter(|character| character.is_alphabetic())
+        .count()
+        > 1_usize
+        && !word.chars().any(char::is_lowercase);
+
+    capitalised && !acronym && !NOT_NAMES.contains(&word.replace('’', "'").as_str())
+}
+
+/// Whether the word at `position` is the first of a sentence, a quote or the text.
+fn starts_sentence(text: &str, position: usize) -> bool {
+    text[..position]
+        .chars()
+        .rev()
+        .find(|character| !character.is_whitespace())
+        .is_none_or(|character| SENTENCE_BREAKS.contains(&character))
+}
+
+/// Returns `name` without a possessive ending.
+fn strip_possessive(text: &str, name: Range<usize>) -> Range<usize> {
+    let word = &text[name.clone()];
+    POSSESSIVES
+        .iter()
+        .find(|suffix| word.ends_with(**suffix) && word.len() > suffix.len())
+        .map_or(name.clone(), |suffix| name.start..name.end - suffix.len())
+}
+
+#[inline]
+fn is_word_start(text: &str, position: usize) -> bool {
+    !text[..position]
+        .chars()
+        .next_back()
+        .is_some_and(char::is_alphanumeric)
+}
+
+#[inline]
+fn is_word_end(text: &str, position: usize) -> bool {
+    !text[position..]
+        .chars()
+        .next()
+        .is_some_and(char::is_alphanumeric)
+}
+
+#[inline]
+fn next_char_len(text: &str, position: usize) -> usize {
+    text[position..]
+        .chars()
+        .next()
+        .map_or(1_usize, char::len_utf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unknown(text: &str) -> Vec<&str> {
+        MentionFinder::new(&Registry::new())
+            .unknown_names(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn unknown_names_skip_common_capitalised_words() {
+        assert_eq!(
+            unknown("On Monday she met Mr Kel-Dorath’s sister in Old Harbour."),
+            vec!["Kel-Dorath", "Old Harbour"]
+        );
+    }
+}
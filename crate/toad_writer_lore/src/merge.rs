@@ -0,0 +1,365 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Merge module.
+//!
+//! Brings entities read from another file into a [`Registry`]. An [`Incoming`] entity is matched
+//! by its id if the entity with that id has the same kind and name, else by a name of the same
+//! kind. Matched entities only gain what they lack: a value that differs from the one already
+//! there is reported as a [`Conflict`] and left as it is.
+
+use crate::{
+    attribute::AttributeChange,
+    entity::{Entity, EntityId, EntityKind},
+    registry::Registry,
+};
+use std::collections::BTreeMap;
+
+/// How the span of a relation that always holds is reported.
+const ALWAYS: &str = "always";
+
+/// An entity read from another file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incoming {
+    /// The id of the entity in the file it comes from, which relations and routes refer to.
+    pub id: Option<EntityId>,
+    /// The entity, whose own id is not used.
+    pub entity: Entity,
+}
+
+/// A value of an entity that differs from the value read for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub entity: EntityId,
+    /// What the value is, such as `description` or the key of an attribute.
+    pub field: String,
+    pub current: String,
+    pub incoming: String,
+}
+
+/// What a merge changed, and what it left alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub added: Vec<EntityId>,
+    /// Entities that were matched and gained something.
+    pub updated: Vec<EntityId>,
+    pub conflicts: Vec<Conflict>,
+    /// Entities with a relation or route to an id of the file that no entity has, and that id.
+    pub unresolved: Vec<(EntityId, EntityId)>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Incoming {
+    /// Returns an entity of `kind` called `name` with the id `id` in its file, if it has one.
+    pub fn new(id: Option<EntityId>, kind: EntityKind, name: &str) -> Self {
+        Self {
+            id,
+            entity: Entity::new(EntityId(u32::MAX), kind, name.trim()),
+        }
+    }
+}
+
+impl From<Entity> for Incoming {
+    fn from(entity: Entity) -> Self {
+        Self {
+            id: Some(entity.id),
+            entity,
+        }
+    }
+}
+
+impl Conflict {
+    /// Returns a sentence explaining the conflict with the name of the entity in `registry`.
+    pub fn describe(&self, registry: &Registry) -> String {
+        let name = registry
+            .get(self.entity)
+            .map(|entity| entity.name.clone())
+            .unwrap_or_else(|| self.entity.to_string());
+        format!(
+            "{name}: kept the {} {:?} over {:?} from the file.",
+            self.field, self.current, self.incoming
+        )
+    }
+}
+
+impl MergeReport {
+    /// Returns true if the merge changed nothing and found nothing to report.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.conflicts.is_empty()
+            && self.unresolved.is_empty()
+    }
+}
+
+impl Registry {
+    /// Merges `incoming` into the registry and returns what changed. Relations and routes are
+    /// added once every entity is matched, with their ids of the file turned into ids of the
+    /// registry.
+    pub fn merge(&mut self, incoming: Vec<Incoming>) -> MergeReport {
+        let mut report = MergeReport::default();
+        let mut ids = BTreeMap::<EntityId, EntityId>::new();
+        let mut targets = Vec::new();
+
+        for item in incoming.iter() {
+            let id = match self.matching(item) {
+                Some(id) => id,
+                None => match self.add(item.entity.kind, item.entity.name.as_str()) {
+                    Ok(id) => {
+                        report.added.push(id);
+                        id
+                    }
+                    Err(_) => continue,
+                },
+            };
+            if let Some(file) = item.id {
+                ids.insert(file, id);
+            }
+            targets.push((id, item));
+            if let Some(entity) = self.get_mut(id) {
+                let before = entity.clone();
+                merge_fields(entity, &item.entity, &mut report.conflicts);
+                if *entity != before {
+                    report.updated.push(id);
+                }
+            }
+        }
+
+        for (id, item) in targets {
+            self.merge_links(id, &item.entity, &ids, &mut report);
+        }
+        report.updated.retain(|id| !report.added.contains(id));
+        report.updated.sort();
+        report.updated.dedup();
+        report
+    }
+
+    /// Returns the entity `item` stands for: the one with its id if the kind and a name agree,
+    /// else the first with its name and kind.
+    fn matching(&self, item: &Incoming) -> Option<EntityId> {
+        let named = |entity: &&Entity| {
+            entity.kind == item.entity.kind
+                && entity
+                    .names()
+                    .any(|name| name.eq_ignore_ascii_case(item.entity.name.as_str()))
+        };
+        item.id
+            .and_then(|id| self.get(id))
+            .filter(named)
+            .or_else(|| self.iter().find(named))
+            .map(Entity::id)
+    }
+
+    /// To add the relations, routes and changes of `incoming` to the entity `id`, with the ids of
+    /// the file in `ids`.
+    fn merge_links(
+        &mut self,
+        id: EntityId,
+        incoming: &Entity,
+        ids: &BTreeMap<EntityId, EntityId>,
+        report: &mut MergeReport,
+    ) {
+        let Some(before) = self.get(id).cloned() else {
+            return;
+        };
+        let mut entity = before.clone();
+
+        for relation in incoming.relations.iter() {
+            let Some(to) = ids.get(&relation.to).copied() else {
+                report.unresolved.push((id, relation.to));
+                continue;
+            };
+            let mut relation = relation.clone();
+            relation.to = to;
+            if relation.validate(id).is_err() {
+                continue;
+            }
+            match entity
+                .relations
+                .iter()
+                .find(|other| other.kind == relation.kind && other.to == to)
+            {
+                Some(other) if *other == relation => {}
+                Some(other) => report.conflicts.push(Conflict {
+                    entity: id,
+                    field: format!("span of {}", relation.kind.as_str().to_lowercase()),
+                    current: other.span().unwrap_or_else(|| ALWAYS.to_string()),
+                    incoming: relation.span().unwrap_or_else(|| ALWAYS.to_string()),
+                }),
+                None => entity.relations.push(relation),
+            }
+        }
+
+        for route in incoming.routes.iter() {
+            let Some(to) = ids.get(&route.to).copied() else {
+                report.unresolved.push((id, route.to));
+                continue;
+            };
+            let mut route = route.clone();
+            route.to = to;
+            if route.validate(id, self).is_err() {
+                continue;
+            }
+            match entity.routes.iter().find(|other| other.to == to) {
+                Some(other) if *other == route => {}
+                Some(other) => report.conflicts.push(Conflict {
+                    entity: id,
+                    field: "route".to_string(),
+                    current: other.describe(),
+                    incoming: route.describe(),
+                }),
+                None => entity.routes.push(route),
+            }
+        }
+
+        for change in incoming.changes.iter() {
+            let same =
+                |other: &&AttributeChange| other.key == change.key && other.since == change.since;
+            match entity.changes.iter().find(same) {
+                Some(other) if other.value == change.value => {}
+                Some(other) => report.conflicts.push(Conflict {
+                    entity: id,
+                    field: format!("{} from {}", change.key, change.since),
+                    current: other.value.clone(),
+                    incoming: change.value.clone(),
+                }),
+                None => entity.changes.push(change.clone()),
+            }
+        }
+
+        if entity != before {
+            report.updated.push(id);
+            if let Some(saved) = self.get_mut(id) {
+                *saved = entity;
+            }
+        }
+    }
+}
+
+/// To give `entity` the aliases, description, image and attributes of `incoming` it lacks, adding
+/// a conflict for every value that differs.
+fn merge_fields(entity: &mut Entity, incoming: &Entity, conflicts: &mut Vec<Conflict>) {
+    let id = entity.id;
+    let mut conflict = |field: &str, current: &str, incoming: &str| {
+        conflicts.push(Conflict {
+            entity: id,
+            field: field.to_string(),
+            current: current.to_string(),
+            incoming: incoming.to_string(),
+        })
+    };
+
+    for alias in incoming.names() {
+        if !entity.names().any(|name| name.eq_ignore_ascii_case(alias)) {
+            entity.aliases.push(alias.to_string());
+        }
+    }
+
+    match (entity.description.as_str(), incoming.description.as_str()) {
+        (_, "") => {}
+        ("", description) => entity.description = description.to_string(),
+        (current, description) if current != description => {
+            conflict("description", current, description)
+        }
+        _ => {}
+    }
+
+    match (&entity.image, &incoming.image) {
+        (_, None) => {}
+        (None, image) => entity.image = image.clone(),
+        (Some(current), Some(image)) if current != image => conflict(
+            "image",
+            current.display().to_string().as_str(),
+            image.display().to_string().as_str(),
+        ),
+        _ => {}
+    }
+
+    for (key, value) in incoming.attributes.iter() {
+        match entity.attributes.get(key) {
+            None => {
+                entity.attributes.insert(key.clone(), value.clone());
+            }
+            Some(current) if current != value => conflict(key, current, value),
+            Some(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relation::{Relation, RelationKind};
+
+    fn incoming(id: u32, kind: EntityKind, name: &str) -> Incoming {
+        Incoming::new(Some(EntityId(id)), kind, name)
+    }
+
+    #[test]
+    fn matched_entities_only_gain_what_they_lack() {
+        let mut registry = Registry::new();
+        let mara = registry.add(EntityKind::Character, "Mara").unwrap();
+        let entity = registry.get_mut(mara).unwrap();
+        entity.description = "A sailor.".to_string();
+        entity
+            .attributes
+            .insert("eyes".to_string(), "green".to_string());
+
+        let mut item = incoming(7_u32, EntityKind::Character, "mara");
+        item.entity.description = "A smuggler.".to_string();
+        item.entity.aliases.push("The Gull".to_string());
+        item.entity
+            .attributes
+            .insert("eyes".to_string(), "grey".to_string());
+        item.entity
+            .attributes
+            .insert("born".to_string(), "1000".to_string());
+        let report = registry.merge(vec![item]);
+
+        let entity = registry.get(mara).unwrap();
+        assert_eq!(entity.description, "A sailor.");
+        assert_eq!(entity.aliases, vec!["The Gull".to_string()]);
+        assert_eq!(entity.attributes["eyes"], "green");
+        assert_eq!(entity.attributes["born"], "1000");
+        assert!(report.added.is_empty());
+        assert_eq!(report.updated, vec![mara]);
+        let fields = report
+            .conflicts
+            .iter()
+            .map(|conflict| conflict.field.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(fields, vec!["description", "eyes"]);
+    }
+
+    #[test]
+    fn names_match_only_within_their_kind() {
+        let mut registry = Registry::new();
+        let city = registry.add(EntityKind::Location, "Mara").unwrap();
+        let report = registry.merge(vec![incoming(0_u32, EntityKind::Character, "Mara")]);
+        assert_eq!(report.added.len(), 1_usize);
+        assert_ne!(report.added[0_usize], city);
+    }
+
+    #[test]
+    fn relations_follow_the_ids_of_the_file() {
+        let mut registry = Registry::new();
+        registry.add(EntityKind::Character, "Tom").unwrap();
+
+        let mut mara = incoming(10_u32, EntityKind::Character, "Mara");
+        mara.entity
+            .relations
+            .push(Relation::new(RelationKind::Sibling, EntityId(11_u32)));
+        mara.entity
+            .relations
+            .push(Relation::new(RelationKind::Enemy, EntityId(12_u32)));
+        let tom = incoming(11_u32, EntityKind::Character, "Tom");
+        let report = registry.merge(vec![mara, tom]);
+
+        let mara = report.added[0_usize];
+        let tom = registry.search("Tom").next().unwrap().id();
+        let relations = &registry.get(mara).unwrap().relations;
+        assert_eq!(relations, &vec![Relation::new(RelationKind::Sibling, tom)]);
+        assert_eq!(report.unresolved, vec![(mara, EntityId(12_u32))]);
+    }
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Registry module.
+//!
+//! The [`Registry`] that holds every lore [`Entity`] of a project.
+
+use crate::entity::{Entity, EntityId, EntityKind};
+use crate::relation::Relation;
+use crate::travel::Route;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+use tree::TreeError;
+
+/// Errors that can occur while changing a [`Registry`].
+#[derive(Debug, Error)]
+pub enum LoreError {
+    /// The entity does not exist in the registry.
+    #[error("Entity {0} does not exist in the lore registry.")]
+    EntityNotFound(EntityId),
+    /// The name is empty or only whitespace.
+    #[error("An entity name cannot be empty.")]
+    EmptyName,
+    /// An entity cannot be tied to itself.
+    #[error("Entity {0} cannot be related to itself.")]
+    SelfRelation(EntityId),
+    /// The relation ends before it starts.
+    #[error("A relation cannot end before it starts.")]
+    InvalidSpan,
+    /// Routes can only join locations.
+    #[error("Entity {0} is not a location.")]
+    NotALocation(EntityId),
+    /// The name does not belong to the entity.
+    #[error("{0:?} is not a name or alias of the entity.")]
+    UnknownName(String),
+    /// The document tree refused a change.
+    #[error(transparent)]
+    Tree(#[from] TreeError),
+}
+
+/// Holds every lore [`Entity`] of a project, ordered by id. Stored as a [`StoredRegistry`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredRegistry", into = "StoredRegistry")]
+pub struct Registry {
+    entities: BTreeMap<EntityId, Entity>,
+    next_id: u32,
+}
+
+/// A [`Registry`] as it is stored: the list of its entities, and the id the next entity gets so
+/// that the ids of removed entities are not given out again.
+#[derive(Serialize, Deserialize)]
+struct StoredRegistry {
+    #[serde(default)]
+    next_id: u32,
+    #[serde(default)]
+    entities: Vec<Entity>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new entity and returns its id.
+    pub fn add(&mut self, kind: EntityKind, name: &str) -> Result<EntityId, LoreError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(LoreError::EmptyName);
+        }
+
+        let id = EntityId(self.next_id);
+        self.next_id += 1_u32;
+        self.entities.insert(id, Entity::new(id, kind, name));
+        Ok(id)
+    }
+
+    /// Adds `entity` with its own id, replacing the entity that has it.
+    pub(crate) fn insert(&mut self, entity: Entity) {
+        self.next_id = self.next_id.max(entity.id.0 + 1_u32);
+        self.entities.insert(entity.id, entity);
+    }
+
+    /// Removes the entity, along with the links other entities have to it, and returns it.
+    pub fn remove(&mut self, id: EntityId) -> Result<Entity, LoreError> {
+        let entity = self
+            .entities
+            .remove(&id)
+            .ok_or(LoreError::EntityNotFound(id))?;
+        self.entities.values_mut().for_each(|other| {
+            other.relations.retain(|relation| relation.to != id);
+            other.routes.retain(|route| route.to != id);
+        });
+        Ok(entity)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Entity> {
+        self.entities.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
+        self.entities.get_mut(&id)
+    }
+
+    /// Returns every entity ordered by id.
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.values()
+    }
+
+    /// Returns every entity of the given `kind`.
+    pub fn of_kind(&self, kind: EntityKind) -> impl Iterator<Item = &Entity> {
+        self.iter().filter(move |entity| entity.kind == kind)
+    }
+
+    /// Returns the entity that is called `name`, either by its name or one of its aliases.
+    pub fn find_by_name(&self, name: &str) -> Option<&Entity> {
+        self.iter()
+            .find(|entity| entity.names().any(|known| known == name))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Adds `relation` to the entity `from`.
+    pub fn relate(&mut self, from: EntityId, relation: Relation) -> Result<(), LoreError> {
+        relation.validate(from)?;
+        if !self.entities.contains_key(&relation.to) {
+            return Err(LoreError::EntityNotFound(relation.to));
+        }
+
+        self.entities
+            .get_mut(&from)
+            .ok_or(LoreError::EntityNotFound(from))?
+            .relations
+            .push(relation);
+        Ok(())
+    }
+
+    /// Adds `route` to the location `from`.
+    pub fn add_route(&mut self, from: EntityId, route: Route) -> Result<(), LoreError> {
+        route.validate(from, self)?;
+        self.entities
+            .get_mut(&from)
+            .ok_or(LoreError::EntityNotFound(from))?
+            .routes
+            .push(route);
+        Ok(())
+    }
+
+    /// Returns every relation along with the entity it starts from.
+    pub fn relations(&self) -> impl Iterator<Item = (EntityId, &Relation)> {
+        self.iter().flat_map(|entity| {
+            entity
+                .relations
+                .iter()
+                .map(move |relation| (entity.id, relation))
+        })
+    }
+
+    /// Returns the relations of other entities that lead to `id`, along with the entity each
+    /// starts from.
+    pub fn relations_to(&self, id: EntityId) -> impl Iterator<Item = (EntityId, &Relation)> {
+        self.relations()
+            .filter(move |(_, relation)| relation.to == id)
+    }
+
+    /// Returns the entities with a name or alias that holds `query`, ignoring case. Every entity
+    /// matches an empty query.
+    pub fn search<'a>(&'a self, query: &str) -> impl Iterator<Item = &'a Entity> {
+        let query = query.trim().to_lowercase();
+        self.iter().filter(move |entity| {
+            entity
+                .names()
+                .any(|name| name.to_lowercase().contains(query.as_str()))
+        })
+    }
+}
+
+impl From<StoredRegistry> for Registry {
+    /// Ids are kept so that references between entities stay valid. The next id is never one
+    /// that is taken.
+    fn from(stored: StoredRegistry) -> Self {
+        let next_id = stored
+            .entities
+            .iter()
+            .map(|entity| entity.id.0 + 1_u32)
+            .chain([stored.next_id])
+            .max()
+            .unwrap_or_default();
+        let entities = stored
+            .entities
+            .into_iter()
+            .map(|entity| (entity.id, entity))
+            .collect();
+
+        Self { entities, next_id }
+    }
+}
+
+impl From<Registry> for StoredRegistry {
+    fn from(registry: Registry) -> Self {
+        Self {
+            next_id: registry.next_id,
+            entities: registry.entities.into_values().collect(),
+        }
+    }
+}
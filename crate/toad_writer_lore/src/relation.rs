@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Relation module.
+//!
+//! Typed ties between lore entities such as parentage, marriage or membership. A [`Relation`] is
+//! stored on the entity it starts from and may only hold for a span of story time.
+
+use crate::{entity::EntityId, registry::LoreError};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// A point in story time. Calendars count it in days, but times are only compared with each other,
+/// so plain years work as well as long as the whole lore uses the same count.
+pub type StoryTime = i64;
+
+/// The kind of tie a [`Relation`] stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RelationKind {
+    /// Any tie without a more precise kind.
+    Related,
+    /// The entity is a parent of the other.
+    Parent,
+    Spouse,
+    Sibling,
+    Ally,
+    Enemy,
+    /// The entity belongs to the other, usually an organisation.
+    MemberOf,
+    /// The entity lies within the other, such as a city within a country.
+    PartOf,
+}
+
+/// A tie from the entity holding it to the entity `to`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Relation {
+    pub kind: RelationKind,
+    pub to: EntityId,
+    /// When the tie started, if known.
+    #[serde(default)]
+    pub since: Option<StoryTime>,
+    /// When the tie ended, if it did.
+    #[serde(default)]
+    pub until: Option<StoryTime>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl RelationKind {
+    /// Every kind in the order they are listed in the UI.
+    pub const ALL: [RelationKind; 8_usize] = [
+        RelationKind::Related,
+        RelationKind::Parent,
+        RelationKind::Spouse,
+        RelationKind::Sibling,
+        RelationKind::Ally,
+        RelationKind::Enemy,
+        RelationKind::MemberOf,
+        RelationKind::PartOf,
+    ];
+
+    /// Returns how the tie reads from the entity holding it, such as `Parent of`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationKind::Related => "Related to",
+            RelationKind::Parent => "Parent of",
+            RelationKind::Spouse => "Spouse of",
+            RelationKind::Sibling => "Sibling of",
+            RelationKind::Ally => "Ally of",
+            RelationKind::Enemy => "Enemy of",
+            RelationKind::MemberOf => "Member of",
+            RelationKind::PartOf => "Part of",
+        }
+    }
+
+    /// Returns how the tie reads from the other entity, such as `Child of`.
+    pub fn inverse_str(&self) -> &'static str {
+        match self {
+            RelationKind::Parent => "Child of",
+            RelationKind::MemberOf => "Has member",
+            RelationKind::PartOf => "Contains",
+            kind => kind.as_str(),
+        }
+    }
+
+    /// Returns true if the tie reads the same from both entities.
+    pub fn is_symmetric(&self) -> bool {
+        !matches!(
+            self,
+            RelationKind::Parent | RelationKind::MemberOf | RelationKind::PartOf
+        )
+    }
+}
+
+impl Display for RelationKind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Relation {
+    /// Returns a relation of `kind` to `to` that always holds.
+    pub fn new(kind: RelationKind, to: EntityId) -> Self {
+        Self {
+            kind,
+            to,
+            since: None,
+            until: None,
+        }
+    }
+
+    /// Returns an error if the relation cannot start from the entity `from`.
+    pub fn validate(&self, from: EntityId) -> Result<(), LoreError> {
+        if from == self.to {
+            return Err(LoreError::SelfRelation(from));
+        }
+        match (self.since, self.until) {
+            (Some(since), Some(until)) if until < since => Err(LoreError::InvalidSpan),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns true if the relation holds at `time`. Bounds are inclusive.
+    pub fn holds_at(&self, time: StoryTime) -> bool {
+        self.since.is_none_or(|since| since <= time) && self.until.is_none_or(|until| time <= until)
+    }
+
+    /// Returns the span of the relation such as `1021–1040`, or [`None`] if it always holds.
+    pub fn span(&self) -> Option<String> {
+        match (self.since, self.until) {
+            (None, None) => None,
+            (Some(since), None) => Some(format!("since {since}")),
+            (None, Some(until)) => Some(format!("until {until}")),
+            (Some(since), Some(until)) => Some(format!("{since}–{until}")),
+        }
+    }
+}
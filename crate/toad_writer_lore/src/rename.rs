@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Rename module.
+//!
+//! To rename a lore entity along with every mention of it in the [`DocumentTree`]. A
+//! [`RenamePlan`] is made first so that the changes can be previewed, and is then applied to the
+//! tree and the registry all at once.
+
+use crate::{
+    entity::EntityId,
+    mention::{whole_words, MentionFinder},
+    registry::{LoreError, Registry},
+};
+use std::ops::Range;
+use syntax::annotation::annotations;
+use tree::{DocumentTree, NodeId, TextEdit};
+
+/// Number of characters shown around a change in a [`RenamePreview`].
+const CONTEXT: usize = 30_usize;
+
+/// All the changes needed to rename a name of an entity from `old` to `new`.
+#[derive(Debug, Clone)]
+pub struct RenamePlan {
+    pub entity: EntityId,
+    pub old: String,
+    pub new: String,
+    /// The changes to the text of the scenes.
+    pub edits: Vec<TextEdit>,
+}
+
+/// A single change of a [`RenamePlan`] with some text around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamePreview {
+    pub scene: NodeId,
+    /// Character range of the change in the scene text.
+    pub chars: Range<usize>,
+    pub before: String,
+    pub after: String,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl RenamePlan {
+    /// Finds every mention of `entity` in the scenes of `tree` that uses the word `old`, and every
+    /// annotation that names it, and plans to change it to `new`. `old` has to be the name of the entity, one of its aliases or a
+    /// whole word of them. Mentions of other entities are left alone even if they hold `old`,
+    /// such as `Mara's Keep` when renaming `Mara`.
+    pub fn new(
+        registry: &Registry,
+        tree: &DocumentTree,
+        entity: EntityId,
+        old: &str,
+        new: &str,
+    ) -> Result<Self, LoreError> {
+        let known = registry
+            .get(entity)
+            .ok_or(LoreError::EntityNotFound(entity))?;
+        if new.trim().is_empty() {
+            return Err(LoreError::EmptyName);
+        }
+        if !known.names().any(|name| !whole_words(name, old).is_empty()) {
+            return Err(LoreError::UnknownName(old.to_string()));
+        }
+
+        let finder = MentionFinder::new(registry);
+        let mut edits = Vec::new();
+        for scene in tree.scenes() {
+            let Some(node) = tree.node(scene) else {
+                continue;
+            };
+            for mention in finder.find(&node.text) {
+                if mention.entity != entity {
+                    continue;
+                }
+                for word in whole_words(&mention.term, old) {
+                    let range = mention.range.start + word.start..mention.range.start + word.end;
+                    edits.push(rename_edit(scene, &node.text, range, &mention.suffix, new));
+                }
+            }
+            for name in annotations(&node.text)
+                .iter()
+                .flat_map(|annotation| annotation.names(&node.text))
+                .filter(|name| finder.entity_named(&node.text[name.clone()]) == Some(entity))
+            {
+                for word in whole_words(&node.text[name.clone()], old) {
+                    let range = name.start + word.start..name.start + word.end;
+                    edits.push(rename_edit(scene, &node.text, range, "", new));
+                }
+            }
+        }
+        edits.sort_by_key(|edit| (edit.node, edit.range.start));
+
+        Ok(Self {
+            entity,
+            old: old.to_string(),
+            new: new.to_string(),
+            edits,
+        })
+    }
+
+    /// Returns a preview of every change in the order they appear.
+    pub fn preview(&self, tree: &DocumentTree) -> Vec<RenamePreview> {
+        self.edits
+            .iter()
+            .filter_map(|edit| {
+                let text = &tree.node(edit.node)?.text;
+                let chars = edit.chars(text);
+                let before = context(text, &edit.range, edit.old.as_str());
+                let after = context(text, &edit.range, edit.new.as_str());
+
+                Some(RenamePreview {
+                    scene: edit.node,
+                    chars,
+                    before,
+                    after,
+                })
+            })
+            .collect()
+    }
+
+    /// Applies the changes to `tree` and renames the entity in `registry`. Nothing is changed if
+    /// any of the edits no longer fits the text.
+    pub fn apply(&self, tree: &mut DocumentTree, registry: &mut Registry) -> Result<(), LoreError> {
+        let known = registry
+            .get_mut(self.entity)
+            .ok_or(LoreError::EntityNotFound(self.entity))?;
+        tree.apply_edits(&self.edits)?;
+
+        known.name = rename_words(&known.name, &self.old, &self.new);
+        known
+            .aliases
+            .iter_mut()
+            .for_each(|alias| *alias = rename_words(alias, &self.old, &self.new));
+        Ok(())
+    }
+}
+
+/// Returns the edit that renames `range` of `text`. A bare possessive apostrophe, as in `James'
+/// book`, is turned into `'s` if the new name does not end in `s` anymore. An apostrophe after a
+/// name that does not end in `s`, or followed by punctuation, closes a quote and is left alone.
+fn rename_edit(node: NodeId, text: &str, range: Range<usize>, suffix: &str, new: &str) -> TextEdit {
+    let bare = (suffix == "'" || suffix == "’")
+        && text[range.end..].starts_with(suffix)
+        && text[range.clone()].ends_with(['s', 'S'])
+        && text[range.end + suffix.len()..]
+            .chars()
+            .next()
+            .is_some_and(|next| next.is_whitespace() || next.is_alphanumeric());
+    if !bare || new.ends_with('s') {
+        return TextEdit {
+            node,
+            old: text[range.clone()].to_string(),
+            range,
+            new: new.to_string(),
+        };
+    }
+
+    let range = range.start..range.end + suffix.len();
+    TextEdit {
+        node,
+        old: text[range.clone()].to_string(),
+        range,
+        new: format!("{new}{suffix}s"),
+    }
+}
+
+/// Replaces every whole word `old` in `name` with `new`.
+fn rename_words(name: &str, old: &str, new: &str) -> String {
+    let mut renamed = name.to_string();
+    for range in whole_words(name, old).into_iter().rev() {
+        renamed.replace_range(range, new);
+    }
+    renamed
+}
+
+/// Returns the text around `range` with `middle` in place of it.
+fn context(text: &str, range: &Range<usize>, middle: &str) -> String {
+    let before = text[..range.start]
+        .chars()
+        .rev()
+        .take(CONTEXT)
+        .collect::<Vec<char>>();
+    let after = text[range.end..].chars().take(CONTEXT).collect::<String>();
+
+    format!("{}{middle}{after}", before.iter().rev().collect::<String>()).replace('\n', "\x20")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::EntityKind;
+
+    fn renamed(text: &str, old: &str, new: &str) -> String {
+        let mut registry = Registry::new();
+        let entity = registry.add(EntityKind::Character, old).unwrap();
+        let mut tree = DocumentTree::new("Book");
+        let chapter = tree.add_chapter("Chapter");
+        let scene = tree.add_scene(chapter, "Scene", text).unwrap();
+
+        let plan = RenamePlan::new(&registry, &tree, entity, old, new).unwrap();
+        plan.apply(&mut tree, &mut registry).unwrap();
+        tree.node(scene).unwrap().text.clone()
+    }
+
+    #[test]
+    fn bare_possessives_get_an_s() {
+        assert_eq!(renamed("James' book.", "James", "Tom"), "Tom's book.");
+        assert_eq!(renamed("James’ book.", "James", "Tom"), "Tom’s book.");
+        assert_eq!(renamed("James' book.", "James", "Niles"), "Niles' book.");
+    }
+
+    #[test]
+    fn closing_quotes_are_left_alone() {
+        assert_eq!(
+            renamed("'Hello, Mara' he said.", "Mara", "Tom"),
+            "'Hello, Tom' he said."
+        );
+        assert_eq!(
+            renamed("'Come here, James', she said.", "James", "Tom"),
+            "'Come here, Tom', she said."
+        );
+        assert_eq!(
+            renamed("'Thank you, James'", "James", "Tom"),
+            "'Thank you, Tom'"
+        );
+    }
+
+    #[test]
+    fn references_are_renamed() {
+        assert_eq!(
+            renamed("Mara came. The {{Mara|old woman}} sat.", "Mara", "Tom"),
+            "Tom came. The {{Tom|old woman}} sat."
+        );
+        assert_eq!(
+            renamed("{{Mara|Mara}} came.", "Mara", "Tom"),
+            "{{Tom|Tom}} came."
+        );
+    }
+
+    #[test]
+    fn facts_are_renamed() {
+        assert_eq!(
+            renamed("{{fact: Mara | hair = grey}} Mara's cat.", "Mara", "Tom"),
+            "{{fact: Tom | hair = grey}} Tom's cat."
+        );
+    }
+
+    #[test]
+    fn scenes_are_renamed() {
+        assert_eq!(
+            renamed(
+                "{{scene: pov = Mara | status = Draft}}\nMara came.",
+                "Mara",
+                "Tom"
+            ),
+            "{{scene: pov = Tom | status = Draft}}\nTom came."
+        );
+        assert_eq!(
+            renamed("{{scene: pov = Maran}}\n", "Mara", "Tom"),
+            "{{scene: pov = Maran}}\n"
+        );
+    }
+}
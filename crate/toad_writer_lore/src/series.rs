@@ -0,0 +1,499 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Series module.
+//!
+//! The books of a series share one [`Registry`]. A book may keep its own description, image,
+//! attributes or aliases for a shared entity with an [`EntityOverride`]. Every other change a book
+//! makes to the lore is made for the whole series.
+
+use crate::{
+    entity::{Entity, EntityId},
+    merge::Conflict,
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Values a book gives a shared entity in place of those of the series.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityOverride {
+    pub entity: EntityId,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub image: Option<PathBuf>,
+    /// Attributes with the value they have in the book.
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    /// Names the entity only goes by in the book.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// What [`Registry::absorb`] did with the lore of a book.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AbsorbReport {
+    /// The id in the series of every entity the book added, by its id in the book.
+    pub ids: BTreeMap<EntityId, EntityId>,
+    /// Values the book and another one both changed, where the series kept its own.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// A value of an entity a book can keep for itself.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OverrideField {
+    Description,
+    Image,
+    Attribute(String),
+    Alias(String),
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl EntityOverride {
+    /// Returns an override of `entity` that keeps nothing.
+    pub fn new(entity: EntityId) -> Self {
+        Self {
+            entity,
+            description: None,
+            image: None,
+            attributes: BTreeMap::new(),
+            aliases: Vec::new(),
+        }
+    }
+
+    /// Returns true if the override keeps nothing.
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none()
+            && self.image.is_none()
+            && self.attributes.is_empty()
+            && self.aliases.is_empty()
+    }
+
+    /// Returns every field the override keeps.
+    pub fn fields(&self) -> Vec<OverrideField> {
+        let mut fields = Vec::new();
+        if self.description.is_some() {
+            fields.push(OverrideField::Description);
+        }
+        if self.image.is_some() {
+            fields.push(OverrideField::Image);
+        }
+        fields.extend(
+            self.attributes
+                .keys()
+                .cloned()
+                .map(OverrideField::Attribute),
+        );
+        fields.extend(self.aliases.iter().cloned().map(OverrideField::Alias));
+        fields
+    }
+
+    /// To keep the value of `field` that `entity` has in the book. A value the entity lacks cannot
+    /// be kept.
+    pub fn keep(&mut self, entity: &Entity, field: &OverrideField) {
+        match field {
+            OverrideField::Description => self.description = Some(entity.description.clone()),
+            OverrideField::Image => self.image = entity.image.clone(),
+            OverrideField::Attribute(key) => {
+                if let Some(value) = entity.attributes.get(key) {
+                    self.attributes.insert(key.clone(), value.clone());
+                }
+            }
+            OverrideField::Alias(alias) => {
+                if entity.aliases.contains(alias) && !self.aliases.contains(alias) {
+                    self.aliases.push(alias.clone());
+                }
+            }
+        }
+    }
+
+    /// To stop keeping `field`, so that the book shares the value of the series again.
+    pub fn share(&mut self, field: &OverrideField) {
+        match field {
+            OverrideField::Description => self.description = None,
+            OverrideField::Image => self.image = None,
+            OverrideField::Attribute(key) => {
+                self.attributes.remove(key);
+            }
+            OverrideField::Alias(alias) => self.aliases.retain(|other| other != alias),
+        }
+    }
+
+    /// To take the values the override keeps from `ours`, the entity as the book has it, giving
+    /// them to `ours` and `base`, the entity as the book loaded it, as they are in `shared`. Those
+    /// values are then left as the series has them.
+    fn split(&mut self, ours: &mut Entity, base: &mut Entity, shared: &Entity) {
+        if self.description.is_some() {
+            self.description = Some(ours.description.clone());
+            ours.description.clone_from(&shared.description);
+            base.description.clone_from(&shared.description);
+        }
+        if self.image.is_some() {
+            self.image = ours.image.clone();
+            ours.image.clone_from(&shared.image);
+            base.image.clone_from(&shared.image);
+        }
+        self.attributes
+            .retain(|key, _| ours.attributes.contains_key(key));
+        for (key, value) in self.attributes.iter_mut() {
+            value.clone_from(&ours.attributes[key]);
+            for entity in [&mut *ours, &mut *base] {
+                match shared.attributes.get(key) {
+                    Some(value) => entity.attributes.insert(key.clone(), value.clone()),
+                    None => entity.attributes.remove(key),
+                };
+            }
+        }
+        self.aliases.retain(|alias| ours.aliases.contains(alias));
+        for entity in [ours, base] {
+            entity.aliases.retain(|alias| !self.aliases.contains(alias));
+        }
+    }
+
+    /// To give `entity` the values the override keeps.
+    fn apply(&self, entity: &mut Entity) {
+        if let Some(description) = self.description.as_ref() {
+            entity.description = description.clone();
+        }
+        if let Some(image) = self.image.as_ref() {
+            entity.image = Some(image.clone());
+        }
+        entity.attributes.extend(self.attributes.clone());
+        for alias in self.aliases.iter() {
+            if !entity.aliases.contains(alias) {
+                entity.aliases.push(alias.clone());
+            }
+        }
+    }
+}
+
+impl Display for OverrideField {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            OverrideField::Description => write!(f, "Description"),
+            OverrideField::Image => write!(f, "Image"),
+            OverrideField::Attribute(key) => write!(f, "Attribute {key:?}"),
+            OverrideField::Alias(alias) => write!(f, "Alias {alias:?}"),
+        }
+    }
+}
+
+impl OverrideField {
+    /// Returns every field of `entity` a book could keep.
+    pub fn of(entity: &Entity) -> Vec<OverrideField> {
+        let mut fields = vec![OverrideField::Description, OverrideField::Image];
+        fields.extend(
+            entity
+                .attributes
+                .keys()
+                .cloned()
+                .map(OverrideField::Attribute),
+        );
+        fields.extend(entity.aliases.iter().cloned().map(OverrideField::Alias));
+        fields
+    }
+}
+
+impl Registry {
+    /// Returns the lore as a book with `overrides` sees it.
+    pub fn overridden(&self, overrides: &[EntityOverride]) -> Registry {
+        let mut book = self.clone();
+        for entity_override in overrides {
+            if let Some(entity) = book.get_mut(entity_override.entity) {
+                entity_override.apply(entity);
+            }
+        }
+        book
+    }
+
+    /// To take in the changes a book with `overrides` made to its view of the lore, `book`, since
+    /// it was `loaded`. Kept values go to the overrides and everything else to the shared lore, so
+    /// entities the book added or removed are added or removed for the whole series. Added
+    /// entities get a new id of the series, as another book may have used theirs meanwhile. Values
+    /// are merged one by one, so that changes other books made in the meantime are kept: a value
+    /// both changed keeps the one of the series and is reported as a conflict. A kept value the
+    /// book no longer has is shared again.
+    pub fn absorb(
+        &mut self,
+        book: &Registry,
+        loaded: &Registry,
+        overrides: &mut Vec<EntityOverride>,
+    ) -> AbsorbReport {
+        let mut report = AbsorbReport::default();
+        let removed = loaded
+            .iter()
+            .map(Entity::id)
+            .filter(|id| book.get(*id).is_none())
+            .collect::<Vec<EntityId>>();
+        for id in removed {
+            let _ = self.remove(id);
+        }
+
+        for entity in book
+            .iter()
+            .filter(|entity| loaded.get(entity.id()).is_none())
+        {
+            if let Ok(id) = self.add(entity.kind, entity.name.as_str()) {
+                report.ids.insert(entity.id(), id);
+            }
+        }
+        for kept in overrides.iter_mut() {
+            if let Some(id) = report.ids.get(&kept.entity) {
+                kept.entity = *id;
+            }
+        }
+
+        for entity in book.iter() {
+            let base = loaded.get(entity.id());
+            if base == Some(entity) {
+                continue;
+            }
+            let mut ours = entity.clone();
+            ours.id = report.ids.get(&entity.id).copied().unwrap_or(entity.id);
+            for relation in ours.relations.iter_mut() {
+                relation.to = report.ids.get(&relation.to).copied().unwrap_or(relation.to);
+            }
+            for route in ours.routes.iter_mut() {
+                route.to = report.ids.get(&route.to).copied().unwrap_or(route.to);
+            }
+
+            let current = self.get(ours.id).cloned();
+            let shared = match (base, current) {
+                (Some(base), Some(current)) => {
+                    let mut base = base.clone();
+                    if let Some(kept) = overrides.iter_mut().find(|kept| kept.entity == ours.id) {
+                        kept.split(&mut ours, &mut base, &current);
+                    }
+                    merge_entity(&base, &ours, &current, &mut report.conflicts)
+                }
+                // The entity is new, or another book removed it and this one still changed it.
+                _ => ours,
+            };
+            self.insert(shared);
+        }
+
+        overrides.retain(|kept| self.get(kept.entity).is_some() && !kept.is_empty());
+        report
+    }
+}
+
+/// Returns the value both sides agree on or the one that changed since `base`, or [`None`] if
+/// both changed it differently.
+fn merged<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> Option<T> {
+    match (ours == base, theirs == base) {
+        (true, _) => Some(theirs.clone()),
+        (false, true) => Some(ours.clone()),
+        (false, false) => (ours == theirs).then(|| ours.clone()),
+    }
+}
+
+/// Returns `theirs` with the items `ours` added to or removed from `base` added or removed.
+fn merged_list<T: PartialEq + Clone>(base: &[T], ours: &[T], theirs: &[T]) -> Vec<T> {
+    let mut list = theirs
+        .iter()
+        .filter(|item| !base.contains(item) || ours.contains(item))
+        .cloned()
+        .collect::<Vec<T>>();
+    for item in ours.iter() {
+        if !base.contains(item) && !list.contains(item) {
+            list.push(item.clone());
+        }
+    }
+    list
+}
+
+/// Returns `theirs`, the entity as the series has it, with what `ours` changed since `base`. A
+/// value both changed differently keeps the one of the series and is added to `conflicts`.
+fn merge_entity(
+    base: &Entity,
+    ours: &Entity,
+    theirs: &Entity,
+    conflicts: &mut Vec<Conflict>,
+) -> Entity {
+    let mut entity = theirs.clone();
+    let mut conflict = |field: &str, current: String, incoming: String| {
+        conflicts.push(Conflict {
+            entity: theirs.id,
+            field: field.to_string(),
+            current,
+            incoming,
+        })
+    };
+    let image = |image: &Option<PathBuf>| {
+        image
+            .as_ref()
+            .map(|image| image.display().to_string())
+            .unwrap_or_default()
+    };
+
+    match merged(&base.kind, &ours.kind, &theirs.kind) {
+        Some(kind) => entity.kind = kind,
+        None => conflict("kind", theirs.kind.to_string(), ours.kind.to_string()),
+    }
+    match merged(&base.name, &ours.name, &theirs.name) {
+        Some(name) => entity.name = name,
+        None => conflict("name", theirs.name.clone(), ours.name.clone()),
+    }
+    match merged(&base.description, &ours.description, &theirs.description) {
+        Some(description) => entity.description = description,
+        None => conflict(
+            "description",
+            theirs.description.clone(),
+            ours.description.clone(),
+        ),
+    }
+    match merged(&base.image, &ours.image, &theirs.image) {
+        Some(kept) => entity.image = kept,
+        None => conflict("image", image(&theirs.image), image(&ours.image)),
+    }
+
+    let keys = base
+        .attributes
+        .keys()
+        .chain(ours.attributes.keys())
+        .chain(theirs.attributes.keys())
+        .cloned()
+        .collect::<BTreeSet<String>>();
+    for key in keys {
+        let value = |entity: &Entity| entity.attributes.get(&key).cloned();
+        match merged(&value(base), &value(ours), &value(theirs)) {
+            Some(Some(value)) => {
+                entity.attributes.insert(key, value);
+            }
+            Some(None) => {
+                entity.attributes.remove(&key);
+            }
+            None => conflict(
+                key.as_str(),
+                value(theirs).unwrap_or_default(),
+                value(ours).unwrap_or_default(),
+            ),
+        }
+    }
+
+    entity.aliases = merged_list(&base.aliases, &ours.aliases, &theirs.aliases);
+    entity.relations = merged_list(&base.relations, &ours.relations, &theirs.relations);
+    entity.routes = merged_list(&base.routes, &ours.routes, &theirs.routes);
+    entity.changes = merged_list(&base.changes, &ours.changes, &theirs.changes);
+    entity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::EntityKind;
+    use crate::relation::{Relation, RelationKind};
+
+    #[test]
+    fn absorbing_keeps_what_other_books_changed() {
+        let mut series = Registry::new();
+        let mara = series.add(EntityKind::Character, "Mara").unwrap();
+        let loaded = series.clone();
+
+        // Another book adds an entity and describes Mara after this book loaded the lore.
+        let tom = series.add(EntityKind::Character, "Tom").unwrap();
+        series.get_mut(mara).unwrap().description = "A smith.".to_string();
+
+        let mut overrides = Vec::new();
+        series.absorb(&loaded, &loaded, &mut overrides);
+        assert!(series.get(tom).is_some());
+        assert_eq!(series.get(mara).unwrap().description, "A smith.");
+
+        let mut book = loaded.clone();
+        book.remove(mara).unwrap();
+        series.absorb(&book, &loaded, &mut overrides);
+        assert!(series.get(mara).is_none());
+        assert!(series.get(tom).is_some());
+    }
+
+    #[test]
+    fn absorbing_shares_what_the_book_changed() {
+        let mut series = Registry::new();
+        let mara = series.add(EntityKind::Character, "Mara").unwrap();
+        let loaded = series.clone();
+        let mut book = loaded.clone();
+        book.get_mut(mara).unwrap().description = "A baker.".to_string();
+
+        let mut overrides = vec![EntityOverride::new(mara)];
+        overrides[0_usize].keep(book.get(mara).unwrap(), &OverrideField::Description);
+        series.absorb(&book, &loaded, &mut overrides);
+        assert_eq!(series.get(mara).unwrap().description, "");
+        assert_eq!(overrides[0_usize].description.as_deref(), Some("A baker."));
+    }
+
+    #[test]
+    fn books_adding_entities_at_once_keep_both() {
+        let mut series = Registry::new();
+        let mara = series.add(EntityKind::Character, "Mara").unwrap();
+        let loaded = series.clone();
+
+        let mut first = loaded.clone();
+        let tom = first.add(EntityKind::Character, "Tom").unwrap();
+        let mut second = loaded.clone();
+        let ash = second.add(EntityKind::Character, "Ash").unwrap();
+        second
+            .relate(ash, Relation::new(RelationKind::Sibling, mara))
+            .unwrap();
+        second
+            .relate(mara, Relation::new(RelationKind::Sibling, ash))
+            .unwrap();
+        assert_eq!(tom, ash);
+
+        let report = series.absorb(&first, &loaded, &mut Vec::new());
+        assert_eq!(report.ids.get(&tom), Some(&tom));
+        let report = series.absorb(&second, &loaded, &mut Vec::new());
+        let shared = report.ids[&ash];
+        assert_ne!(shared, tom);
+
+        let names = series
+            .iter()
+            .map(|entity| entity.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["Mara", "Tom", "Ash"]);
+        assert_eq!(series.get(shared).unwrap().relations[0_usize].to, mara);
+        assert_eq!(series.get(mara).unwrap().relations[0_usize].to, shared);
+        assert!(report.conflicts.is_empty());
+    }
+
+    #[test]
+    fn values_are_merged_one_by_one() {
+        let mut series = Registry::new();
+        let mara = series.add(EntityKind::Character, "Mara").unwrap();
+        let loaded = series.clone();
+
+        // Another book describes Mara and gives her green eyes meanwhile.
+        let entity = series.get_mut(mara).unwrap();
+        entity.description = "A smith.".to_string();
+        entity
+            .attributes
+            .insert("eyes".to_string(), "green".to_string());
+
+        let mut book = loaded.clone();
+        let entity = book.get_mut(mara).unwrap();
+        entity.description = "A baker.".to_string();
+        entity
+            .attributes
+            .insert("hair".to_string(), "grey".to_string());
+        entity.aliases.push("Old Mara".to_string());
+        let report = series.absorb(&book, &loaded, &mut Vec::new());
+
+        let entity = series.get(mara).unwrap();
+        assert_eq!(entity.description, "A smith.");
+        assert_eq!(entity.attributes["eyes"], "green");
+        assert_eq!(entity.attributes["hair"], "grey");
+        assert_eq!(entity.aliases, vec!["Old Mara".to_string()]);
+        assert_eq!(
+            report.conflicts,
+            vec![Conflict {
+                entity: mara,
+                field: "description".to_string(),
+                current: "A smith.".to_string(),
+                incoming: "A baker.".to_string(),
+            }]
+        );
+    }
+}
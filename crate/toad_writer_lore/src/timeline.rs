@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Timeline module.
+//!
+//! Puts the story in chronological order. Lore events are dated by their [`DATE`] attribute and
+//! scenes by their metadata, their date annotation or the first date written in them, all read
+//! with a [`Calendar`]. Every character and location taking part gets a lane.
+
+use crate::{
+    calendar::Calendar,
+    entity::{EntityId, EntityKind},
+    mention::MentionFinder,
+    registry::Registry,
+    relation::StoryTime,
+};
+use std::collections::BTreeSet;
+use std::ops::Range;
+use tree::{DocumentTree, NodeId};
+
+/// Attribute that holds when an event took place.
+pub const DATE: &str = "date";
+
+/// Where a [`TimelineEvent`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineSource {
+    /// An event of the lore.
+    Entity(EntityId),
+    /// A scene with a date in it.
+    Scene(NodeId),
+}
+
+/// Something that happened at a point in story time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEvent {
+    pub time: StoryTime,
+    pub title: String,
+    pub source: TimelineSource,
+    /// The scene and byte range in its text to show for the event: the date of a scene, or the
+    /// first mention of a lore event.
+    pub location: Option<(NodeId, Range<usize>)>,
+    /// The characters and locations taking part, empty if there are none.
+    pub lanes: Vec<EntityId>,
+}
+
+/// The dated events of a story in chronological order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timeline {
+    /// A lane for every character, then every location, that takes part in an event.
+    pub lanes: Vec<EntityId>,
+    pub events: Vec<TimelineEvent>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// Returns the time written as `text`: a day count, or a date of `calendar`.
+pub fn parse_time(text: &str, calendar: Option<&Calendar>) -> Option<StoryTime> {
+    let text = text.trim();
+    if let Ok(time) = text.parse::<StoryTime>() {
+        return Some(time);
+    }
+    let calendar = calendar?;
+    calendar.to_day(calendar.parse(text)?).ok()
+}
+
+impl Timeline {
+    /// Returns the timeline of the events of `registry` and the scenes of `tree` dated with
+    /// `calendar`. Scenes are only dated if there is a calendar.
+    pub fn new(registry: &Registry, calendar: Option<&Calendar>, tree: &DocumentTree) -> Self {
+        let finder = MentionFinder::new(registry);
+        let takes_part = |id: &EntityId| {
+            registry.get(*id).is_some_and(|entity| {
+                matches!(entity.kind, EntityKind::Character | EntityKind::Location)
+            })
+        };
+        let mut events = Vec::new();
+
+        for event in registry.of_kind(EntityKind::Event) {
+            let Some(time) = event
+                .attributes
+                .get(DATE)
+                .and_then(|date| parse_time(date, calendar))
+            else {
+                continue;
+            };
+            let lanes = event
+                .relations
+                .iter()
+                .map(|relation| relation.to)
+                .chain(registry.relations_to(event.id()).map(|(from, _)| from))
+                .filter(takes_part)
+                .collect::<BTreeSet<EntityId>>();
+            let location = tree.scenes().into_iter().find_map(|scene| {
+                let text = tree.node(scene)?.text.as_str();
+                finder
+                    .find(text)
+                    .into_iter()
+                    .find(|mention| mention.entity == event.id())
+                    .map(|mention| (scene, mention.range))
+            });
+
+            events.push(TimelineEvent {
+                time,
+                title: event.name.clone(),
+                source: TimelineSource::Entity(event.id()),
+                location,
+                lanes: lanes.into_iter().collect(),
+            });
+        }
+
+        if let Some(calendar) = calendar {
+            events.extend(Self::dated_scenes(calendar, tree, &finder, &takes_part));
+        }
+        events.sort_by(|lhs, rhs| lhs.time.cmp(&rhs.time).then(lhs.title.cmp(&rhs.title)));
+
+        let mut lanes = events
+            .iter()
+            .flat_map(|event| event.lanes.iter().copied())
+            .collect::<BTreeSet<EntityId>>()
+            .into_iter()
+            .filter_map(|id| registry.get(id))
+            .collect::<Vec<_>>();
+        lanes.sort_by(|lhs, rhs| lhs.kind.cmp(&rhs.kind).then(lhs.name.cmp(&rhs.name)));
+
+        Self {
+            lanes: lanes.into_iter().map(|entity| entity.id()).collect(),
+            events,
+        }
+    }
+
+    /// Returns an event for every scene of `tree` with a date of `calendar` in it.
+    fn dated_scenes(
+        calendar: &Calendar,
+        tree: &DocumentTree,
+        finder: &MentionFinder,
+        takes_part: &impl Fn(&EntityId) -> bool,
+    ) -> Vec<TimelineEvent> {
+        let mut events = Vec::new();
+        for scene in tree.scenes() {
+            let Some(node) = tree.node(scene) else {
+                continue;
+            };
+            let Some((range, time)) = calendar.node_day(node) else {
+                continue;
+            };
+
+            let lanes = finder
+                .find(node.text.as_str())
+                .into_iter()
+                .map(|mention| mention.entity)
+                .chain(finder.entity_named(&node.metadata.pov))
+                .chain(finder.entity_named(&node.metadata.location))
+                .filter(takes_part)
+                .collect::<BTreeSet<EntityId>>();
+            let chapter = tree
+                .chapter_of(scene)
+                .and_then(|chapter| tree.node(chapter))
+                .map(|chapter| chapter.title.as_str())
+                .unwrap_or_default();
+            events.push(TimelineEvent {
+                time,
+                title: format!("{chapter}: {}", node.title),
+                source: TimelineSource::Scene(scene),
+                location: Some((scene, range)),
+                lanes: lanes.into_iter().collect(),
+            });
+        }
+        events
+    }
+
+    /// Returns the times of the first and the last event.
+    pub fn span(&self) -> Option<(StoryTime, StoryTime)> {
+        Some((self.events.first()?.time, self.events.last()?.time))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
@@ -0,0 +1,433 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Travel module.
+//!
+//! Routes join locations with a distance and the ways they can be travelled. The [`TravelMap`]
+//! finds the fastest way between two places, and [`check_travel`] flags characters who reach a
+//! place sooner than it can be reached. A place is reached at once from the places it lies within
+//! and the places that lie within it, but not from a place that only shares a container with it:
+//! two rooms of a castle are as far apart as the routes between them.
+
+use crate::{
+    calendar::Calendar,
+    entity::{EntityId, EntityKind},
+    mention::MentionFinder,
+    registry::{LoreError, Registry},
+    relation::{RelationKind, StoryTime},
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use tree::{DocumentTree, NodeId};
+
+/// Hours in a day of story time.
+const HOURS: u64 = 24_u64;
+
+/// A way of travelling along a [`Route`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TravelMode {
+    Foot,
+    Horse,
+    Cart,
+    Ship,
+    Flight,
+}
+
+/// A way from the location holding the route to the location `to`, and back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Route {
+    pub to: EntityId,
+    /// Length of the route, in the unit of the [`TravelMode::speed`], such as kilometres.
+    pub distance: u32,
+    /// The ways the route can be travelled, every way if empty.
+    #[serde(default)]
+    pub modes: Vec<TravelMode>,
+}
+
+/// The fastest ways between the locations of a [`Registry`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TravelMap {
+    /// Every location reached along a route from a location, with the hours it takes.
+    ways: BTreeMap<EntityId, Vec<(EntityId, u64)>>,
+    /// The locations each location lies within.
+    containers: BTreeMap<EntityId, Vec<EntityId>>,
+    /// The locations that lie within each location.
+    contents: BTreeMap<EntityId, Vec<EntityId>>,
+}
+
+/// A character who is at `from` in one scene and at `to` in a later one, with less story time in
+/// between than the fastest way takes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Journey {
+    pub character: EntityId,
+    pub from: EntityId,
+    pub to: EntityId,
+    /// The scene the character left from.
+    pub departure: NodeId,
+    /// The scene the character arrives in, and the byte range of the mention of `to` in it.
+    pub arrival: NodeId,
+    pub range: Range<usize>,
+    /// Days between the two scenes.
+    pub days: StoryTime,
+    /// Hours the fastest way takes.
+    pub hours: u64,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl TravelMode {
+    /// Every mode in the order they are listed in the UI.
+    pub const ALL: [TravelMode; 5_usize] = [
+        TravelMode::Foot,
+        TravelMode::Horse,
+        TravelMode::Cart,
+        TravelMode::Ship,
+        TravelMode::Flight,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TravelMode::Foot => "On foot",
+            TravelMode::Horse => "On horseback",
+            TravelMode::Cart => "By cart",
+            TravelMode::Ship => "By ship",
+            TravelMode::Flight => "By air",
+        }
+    }
+
+    /// Returns the distance covered in a day, taking rests into account.
+    pub fn speed(&self) -> u32 {
+        match self {
+            TravelMode::Foot => 30_u32,
+            TravelMode::Horse => 60_u32,
+            TravelMode::Cart => 40_u32,
+            TravelMode::Ship => 150_u32,
+            TravelMode::Flight => 500_u32,
+        }
+    }
+}
+
+impl Display for TravelMode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Route {
+    /// Returns a route of `distance` to `to` that can be travelled every way.
+    pub fn new(to: EntityId, distance: u32) -> Self {
+        Self {
+            to,
+            distance,
+            modes: Vec::new(),
+        }
+    }
+
+    /// Returns the fastest way to travel the route.
+    pub fn fastest(&self) -> TravelMode {
+        let modes = match self.modes.is_empty() {
+            true => TravelMode::ALL.as_slice(),
+            false => self.modes.as_slice(),
+        };
+        modes
+            .iter()
+            .copied()
+            .max_by_key(TravelMode::speed)
+            .unwrap_or(TravelMode::Foot)
+    }
+
+    /// Returns the hours it takes to travel the route the fastest way, rounded up.
+    pub fn hours(&self) -> u64 {
+        let speed = self.fastest().speed() as u64;
+        (self.distance as u64 * HOURS).div_ceil(speed)
+    }
+
+    /// Returns an error if the route cannot start from the location `from` of `registry`.
+    pub fn validate(&self, from: EntityId, registry: &Registry) -> Result<(), LoreError> {
+        if from == self.to {
+            return Err(LoreError::SelfRelation(from));
+        }
+        for id in [from, self.to] {
+            match registry.get(id) {
+                Some(entity) if entity.kind == EntityKind::Location => {}
+                Some(_) => return Err(LoreError::NotALocation(id)),
+                None => return Err(LoreError::EntityNotFound(id)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the route as it reads from the location holding it, such as `120 by ship`.
+    pub fn describe(&self) -> String {
+        match self.modes.as_slice() {
+            [] => format!("{}", self.distance),
+            modes => {
+                let modes = modes
+                    .iter()
+                    .map(|mode| mode.as_str().to_lowercase())
+                    .collect::<Vec<String>>();
+                format!("{} {}", self.distance, modes.join(" or "))
+            }
+        }
+    }
+}
+
+impl TravelMap {
+    /// Returns the ways between the locations of `registry`, along its routes and in and out of
+    /// the places they lie within.
+    pub fn new(registry: &Registry) -> Self {
+        let mut map = Self::default();
+        let is_location = |id: EntityId| {
+            registry
+                .get(id)
+                .is_some_and(|entity| entity.kind == EntityKind::Location)
+        };
+
+        for location in registry.of_kind(EntityKind::Location) {
+            for route in location.routes.iter().filter(|route| is_location(route.to)) {
+                let hours = route.hours();
+                map.ways
+                    .entry(location.id())
+                    .or_default()
+                    .push((route.to, hours));
+                map.ways
+                    .entry(route.to)
+                    .or_default()
+                    .push((location.id(), hours));
+            }
+        }
+        registry
+            .relations()
+            .filter(|(from, relation)| {
+                relation.kind == RelationKind::PartOf
+                    && is_location(*from)
+                    && is_location(relation.to)
+            })
+            .for_each(|(from, relation)| {
+                map.containers.entry(from).or_default().push(relation.to);
+                map.contents.entry(relation.to).or_default().push(from);
+            });
+
+        map
+    }
+
+    /// Returns the hours the fastest way from `from` to `to` takes, or [`None`] if no route leads
+    /// there. Leaving a place for the places it lies within and entering the places within it take
+    /// no time, but a place left that way is only entered again along a route.
+    pub fn hours(&self, from: EntityId, to: EntityId) -> Option<u64> {
+        // A state is a location and whether it was reached by leaving a place within it.
+        let mut best = BTreeMap::from([((from, false), 0_u64)]);
+        let mut queue = BinaryHeap::from([Reverse((0_u64, from, false))]);
+
+        while let Some(Reverse((hours, id, left))) = queue.pop() {
+            if id == to {
+                return Some(hours);
+            }
+            if best.get(&(id, left)).is_some_and(|known| *known < hours) {
+                continue;
+            }
+            let routes = self
+                .ways
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .map(|(next, way)| (*next, hours + way, false));
+            let up = self
+                .containers
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .map(|next| (*next, hours, true));
+            let down = self
+                .contents
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .filter(|_| !left)
+                .map(|next| (*next, hours, false));
+            for (next, arrival, left) in routes.chain(up).chain(down) {
+                if best.get(&(next, left)).is_none_or(|known| arrival < *known) {
+                    best.insert((next, left), arrival);
+                    queue.push(Reverse((arrival, next, left)));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Journey {
+    /// Returns a sentence explaining the problem with the names of the entities in `registry`.
+    pub fn describe(&self, registry: &Registry) -> String {
+        let name = |id: &EntityId| {
+            registry
+                .get(*id)
+                .map(|entity| entity.name.clone())
+                .unwrap_or_else(|| id.to_string())
+        };
+        let days = match self.days {
+            0_i64 => "the same day".to_string(),
+            1_i64 => "1 day".to_string(),
+            days => format!("{days} days"),
+        };
+        format!(
+            "{} goes from {} to {} in {days}, but the fastest way takes {:.1} days.",
+            name(&self.character),
+            name(&self.from),
+            name(&self.to),
+            self.hours as f64 / HOURS as f64,
+        )
+    }
+}
+
+/// Returns the journeys between the scenes of `tree` that are too fast for the routes of
+/// `registry`. Scenes are dated with `calendar` as in [`Calendar::node_day`] and placed at the
+/// location of their metadata or else the first location they mention. Characters mentioned in,
+/// or the point of view of, two scenes in a row, in story time, make the journey between them.
+/// Dates only name days, so a journey may take up to a day longer than the days in between.
+/// Journeys without a known route are never flagged.
+pub fn check_travel(registry: &Registry, calendar: &Calendar, tree: &DocumentTree) -> Vec<Journey> {
+    let finder = MentionFinder::new(registry);
+    let kind = |id: EntityId| registry.get(id).map(|entity| entity.kind);
+    let mut scenes = Vec::new();
+
+    for scene in tree.scenes() {
+        let Some(node) = tree.node(scene) else {
+            continue;
+        };
+        let Some((_, time)) = calendar.node_day(node) else {
+            continue;
+        };
+        let text = node.text.as_str();
+        let mentions = finder.find(text);
+        let setting = finder
+            .entity_named(&node.metadata.location)
+            .filter(|id| kind(*id) == Some(EntityKind::Location))
+            .map(|id| (id, 0_usize..0_usize));
+        let Some((place, range)) = setting.or_else(|| {
+            mentions
+                .iter()
+                .find(|mention| kind(mention.entity) == Some(EntityKind::Location))
+                .map(|mention| (mention.entity, mention.range.clone()))
+        }) else {
+            continue;
+        };
+        let mut characters = mentions
+            .iter()
+            .map(|mention| mention.entity)
+            .chain(finder.entity_named(&node.metadata.pov))
+            .filter(|id| kind(*id) == Some(EntityKind::Character))
+            .collect::<Vec<EntityId>>();
+        characters.sort();
+        characters.dedup();
+        scenes.push((time, scene, place, range, characters));
+    }
+    // The sort is stable, so scenes of the same day keep the order of the manuscript.
+    scenes.sort_by_key(|(time, ..)| *time);
+
+    let map = TravelMap::new(registry);
+    let mut last = BTreeMap::<EntityId, (StoryTime, NodeId, EntityId)>::new();
+    let mut journeys = Vec::new();
+    for (time, scene, place, range, characters) in scenes {
+        for character in characters {
+            if let Some((since, departure, from)) = last.insert(character, (time, scene, place)) {
+                if from == place {
+                    continue;
+                }
+                let days = time - since;
+                let Some(hours) = map.hours(from, place) else {
+                    continue;
+                };
+                if hours > (days as u64 + 1_u64) * HOURS {
+                    journeys.push(Journey {
+                        character,
+                        from,
+                        to: place,
+                        departure,
+                        arrival: scene,
+                        range: range.clone(),
+                        days,
+                        hours,
+                    });
+                }
+            }
+        }
+    }
+    journeys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relation::Relation;
+
+    fn place(registry: &mut Registry, name: &str) -> EntityId {
+        registry.add(EntityKind::Location, name).unwrap()
+    }
+
+    fn within(registry: &mut Registry, inner: EntityId, outer: EntityId) {
+        registry
+            .relate(inner, Relation::new(RelationKind::PartOf, outer))
+            .unwrap();
+    }
+
+    #[test]
+    fn containers_are_reached_at_once() {
+        let mut registry = Registry::new();
+        let city = place(&mut registry, "Varn");
+        let tavern = place(&mut registry, "Tavern");
+        let cellar = place(&mut registry, "Cellar");
+        within(&mut registry, tavern, city);
+        within(&mut registry, cellar, tavern);
+
+        let map = TravelMap::new(&registry);
+        assert_eq!(map.hours(cellar, city), Some(0_u64));
+        assert_eq!(map.hours(city, cellar), Some(0_u64));
+    }
+
+    #[test]
+    fn siblings_are_not_reached_through_their_container() {
+        let mut registry = Registry::new();
+        let kingdom = place(&mut registry, "Aster");
+        let north = place(&mut registry, "Northwatch");
+        let south = place(&mut registry, "Southwatch");
+        within(&mut registry, north, kingdom);
+        within(&mut registry, south, kingdom);
+
+        assert_eq!(TravelMap::new(&registry).hours(north, south), None);
+
+        registry
+            .add_route(north, Route::new(south, 60_u32))
+            .unwrap();
+        let map = TravelMap::new(&registry);
+        assert_eq!(
+            map.hours(north, south),
+            Some(Route::new(south, 60_u32).hours())
+        );
+    }
+
+    #[test]
+    fn routes_between_containers_lead_to_their_contents() {
+        let mut registry = Registry::new();
+        let varn = place(&mut registry, "Varn");
+        let tavern = place(&mut registry, "Tavern");
+        let osk = place(&mut registry, "Osk");
+        let market = place(&mut registry, "Market");
+        within(&mut registry, tavern, varn);
+        within(&mut registry, market, osk);
+        let route = Route {
+            to: osk,
+            distance: 30_u32,
+            modes: vec![TravelMode::Foot],
+        };
+        registry.add_route(varn, route).unwrap();
+
+        let map = TravelMap::new(&registry);
+        assert_eq!(map.hours(tavern, market), Some(24_u64));
+        assert_eq!(map.hours(market, tavern), Some(24_u64));
+    }
+}
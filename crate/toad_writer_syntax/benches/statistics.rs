@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Statistics benchmark.
+//!
+//! Compares counting the whole text on every keystroke with [`LineStatistics`] updating only the
+//! edited line. The incremental time should stay flat as the manuscript grows.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use toad_writer_syntax::statistics::{LineStatistics, Statistics};
+
+/// A paragraph of about a hundred words.
+const PARAGRAPH: &str = "The toad sat by the well, watching the lanterns sway in the wind while \
+    the villagers argued about the harvest. Nobody noticed when it hopped onto the rim, nor when \
+    it began to speak in a voice older than the stones themselves. It told them of rain that \
+    would not come, of roads that would flood, and of a traveller who had not yet left home but \
+    who would arrive before the first frost. Then it was gone.";
+/// Number of keystrokes typed in every run.
+const KEYSTROKES: usize = 100_usize;
+/// Sizes of the manuscripts in paragraphs.
+const SIZES: [usize; 4_usize] = [10_usize, 100_usize, 1_000_usize, 10_000_usize];
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+fn main() {
+    println!(
+        "{:>10} {:>8} {:>16} {:>16}",
+        "paragraphs", "words", "full recount", "incremental"
+    );
+
+    for size in SIZES {
+        let mut lines = vec![PARAGRAPH.to_string(); size];
+        let text = lines.join("\n");
+        let words = Statistics::new(text.as_str()).words;
+        let full = time_full(&text);
+        let incremental = time_incremental(&mut lines, &text);
+
+        println!("{size:>10} {words:>8} {full:>16.2?} {incremental:>16.2?}");
+    }
+}
+
+/// Returns the time taken per keystroke when the whole text is counted again.
+fn time_full(text: &str) -> Duration {
+    let mut text = text.to_string();
+    let start = Instant::now();
+    for _ in 0_usize..KEYSTROKES {
+        text.push('a');
+        black_box(Statistics::new(text.as_str()));
+    }
+    start.elapsed() / KEYSTROKES as u32
+}
+
+/// Returns the time taken per keystroke when only the edited line is counted again.
+fn time_incremental(lines: &mut [String], text: &str) -> Duration {
+    let mut statistics = LineStatistics::new(text);
+    let middle = lines.len() / 2_usize;
+    let start = Instant::now();
+    for _ in 0_usize..KEYSTROKES {
+        lines[middle].push('a');
+        statistics.splice(middle, 1_usize, &[lines[middle].as_str()]);
+        black_box(statistics.total());
+    }
+    start.elapsed() / KEYSTROKES as u32
+}
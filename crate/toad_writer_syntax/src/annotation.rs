@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Annotation module.
+//!
+//! Annotations tell the lore about the story without being part of it. They are written between
+//! double braces on a single line and left out of exports:
+//!
+//! - `{{date: 3 Thaw 12}}` dates the scene it is written in.
+//! - `{{Mara|the old woman}}` marks `the old woman` as a mention of Mara. Only the text after the
+//!   bar is part of the story.
+//! - `{{fact: Mara | hair = grey}}` states the value an attribute of Mara has at that point.
+//! - `{{scene: pov = Mara | date = 3 Thaw 12 | location = Varn | status = Draft}}` holds the
+//!   [`SceneMetadata`] of the scene it is written in, usually on its first line.
+//!
+//! Double braces that do not hold one of these are left as they are.
+
+use std::ops::Range;
+use tree::{SceneMetadata, SceneStatus};
+
+/// Opens an annotation.
+pub const OPEN: &str = "{{";
+/// Closes an annotation.
+pub const CLOSE: &str = "}}";
+/// Starts the annotation of a date.
+const DATE: &str = "date:";
+/// Starts the annotation of a fact.
+const FACT: &str = "fact:";
+/// Starts the annotation of the metadata of a scene.
+const SCENE: &str = "scene:";
+/// Separates the entity of a reference or a fact from the rest.
+const SEPARATOR: char = '|';
+/// Separates the attribute of a fact from its value.
+const ASSIGN: char = '=';
+
+/// What an [`Annotation`] states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// The date of the scene, as written.
+    Date(String),
+    /// The name of an entity, and the byte range in the text of the words that mention it.
+    Reference { entity: String, shown: Range<usize> },
+    /// The value an attribute of the entity named `entity` has.
+    Fact {
+        entity: String,
+        key: String,
+        value: String,
+    },
+    /// The metadata of the scene.
+    Scene(SceneMetadata),
+}
+
+/// An annotation found in text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    /// Byte range of the annotation in the text, braces included.
+    pub range: Range<usize>,
+    pub kind: AnnotationKind,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Annotation {
+    /// Returns the byte ranges of the annotation that are not part of the story: all of it but
+    /// the shown text of a reference.
+    pub fn hidden(&self) -> Vec<Range<usize>> {
+        match &self.kind {
+            AnnotationKind::Reference { shown, .. } => {
+                vec![self.range.start..shown.start, shown.end..self.range.end]
+            }
+            _ => vec![self.range.clone()],
+        }
+    }
+
+    /// Returns the byte ranges in `text` of the entity names written in the annotation: the
+    /// entity of a reference or a fact, and the point of view and location of a scene.
+    pub fn names(&self, text: &str) -> Vec<Range<usize>> {
+        let inner = self.range.start + OPEN.len()..self.range.end - CLOSE.len();
+        let content = &text[inner.clone()];
+        let names = match &self.kind {
+            AnnotationKind::Date(_) => Vec::new(),
+            AnnotationKind::Reference { .. } => content
+                .find(SEPARATOR)
+                .map(|bar| 0_usize..bar)
+                .into_iter()
+                .collect(),
+            AnnotationKind::Fact { .. } => content
+                .find(FACT)
+                .zip(content.find(SEPARATOR))
+                .map(|(start, bar)| start + FACT.len()..bar)
+                .into_iter()
+                .collect(),
+            AnnotationKind::Scene(_) => {
+                let mut names = Vec::new();
+                let mut start = content
+                    .find(SCENE)
+                    .map_or(0_usize, |start| start + SCENE.len());
+                for field in content[start..].split(SEPARATOR) {
+                    if let Some((key, _)) = field.split_once(ASSIGN) {
+                        if matches!(key.trim().to_lowercase().as_str(), "pov" | "location") {
+                            names.push(start + key.len() + ASSIGN.len_utf8()..start + field.len());
+                        }
+                    }
+                    start += field.len() + SEPARATOR.len_utf8();
+                }
+                names
+            }
+        };
+        names
+            .into_iter()
+            .map(|range| {
+                let name = &content[range.clone()];
+                let start = inner.start + range.start + (name.len() - name.trim_start().len());
+                start..start + name.trim().len()
+            })
+            .filter(|range| !range.is_empty())
+            .collect()
+    }
+}
+
+/// Returns every annotation of `text` in the order they appear.
+pub fn annotations(text: &str) -> Vec<Annotation> {
+    let mut found = Vec::new();
+    let mut position = 0_usize;
+
+    while let Some(start) = text[position..].find(OPEN).map(|start| position + start) {
+        let inner = start + OPEN.len();
+        let line_end = text[inner..]
+            .find('\n')
+            .map_or(text.len(), |end| inner + end);
+        let Some(end) = text[inner..line_end].find(CLOSE).map(|end| inner + end) else {
+            position = inner;
+            continue;
+        };
+        match parse(text, inner..end) {
+            Some(kind) => {
+                found.push(Annotation {
+                    range: start..end + CLOSE.len(),
+                    kind,
+                });
+                position = end + CLOSE.len();
+            }
+            None => position = inner,
+        }
+    }
+    found
+}
+
+/// Returns `text` with the hidden parts of its annotations replaced by spaces, so that byte
+/// ranges in it are ranges in `text` as well.
+pub fn hide_annotations(text: &str) -> String {
+    let mut hidden = text.to_string();
+    for range in annotations(text).iter().flat_map(Annotation::hidden).rev() {
+        hidden.replace_range(range.clone(), " ".repeat(range.len()).as_str());
+    }
+    hidden
+}
+
+/// Returns `text` without the hidden parts of its annotations, to be exported. Lines that only
+/// held annotations are left out, and no double or leading space is left where an annotation
+/// was.
+pub fn strip_annotations(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let found = annotations(line);
+        if found.is_empty() {
+            stripped.push_str(line);
+            continue;
+        }
+
+        let mut kept = String::with_capacity(line.len());
+        let mut position = 0_usize;
+        for hidden in found.iter().flat_map(Annotation::hidden) {
+            kept.push_str(&line[position..hidden.start]);
+            position = hidden.end;
+            if (kept.is_empty() || kept.ends_with(' ')) && line[position..].starts_with(' ') {
+                position += 1_usize;
+            }
+        }
+        kept.push_str(&line[position..]);
+
+        let content = kept.trim_end_matches(['\n', '\r']);
+        if content.trim().is_empty() {
+            continue;
+        }
+        stripped.push_str(content.trim_end());
+        stripped.push_str(&kept[content.len()..]);
+    }
+    stripped
+}
+
+/// Returns the annotation that holds `metadata`, such as `{{scene: pov = Mara | status = Draft}}`.
+/// Fields left empty are left out, and characters that would end a field or the annotation are
+/// dropped from the values.
+pub fn scene_annotation(metadata: &SceneMetadata) -> String {
+    let status = metadata.status.to_string();
+    let fields = [
+        ("pov", metadata.pov.as_str()),
+        ("date", metadata.date.as_str()),
+        ("location", metadata.location.as_str()),
+        ("status", status.as_str()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key, value.replace(CLOSE, "").replace([SEPARATOR, '\n'], "")))
+    .filter(|(_, value)| !value.trim().is_empty())
+    .map(|(key, value)| format!("{key} {ASSIGN} {}", value.trim()))
+    .collect::<Vec<String>>();
+    format!("{OPEN}{SCENE} {}{CLOSE}", fields.join(" | "))
+}
+
+/// Returns the metadata held by the fields of a scene annotation, or [`None`] if a field is not
+/// one of the metadata.
+fn parse_scene(fields: &str) -> Option<SceneMetadata> {
+    let mut metadata = SceneMetadata::default();
+    for field in fields
+        .split(SEPARATOR)
+        .filter(|field| !field.trim().is_empty())
+    {
+        let (key, value) = field.split_once(ASSIGN)?;
+        let value = value.trim().to_string();
+        match key.trim().to_lowercase().as_str() {
+            "pov" => metadata.pov = value,
+            "date" => metadata.date = value,
+            "location" => metadata.location = value,
+            "status" => {
+                metadata.status = SceneStatus::ALL
+                    .into_iter()
+                    .find(|status| status.to_string().eq_ignore_ascii_case(value.as_str()))?;
+            }
+            _ => return None,
+        }
+    }
+    Some(metadata)
+}
+
+/// Returns what the annotation with the inner byte range `inner` of `text` states, or [`None`] if
+/// it is not an annotation.
+fn parse(text: &str, inner: Range<usize>) -> Option<AnnotationKind> {
+    let content = &text[inner.clone()];
+    let trimmed = content.trim_start();
+
+    if let Some(date) = trimmed.strip_prefix(DATE) {
+        let date = date.trim();
+        return (!date.is_empty()).then(|| AnnotationKind::Date(date.to_string()));
+    }
+    if let Some(fact) = trimmed.strip_prefix(FACT) {
+        let (entity, assignment) = fact.split_once(SEPARATOR)?;
+        let (key, value) = assignment.split_once(ASSIGN)?;
+        let (entity, key, value) = (entity.trim(), key.trim(), value.trim());
+        if entity.is_empty() || key.is_empty() || value.is_empty() {
+            return None;
+        }
+        return Some(AnnotationKind::Fact {
+            entity: entity.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    if let Some(fields) = trimmed.strip_prefix(SCENE) {
+        return parse_scene(fields).map(AnnotationKind::Scene);
+    }
+
+    let bar = content.find(SEPARATOR)?;
+    let entity = content[..bar].trim();
+    let shown = &content[bar + SEPARATOR.len_utf8()..];
+    let start = inner.start + bar + SEPARATOR.len_utf8() + (shown.len() - shown.trim_start().len());
+    let end = inner.end - (shown.len() - shown.trim_end().len());
+    if entity.is_empty() || start >= end {
+        return None;
+    }
+    Some(AnnotationKind::Reference {
+        entity: entity.to_string(),
+        shown: start..end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statistics::{LineStatistics, Statistics};
+
+    const BARE: &str = "The old woman waited by the gate.";
+    const ANNOTATED: &str = "{{date: 3 Thaw 12}} The {{Mara|old woman}} waited \
+                             {{fact: Mara | hair = grey}} by the gate.";
+
+    #[test]
+    fn hidden_annotations_keep_the_byte_ranges() {
+        let hidden = hide_annotations(ANNOTATED);
+        assert_eq!(hidden.len(), ANNOTATED.len());
+        let start = ANNOTATED.find("old woman").unwrap();
+        assert_eq!(&hidden[start..start + "old woman".len()], "old woman");
+    }
+
+    #[test]
+    fn annotations_are_not_counted() {
+        let hidden = hide_annotations(ANNOTATED);
+        assert_eq!(
+            Statistics::new(hidden.as_str()).words,
+            Statistics::new(BARE).words
+        );
+        let stripped = strip_annotations(ANNOTATED);
+        let mut lines = LineStatistics::new(BARE);
+        lines.splice(0_usize, 1_usize, &[stripped.as_str()]);
+        assert_eq!(lines.total(), Statistics::new(BARE));
+    }
+
+    #[test]
+    fn stripped_annotations_leave_the_story() {
+        assert_eq!(strip_annotations(ANNOTATED), BARE);
+        assert_eq!(strip_annotations("{{date: 3 Thaw 12}}\nText.\n"), "Text.\n");
+        assert!(annotations("{{no annotation}}").is_empty());
+    }
+
+    #[test]
+    fn names_are_found_in_the_annotations() {
+        let text = "{{date: 3 Thaw 12}} {{Mara|old woman}} {{fact: Mara Venn | hair = grey}} \
+                    {{scene: pov = Tom | status = Draft | location =  Varn }}";
+        let names = annotations(text)
+            .iter()
+            .flat_map(|annotation| annotation.names(text))
+            .map(|range| &text[range])
+            .collect::<Vec<&str>>();
+        assert_eq!(names, ["Mara", "Mara Venn", "Tom", "Varn"]);
+    }
+
+    #[test]
+    fn scene_annotations_hold_the_metadata() {
+        let metadata = SceneMetadata {
+            pov: "Mara".to_string(),
+            date: "3 Thaw 12".to_string(),
+            location: String::new(),
+            status: SceneStatus::Revised,
+        };
+        let annotation = scene_annotation(&metadata);
+        assert_eq!(
+            annotation,
+            "{{scene: pov = Mara | date = 3 Thaw 12 | status = Revised}}"
+        );
+        assert_eq!(
+            annotations(annotation.as_str())[0_usize].kind,
+            AnnotationKind::Scene(metadata)
+        );
+        assert!(annotations("{{scene: mood = grim}}").is_empty());
+        assert_eq!(
+            strip_annotations(&format!("{annotation}\nText.\n")),
+            "Text.\n"
+        );
+    }
+}
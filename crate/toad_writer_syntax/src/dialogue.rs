@@ -0,0 +1,360 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Dialogue module.
+//!
+//! Finds the quoted dialogue of a document and guesses who speaks every line. Curly and straight
+//! quotes are both understood, single quotes inside double quotes (or the other way around) are
+//! part of the outer line, and apostrophes such as in `don't` are not quotes. Dialogue that is
+//! still open at the end of a paragraph ends there, as a new paragraph of the same speech opens
+//! with a quote again.
+//!
+//! Speakers are taken, in order, from a speech tag such as `said Mara` or `Mara asked`, from a
+//! known name in the narration of the paragraph, from another line of the same paragraph, and
+//! finally from the back and forth of a conversation between two speakers.
+
+use crate::segment::word_ranges;
+use std::ops::Range;
+use tree::{DocumentTree, NodeId};
+
+/// Verbs that tag a line of dialogue with its speaker.
+const SPEECH_VERBS: [&str; 28_usize] = [
+    "said",
+    "says",
+    "asked",
+    "asks",
+    "replied",
+    "replies",
+    "answered",
+    "whispered",
+    "shouted",
+    "yelled",
+    "cried",
+    "called",
+    "muttered",
+    "murmured",
+    "mumbled",
+    "added",
+    "continued",
+    "snapped",
+    "exclaimed",
+    "began",
+    "insisted",
+    "admitted",
+    "explained",
+    "laughed",
+    "sighed",
+    "hissed",
+    "growled",
+    "demanded",
+];
+/// Capitalised words that are not names, such as pronouns, titles, weekdays and months. Shared
+/// with the lore so that both tell names apart the same way.
+pub const NOT_NAMES: [&str; 46_usize] = [
+    "I",
+    "I'm",
+    "I'd",
+    "I'll",
+    "I've",
+    "He",
+    "She",
+    "They",
+    "We",
+    "You",
+    "It",
+    "The",
+    "A",
+    "An",
+    "His",
+    "Her",
+    "Then",
+    "But",
+    "And",
+    "So",
+    "When",
+    "Now",
+    "Still",
+    "Mr",
+    "Mrs",
+    "Ms",
+    "Dr",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// The kind of quote mark that opened a quote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Double,
+    Single,
+}
+
+/// A single line of dialogue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogueLine {
+    pub scene: NodeId,
+    /// Byte range of the line in the scene text, quote marks included.
+    pub range: Range<usize>,
+    /// The canonical name of the speaker if it could be guessed.
+    pub speaker: Option<String>,
+}
+
+/// Guesses the speakers of dialogue from speech tags and a list of known names.
+#[derive(Debug, Default, Clone)]
+pub struct SpeakerFinder {
+    /// Every known name or alias along with the canonical name, longest first.
+    names: Vec<(String, String)>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// Returns the byte ranges of the outermost quotes of `paragraph`, quote marks included.
+pub fn quote_ranges(paragraph: &str) -> Vec<Range<usize>> {
+    let chars = paragraph.char_indices().collect::<Vec<(usize, char)>>();
+    let mut ranges = Vec::new();
+    let mut open = Vec::<(Mark, usize)>::new();
+
+    for (index, (position, ch)) in chars.iter().copied().enumerate() {
+        let before = index
+            .checked_sub(1_usize)
+            .and_then(|before| chars.get(before))
+            .map(|(_, ch)| *ch);
+        let after = chars.get(index + 1_usize).map(|(_, ch)| *ch);
+        let top = open.last().map(|(mark, _)| *mark);
+
+        let closes = match ch {
+            '”' => open.iter().any(|(mark, _)| *mark == Mark::Double),
+            '"' => top == Some(Mark::Double),
+            '’' | '\'' => top == Some(Mark::Single) && !is_apostrophe(before, after),
+            _ => false,
+        };
+        if closes {
+            let mark = if matches!(ch, '”' | '"') {
+                Mark::Double
+            } else {
+                Mark::Single
+            };
+            while let Some((popped, start)) = open.pop() {
+                if popped == mark {
+                    if open.is_empty() {
+                        ranges.push(start..position + ch.len_utf8());
+                    }
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let opens = match ch {
+            '“' | '"' => Some(Mark::Double),
+            '‘' => Some(Mark::Single),
+            '\'' if !before.is_some_and(char::is_alphanumeric)
+                && after.is_some_and(|after| !after.is_whitespace()) =>
+            {
+                Some(Mark::Single)
+            }
+            _ => None,
+        };
+        if let Some(mark) = opens {
+            open.push((mark, position));
+        }
+    }
+    if let Some((_, start)) = open.first() {
+        ranges.push(*start..paragraph.len());
+    }
+
+    ranges
+}
+
+/// Returns true if a `'` between `before` and `after` is part of a word, as in `don't`.
+fn is_apostrophe(before: Option<char>, after: Option<char>) -> bool {
+    before.is_some_and(char::is_alphanumeric) && after.is_some_and(char::is_alphanumeric)
+}
+
+impl DialogueLine {
+    /// Returns the text of the line without its quote marks.
+    pub fn text<'a>(&self, scene: &'a str) -> &'a str {
+        scene[self.range.clone()].trim_matches(['"', '“', '”', '\'', '‘', '’'])
+    }
+}
+
+impl SpeakerFinder {
+    /// Returns a finder that knows `names`, pairs of a name or alias and the canonical name it
+    /// stands for.
+    pub fn new<'a>(names: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut names = names
+            .into_iter()
+            .filter(|(name, _)| !name.trim().is_empty())
+            .map(|(name, canonical)| (name.to_string(), canonical.to_string()))
+            .collect::<Vec<(String, String)>>();
+        names.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+        Self { names }
+    }
+
+    /// Returns every line of dialogue in `tree` in reading order.
+    pub fn lines(&self, tree: &DocumentTree) -> Vec<DialogueLine> {
+        tree.scenes()
+            .into_iter()
+            .flat_map(|scene| self.scene_lines(tree, scene))
+            .collect()
+    }
+
+    /// Returns the lines of dialogue in `scene`.
+    pub fn scene_lines(&self, tree: &DocumentTree, scene: NodeId) -> Vec<DialogueLine> {
+        let mut lines = Vec::new();
+        // The last two different speakers, the most recent last.
+        let mut conversation = Vec::<String>::new();
+
+        for paragraph in tree.paragraphs(scene) {
+            let quotes = quote_ranges(paragraph.text);
+            if quotes.is_empty() {
+                continue;
+            }
+
+            let mut speakers = quotes
+                .iter()
+                .enumerate()
+                .map(|(index, quote)| {
+                    let before = index
+                        .checked_sub(1_usize)
+                        .map_or(0_usize, |before| quotes[before].end);
+                    let after = quotes
+                        .get(index + 1_usize)
+                        .map_or(paragraph.text.len(), |next| next.start);
+                    self.tagged_speaker(&paragraph.text[quote.end..after], true)
+                        .or_else(|| {
+                            self.tagged_speaker(&paragraph.text[before..quote.start], false)
+                        })
+                })
+                .collect::<Vec<Option<String>>>();
+
+            let narration = self.narration_speaker(paragraph.text, &quotes);
+            let shared = speakers.iter().flatten().next().cloned().or(narration);
+            let alternating = match conversation.as_slice() {
+                [older, _] if shared.is_none() => Some(older.clone()),
+                _ => None,
+            };
+            for speaker in speakers.iter_mut().filter(|speaker| speaker.is_none()) {
+                *speaker = shared.clone().or_else(|| alternating.clone());
+            }
+
+            for (quote, speaker) in quotes.iter().zip(speakers) {
+                if let Some(speaker) = speaker.as_ref() {
+                    conversation.retain(|other| other != speaker);
+                    conversation.push(speaker.clone());
+                    if conversation.len() > 2_usize {
+                        conversation.remove(0_usize);
+                    }
+                }
+                lines.push(DialogueLine {
+                    scene,
+                    range: paragraph.scene_range(quote.clone()),
+                    speaker,
+                });
+            }
+        }
+
+        lines
+    }
+
+    /// Returns the speaker named by a speech tag in `narration`. A tag after the line only
+    /// counts in its first sentence, and a tag before it only in its last.
+    fn tagged_speaker(&self, narration: &str, after: bool) -> Option<String> {
+        let narration = if after {
+            narration
+                .split_inclusive(['.', '!', '?'])
+                .next()
+                .unwrap_or_default()
+        } else {
+            narration.rsplit(['.', '!', '?']).next().unwrap_or_default()
+        };
+        let words = word_ranges(narration);
+
+        for (index, range) in words.iter().enumerate() {
+            if !SPEECH_VERBS.contains(&narration[range.clone()].to_lowercase().as_str()) {
+                continue;
+            }
+            if let Some(name) = self.name_at(narration, &words[index + 1_usize..]) {
+                return Some(name);
+            }
+            let before = words[..index]
+                .iter()
+                .rposition(|range| !self.is_name_word(&narration[range.clone()]))
+                .map_or(0_usize, |position| position + 1_usize);
+            let tag = &words[before..index];
+            let known =
+                (0_usize..tag.len()).find_map(|start| self.known_name_at(narration, &tag[start..]));
+            if let Some(name) = known.or_else(|| self.name_at(narration, tag)) {
+                return Some(name);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the first known name in the narration of `paragraph`, the text outside `quotes`.
+    fn narration_speaker(&self, paragraph: &str, quotes: &[Range<usize>]) -> Option<String> {
+        let mut start = 0_usize;
+        let mut narration = String::new();
+        for quote in quotes {
+            narration.push_str(&paragraph[start..quote.start]);
+            narration.push('\n');
+            start = quote.end;
+        }
+        narration.push_str(&paragraph[start..]);
+
+        let words = word_ranges(narration.as_str());
+        (0_usize..words.len())
+            .find_map(|index| self.known_name_at(narration.as_str(), &words[index..]))
+    }
+
+    /// Returns the name that starts at the first of `words`: a known name, or else a run of
+    /// capitalised words.
+    fn name_at(&self, text: &str, words: &[Range<usize>]) -> Option<String> {
+        if let Some(name) = self.known_name_at(text, words) {
+            return Some(name);
+        }
+
+        let count = words
+            .iter()
+            .take_while(|range| self.is_name_word(&text[range.start..range.end]))
+            .count();
+        let (first, last) = (words.first()?, words.get(count.checked_sub(1_usize)?)?);
+        Some(text[first.start..last.end].to_string())
+    }
+
+    /// Returns the canonical name of the known name that starts at the first of `words`.
+    fn known_name_at(&self, text: &str, words: &[Range<usize>]) -> Option<String> {
+        let start = words.first()?.start;
+        self.names
+            .iter()
+            .find(|(name, _)| {
+                text[start..].starts_with(name.as_str())
+                    && words.iter().any(|range| range.end == start + name.len())
+            })
+            .map(|(_, canonical)| canonical.clone())
+    }
+
+    /// Returns true if `word` looks like part of a name.
+    fn is_name_word(&self, word: &str) -> bool {
+        word.chars().next().is_some_and(char::is_uppercase) && !NOT_NAMES.contains(&word)
+    }
+}
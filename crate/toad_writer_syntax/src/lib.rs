@@ -1,4 +1,13 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
 
-//! Library for handling the format of Toad Writer documents.
+//! Library for handling the syntax of Toad Writer documents.
+
+pub mod annotation;
+pub mod dialogue;
+pub mod lint;
+pub mod readability;
+pub mod search;
+pub mod segment;
+pub mod spell;
+pub mod statistics;
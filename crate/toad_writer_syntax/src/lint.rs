@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Lint module.
+//!
+//! A prose linter that runs a set of [`Rule`]s over the paragraphs of a [`DocumentTree`]. Every
+//! rule can be turned off or given another [`Severity`] through a [`LintConfig`].
+
+mod rules;
+
+use crate::annotation::hide_annotations;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use tree::{DocumentTree, NodeId, Paragraph};
+
+/// How much a [`Diagnostic`] matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Hint,
+    Warning,
+    Error,
+}
+
+/// Something a [`Rule`] found in a scene.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub scene: NodeId,
+    /// Byte range in the scene text.
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+/// A [`Finding`] along with the rule that made it and its severity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Id of the [`Rule`].
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub scene: NodeId,
+    /// Byte range in the scene text.
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+/// A single lint check.
+pub trait Rule {
+    /// Returns the id used to configure the rule, such as `passive-voice`.
+    fn id(&self) -> &'static str;
+
+    /// Returns the name shown to the writer.
+    fn name(&self) -> &'static str;
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Returns the byte ranges in `text`, the text of a paragraph, that break the rule along
+    /// with a message for each.
+    fn check_paragraph(&self, _text: &str) -> Vec<(Range<usize>, String)> {
+        Vec::new()
+    }
+
+    /// Returns what the rule finds in `paragraphs`. The default checks every paragraph on its
+    /// own; rules that compare paragraphs with each other check them all at once instead.
+    fn check(&self, paragraphs: &[Paragraph]) -> Vec<Finding> {
+        paragraphs
+            .iter()
+            .flat_map(|paragraph| {
+                self.check_paragraph(paragraph.text)
+                    .into_iter()
+                    .map(|(range, message)| Finding {
+                        scene: paragraph.scene,
+                        range: paragraph.scene_range(range),
+                        message,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Whether a [`Rule`] runs and the [`Severity`] of what it finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleConfig {
+    pub enabled: bool,
+    pub severity: Severity,
+}
+
+/// The [`RuleConfig`] of every rule that does not use its defaults, by rule id.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LintConfig {
+    rules: BTreeMap<String, RuleConfig>,
+}
+
+/// Runs every built-in [`Rule`].
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Severity {
+    pub const ALL: [Severity; 3_usize] = [Severity::Hint, Severity::Warning, Severity::Error];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Hint => "Hint",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl LintConfig {
+    /// Returns the configuration of `rule`, its defaults if it was never changed.
+    pub fn get(&self, rule: &dyn Rule) -> RuleConfig {
+        self.rules.get(rule.id()).copied().unwrap_or(RuleConfig {
+            enabled: true,
+            severity: rule.default_severity(),
+        })
+    }
+
+    pub fn set(&mut self, rule: &dyn Rule, config: RuleConfig) {
+        self.rules.insert(rule.id().to_string(), config);
+    }
+}
+
+impl Linter {
+    /// Returns a linter with every built-in rule.
+    pub fn new() -> Self {
+        Self {
+            rules: rules::all(),
+        }
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+
+    /// Returns what the enabled rules find in every scene of `tree`, ordered by scene and
+    /// position.
+    pub fn lint(&self, tree: &DocumentTree, config: &LintConfig) -> Vec<Diagnostic> {
+        // Annotations are not part of the story, and hiding them keeps the byte ranges.
+        let mut tree = tree.clone();
+        for scene in tree.scenes() {
+            if let Some(node) = tree.node_mut(scene) {
+                node.text = hide_annotations(&node.text);
+            }
+        }
+        let paragraphs = tree.all_paragraphs();
+        let mut diagnostics = Vec::new();
+
+        for rule in self.rules() {
+            let RuleConfig { enabled, severity } = config.get(rule);
+            if !enabled {
+                continue;
+            }
+            diagnostics.extend(
+                rule.check(&paragraphs)
+                    .into_iter()
+                    .map(|finding| Diagnostic {
+                        rule: rule.id(),
+                        severity,
+                        scene: finding.scene,
+                        range: finding.range,
+                        message: finding.message,
+                    }),
+            );
+        }
+
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.scene, diagnostic.range.start));
+        diagnostics
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
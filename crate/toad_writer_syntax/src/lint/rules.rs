@@ -0,0 +1,399 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Rules module.
+//!
+//! The built-in lint [`Rule`]s.
+
+use crate::{
+    lint::{Finding, Rule, Severity},
+    segment::{sentence_ranges, word_ranges},
+};
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+use tree::Paragraph;
+
+/// Words ending in `ly` that are not adverbs or that are rarely a problem.
+const NOT_ADVERBS: [&str; 24_usize] = [
+    "only", "family", "early", "reply", "apply", "supply", "ally", "belly", "bully", "holy",
+    "daily", "likely", "lonely", "lovely", "ugly", "silly", "jelly", "fly", "july", "italy",
+    "friendly", "elderly", "rely", "really",
+];
+/// Forms of `to be` that start a passive construction.
+const BE_FORMS: [&str; 8_usize] = ["am", "is", "are", "was", "were", "be", "been", "being"];
+/// Past participles that do not end in `ed`.
+const IRREGULAR_PARTICIPLES: [&str; 36_usize] = [
+    "born",
+    "beaten",
+    "begun",
+    "bitten",
+    "blown",
+    "broken",
+    "brought",
+    "built",
+    "bought",
+    "caught",
+    "chosen",
+    "done",
+    "drawn",
+    "driven",
+    "eaten",
+    "fallen",
+    "forgotten",
+    "found",
+    "given",
+    "hidden",
+    "held",
+    "hit",
+    "kept",
+    "known",
+    "left",
+    "lost",
+    "made",
+    "paid",
+    "said",
+    "seen",
+    "sent",
+    "shot",
+    "sold",
+    "taken",
+    "thrown",
+    "written",
+];
+/// Words that filter the scene through a character instead of showing it.
+const FILTER_WORDS: [&str; 16_usize] = [
+    "saw",
+    "heard",
+    "felt",
+    "noticed",
+    "realized",
+    "realised",
+    "wondered",
+    "watched",
+    "seemed",
+    "decided",
+    "knew",
+    "thought",
+    "looked",
+    "sensed",
+    "observed",
+    "experienced",
+];
+/// Sentences with more words than this are too long.
+const LONG_SENTENCE: usize = 40_usize;
+/// Worn out phrases.
+const CLICHES: [&str; 20_usize] = [
+    "at the end of the day",
+    "avoid like the plague",
+    "better late than never",
+    "calm before the storm",
+    "cold as ice",
+    "dead as a doornail",
+    "easier said than done",
+    "every cloud has a silver lining",
+    "in the nick of time",
+    "last but not least",
+    "only time will tell",
+    "quiet as a mouse",
+    "read between the lines",
+    "scared to death",
+    "sent shivers down",
+    "time stood still",
+    "tip of the iceberg",
+    "heart skipped a beat",
+    "white as a sheet",
+    "without a doubt",
+];
+/// Words with more than one accepted spelling. A document should stick to one of them.
+const SPELLING_VARIANTS: [(&str, &str); 12_usize] = [
+    ("grey", "gray"),
+    ("colour", "color"),
+    ("favourite", "favorite"),
+    ("honour", "honor"),
+    ("realise", "realize"),
+    ("realised", "realized"),
+    ("centre", "center"),
+    ("theatre", "theater"),
+    ("travelled", "traveled"),
+    ("towards", "toward"),
+    ("amongst", "among"),
+    ("whilst", "while"),
+];
+
+/// The same word twice in a row, as in `the the`.
+pub struct RepeatedWords;
+
+/// Adverbs ending in `ly`.
+pub struct Adverbs;
+
+/// A form of `to be` followed by a past participle.
+pub struct PassiveVoice;
+
+/// Words such as `saw` or `felt` that distance the reader from the scene.
+pub struct FilterWords;
+
+/// Sentences longer than [`LONG_SENTENCE`] words.
+pub struct LongSentences;
+
+/// Phrases from [`CLICHES`].
+pub struct Cliches {
+    regex: Regex,
+}
+
+/// Both spellings from [`SPELLING_VARIANTS`] used in the same document.
+pub struct SpellingVariants;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// Returns every built-in rule.
+pub(super) fn all() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(RepeatedWords),
+        Box::new(Adverbs),
+        Box::new(PassiveVoice),
+        Box::new(FilterWords),
+        Box::new(LongSentences),
+        Box::new(Cliches::new()),
+        Box::new(SpellingVariants),
+    ]
+}
+
+/// Returns the byte range and lowercase text of every word in `text`.
+fn lowercase_words(text: &str) -> Vec<(Range<usize>, String)> {
+    word_ranges(text)
+        .into_iter()
+        .map(|range| {
+            let word = text[range.clone()].to_lowercase();
+            (range, word)
+        })
+        .collect()
+}
+
+fn is_adverb(word: &str) -> bool {
+    word.len() > 4_usize && word.ends_with("ly") && !NOT_ADVERBS.contains(&word)
+}
+
+fn is_participle(word: &str) -> bool {
+    (word.len() > 3_usize && word.ends_with("ed")) || IRREGULAR_PARTICIPLES.contains(&word)
+}
+
+impl Rule for RepeatedWords {
+    fn id(&self) -> &'static str {
+        "repeated-words"
+    }
+
+    fn name(&self) -> &'static str {
+        "Repeated words"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check_paragraph(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        lowercase_words(text)
+            .windows(2_usize)
+            .filter(|pair| pair[0_usize].1 == pair[1_usize].1)
+            .filter(|pair| {
+                text[pair[0_usize].0.end..pair[1_usize].0.start]
+                    .trim()
+                    .is_empty()
+            })
+            .map(|pair| {
+                let range = pair[0_usize].0.start..pair[1_usize].0.end;
+                (range, format!("“{}” is repeated.", pair[1_usize].1))
+            })
+            .collect()
+    }
+}
+
+impl Rule for Adverbs {
+    fn id(&self) -> &'static str {
+        "adverbs"
+    }
+
+    fn name(&self) -> &'static str {
+        "Overused adverbs"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Hint
+    }
+
+    fn check_paragraph(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        lowercase_words(text)
+            .into_iter()
+            .filter(|(_, word)| is_adverb(word))
+            .map(|(range, word)| {
+                let message = format!("“{word}” is an adverb, a stronger verb may fit better.");
+                (range, message)
+            })
+            .collect()
+    }
+}
+
+impl Rule for PassiveVoice {
+    fn id(&self) -> &'static str {
+        "passive-voice"
+    }
+
+    fn name(&self) -> &'static str {
+        "Passive voice"
+    }
+
+    fn check_paragraph(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        let words = lowercase_words(text);
+        let mut findings = Vec::new();
+
+        for (index, (range, word)) in words.iter().enumerate() {
+            if !BE_FORMS.contains(&word.as_str()) {
+                continue;
+            }
+            let mut next = index + 1_usize;
+            if words.get(next).is_some_and(|(_, word)| is_adverb(word)) {
+                next += 1_usize;
+            }
+            let Some((participle, _)) = words.get(next).filter(|(_, word)| is_participle(word))
+            else {
+                continue;
+            };
+            let range = range.start..participle.end;
+            let message = format!("“{}” may be passive voice.", &text[range.clone()]);
+            findings.push((range, message));
+        }
+
+        findings
+    }
+}
+
+impl Rule for FilterWords {
+    fn id(&self) -> &'static str {
+        "filter-words"
+    }
+
+    fn name(&self) -> &'static str {
+        "Filter words"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Hint
+    }
+
+    fn check_paragraph(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        lowercase_words(text)
+            .into_iter()
+            .filter(|(_, word)| FILTER_WORDS.contains(&word.as_str()))
+            .map(|(range, word)| {
+                let message = format!("“{word}” filters the scene through a character.");
+                (range, message)
+            })
+            .collect()
+    }
+}
+
+impl Rule for LongSentences {
+    fn id(&self) -> &'static str {
+        "long-sentences"
+    }
+
+    fn name(&self) -> &'static str {
+        "Very long sentences"
+    }
+
+    fn check_paragraph(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        sentence_ranges(text)
+            .into_iter()
+            .filter_map(|range| {
+                let count = word_ranges(&text[range.clone()]).len();
+                let message = format!("This sentence has {count} words.");
+                (count > LONG_SENTENCE).then_some((range, message))
+            })
+            .collect()
+    }
+}
+
+impl Cliches {
+    pub fn new() -> Self {
+        let pattern = format!(r"\b(?:{})\b", CLICHES.join("|"));
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .expect("Cliché pattern should be valid.");
+        Self { regex }
+    }
+}
+
+impl Default for Cliches {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rule for Cliches {
+    fn id(&self) -> &'static str {
+        "cliches"
+    }
+
+    fn name(&self) -> &'static str {
+        "Clichés"
+    }
+
+    fn check_paragraph(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        self.regex
+            .find_iter(text)
+            .map(|found| {
+                let message = format!("“{}” is a cliché.", found.as_str());
+                (found.range(), message)
+            })
+            .collect()
+    }
+}
+
+impl Rule for SpellingVariants {
+    fn id(&self) -> &'static str {
+        "spelling-variants"
+    }
+
+    fn name(&self) -> &'static str {
+        "Inconsistent spelling"
+    }
+
+    /// Flags the less common spelling of every pair used both ways across `paragraphs`. On a tie
+    /// the second spelling of the pair is flagged.
+    fn check(&self, paragraphs: &[Paragraph]) -> Vec<Finding> {
+        let words = paragraphs
+            .iter()
+            .map(|paragraph| (paragraph, lowercase_words(paragraph.text)))
+            .collect::<Vec<_>>();
+        let count = |variant: &str| {
+            words
+                .iter()
+                .flat_map(|(_, words)| words)
+                .filter(|(_, word)| word == variant)
+                .count()
+        };
+        let mut findings = Vec::new();
+
+        for (first, second) in SPELLING_VARIANTS {
+            let (first_count, second_count) = (count(first), count(second));
+            if first_count == 0_usize || second_count == 0_usize {
+                continue;
+            }
+            let (flagged, kept) = if first_count < second_count {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            for (paragraph, words) in &words {
+                let found = words.iter().filter(|(_, word)| word == flagged);
+                findings.extend(found.map(|(range, _)| Finding {
+                    scene: paragraph.scene,
+                    range: paragraph.scene_range(range.clone()),
+                    message: format!("“{flagged}” is also spelled “{kept}” in this document."),
+                }));
+            }
+        }
+
+        findings
+    }
+}
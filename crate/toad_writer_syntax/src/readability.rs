@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Readability module.
+//!
+//! Readability indices such as Flesch–Kincaid along with average sentence length, the share of
+//! dialogue and lexical diversity. Counts are kept apart from the scores so that the counts of
+//! scenes can be added up into chapters. Syllables are guessed from vowel groups, which is close
+//! enough for English prose.
+
+use crate::{
+    dialogue::quote_ranges,
+    segment::{sentence_ranges, word_ranges},
+};
+use std::collections::HashSet;
+use std::ops::AddAssign;
+use tree::{DocumentTree, NodeId};
+
+/// Vowels counted for syllables.
+const VOWELS: [char; 6_usize] = ['a', 'e', 'i', 'o', 'u', 'y'];
+/// Words with at least this many syllables are complex.
+const COMPLEX_SYLLABLES: usize = 3_usize;
+/// Column names of the CSV report.
+const CSV_HEADER: &str = "chapter,scene,words,sentences,average_sentence_length,\
+flesch_reading_ease,flesch_kincaid_grade,gunning_fog,coleman_liau,smog,automated_readability,\
+dialogue_share,lexical_diversity";
+
+/// The counts that readability scores are computed from.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Readability {
+    pub sentences: usize,
+    pub words: usize,
+    pub syllables: usize,
+    /// Letters and digits in words.
+    pub letters: usize,
+    /// Words with three syllables or more.
+    pub complex_words: usize,
+    /// Words between quotes.
+    pub dialogue_words: usize,
+    /// Every different word in lowercase.
+    pub vocabulary: HashSet<String>,
+}
+
+/// A row of a readability report, either a whole chapter or one of its scenes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportRow {
+    pub chapter: String,
+    /// The title of the scene, or [`None`] for the row of the whole chapter.
+    pub scene: Option<String>,
+    pub node: NodeId,
+    pub readability: Readability,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Readability {
+    /// Counts `text`. Every line is a paragraph, and dialogue left open at the end of a
+    /// paragraph is closed there.
+    pub fn new(text: &str) -> Self {
+        let mut readability = Self::default();
+
+        for line in text.lines() {
+            let dialogue = quote_ranges(line);
+            readability.sentences += sentence_ranges(line)
+                .into_iter()
+                .filter(|range| !word_ranges(&line[range.clone()]).is_empty())
+                .count();
+
+            for range in word_ranges(line) {
+                let word = line[range.clone()].to_lowercase();
+                let syllables = syllables(word.as_str());
+                readability.words += 1_usize;
+                readability.syllables += syllables;
+                readability.letters += word.chars().filter(|ch| ch.is_alphanumeric()).count();
+                if syllables >= COMPLEX_SYLLABLES {
+                    readability.complex_words += 1_usize;
+                }
+                if dialogue.iter().any(|quote| quote.contains(&range.start)) {
+                    readability.dialogue_words += 1_usize;
+                }
+                readability.vocabulary.insert(word);
+            }
+        }
+
+        readability
+    }
+
+    /// Returns the number of words per sentence.
+    pub fn average_sentence_length(&self) -> f64 {
+        ratio(self.words, self.sentences)
+    }
+
+    /// Returns the Flesch reading ease. Higher is easier, most prose scores between 60 and 80.
+    pub fn flesch_reading_ease(&self) -> f64 {
+        self.scored(|| {
+            206.835_f64
+                - 1.015_f64 * self.average_sentence_length()
+                - 84.6_f64 * ratio(self.syllables, self.words)
+        })
+    }
+
+    /// Returns the Flesch–Kincaid grade level.
+    pub fn flesch_kincaid_grade(&self) -> f64 {
+        self.scored(|| {
+            0.39_f64 * self.average_sentence_length() + 11.8_f64 * ratio(self.syllables, self.words)
+                - 15.59_f64
+        })
+    }
+
+    /// Returns the Gunning fog index.
+    pub fn gunning_fog(&self) -> f64 {
+        self.scored(|| {
+            0.4_f64
+                * (self.average_sentence_length() + 100_f64 * ratio(self.complex_words, self.words))
+        })
+    }
+
+    /// Returns the Coleman–Liau index.
+    pub fn coleman_liau(&self) -> f64 {
+        self.scored(|| {
+            0.0588_f64 * 100_f64 * ratio(self.letters, self.words)
+                - 0.296_f64 * 100_f64 * ratio(self.sentences, self.words)
+                - 15.8_f64
+        })
+    }
+
+    /// Returns the SMOG grade.
+    pub fn smog(&self) -> f64 {
+        self.scored(|| {
+            1.043_f64 * (30_f64 * ratio(self.complex_words, self.sentences)).sqrt() + 3.1291_f64
+        })
+    }
+
+    /// Returns the automated readability index.
+    pub fn automated_readability(&self) -> f64 {
+        self.scored(|| {
+            4.71_f64 * ratio(self.letters, self.words) + 0.5_f64 * self.average_sentence_length()
+                - 21.43_f64
+        })
+    }
+
+    /// Returns the share of the words that are dialogue, from `0` to `1`.
+    pub fn dialogue_share(&self) -> f64 {
+        ratio(self.dialogue_words, self.words)
+    }
+
+    /// Returns the number of different words for every word, from `0` to `1`.
+    pub fn lexical_diversity(&self) -> f64 {
+        ratio(self.vocabulary.len(), self.words)
+    }
+
+    /// Returns `score`, or `0` for text without words or sentences.
+    fn scored(&self, score: impl Fn() -> f64) -> f64 {
+        if self.words == 0_usize || self.sentences == 0_usize {
+            return 0_f64;
+        }
+        score()
+    }
+}
+
+impl AddAssign<&Readability> for Readability {
+    fn add_assign(&mut self, rhs: &Readability) {
+        self.sentences += rhs.sentences;
+        self.words += rhs.words;
+        self.syllables += rhs.syllables;
+        self.letters += rhs.letters;
+        self.complex_words += rhs.complex_words;
+        self.dialogue_words += rhs.dialogue_words;
+        self.vocabulary.extend(rhs.vocabulary.iter().cloned());
+    }
+}
+
+impl ReportRow {
+    /// Returns the row as a line of CSV.
+    pub fn to_csv(&self) -> String {
+        let readability = &self.readability;
+        let scores = [
+            readability.average_sentence_length(),
+            readability.flesch_reading_ease(),
+            readability.flesch_kincaid_grade(),
+            readability.gunning_fog(),
+            readability.coleman_liau(),
+            readability.smog(),
+            readability.automated_readability(),
+            readability.dialogue_share(),
+            readability.lexical_diversity(),
+        ];
+
+        let mut fields = vec![
+            csv_field(self.chapter.as_str()),
+            csv_field(self.scene.as_deref().unwrap_or_default()),
+            readability.words.to_string(),
+            readability.sentences.to_string(),
+        ];
+        fields.extend(scores.iter().map(|score| format!("{score:.2}")));
+        fields.join(",")
+    }
+}
+
+/// Returns a row for every chapter of `tree` followed by a row for each of its scenes.
+pub fn report(tree: &DocumentTree) -> Vec<ReportRow> {
+    let mut rows = Vec::new();
+
+    for chapter in tree.chapters() {
+        let Some(title) = tree.node(chapter).map(|node| node.title.clone()) else {
+            continue;
+        };
+        let scenes = tree
+            .children(chapter)
+            .iter()
+            .filter_map(|scene| tree.node(*scene).map(|node| (*scene, node)))
+            .map(|(scene, node)| ReportRow {
+                chapter: title.clone(),
+                scene: Some(node.title.clone()),
+                node: scene,
+                readability: Readability::new(node.text.as_str()),
+            })
+            .collect::<Vec<ReportRow>>();
+
+        let mut total = Readability::default();
+        scenes.iter().for_each(|row| total += &row.readability);
+        rows.push(ReportRow {
+            chapter: title,
+            scene: None,
+            node: chapter,
+            readability: total,
+        });
+        rows.extend(scenes);
+    }
+
+    rows
+}
+
+/// Returns `rows` as CSV with a header line.
+pub fn to_csv(rows: &[ReportRow]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    for row in rows {
+        csv.push('\n');
+        csv.push_str(row.to_csv().as_str());
+    }
+    csv.push('\n');
+    csv
+}
+
+/// Returns the guessed number of syllables in `word`, which has to be lowercase. Every word has
+/// at least one.
+pub fn syllables(word: &str) -> usize {
+    let letters = word
+        .chars()
+        .filter(|ch| ch.is_alphabetic())
+        .collect::<Vec<char>>();
+    let mut count = 0_usize;
+    let mut previous_vowel = false;
+
+    for ch in letters.iter() {
+        let vowel = VOWELS.contains(ch);
+        if vowel && !previous_vowel {
+            count += 1_usize;
+        }
+        previous_vowel = vowel;
+    }
+
+    // A final `e` is usually silent, as in `make`, but not after a consonant and `l` as in
+    // `table`.
+    let silent = match letters.as_slice() {
+        [.., before, 'l', 'e'] => VOWELS.contains(before),
+        [.., before, 'e'] => !VOWELS.contains(before),
+        _ => false,
+    };
+    if silent {
+        count = count.saturating_sub(1_usize);
+    }
+
+    count.max(1_usize)
+}
+
+/// Returns `numerator / denominator`, or `0` if the denominator is `0`.
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0_usize {
+        return 0_f64;
+    }
+    numerator as f64 / denominator as f64
+}
+
+/// Returns `field` as a CSV field, quoted if it holds a comma, a quote or a line break. Shared by
+/// every report written as CSV.
+pub fn csv_field(field: &str) -> String {
+    if !field.contains([',', '"', '\n', '\r']) {
+        return field.to_string();
+    }
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syllables_are_guessed_from_vowel_groups() {
+        assert_eq!(syllables("make"), 1_usize);
+        assert_eq!(syllables("table"), 2_usize);
+        assert_eq!(syllables("beautiful"), 3_usize);
+        assert_eq!(syllables("the"), 1_usize);
+        assert_eq!(syllables("rhythm"), 1_usize);
+    }
+
+    #[test]
+    fn counts_split_dialogue_from_narration() {
+        let readability = Readability::new("The cat sat.\n\"Run, cat,\" she said.");
+        assert_eq!(readability.sentences, 2_usize);
+        assert_eq!(readability.words, 7_usize);
+        assert_eq!(readability.dialogue_words, 2_usize);
+        assert_eq!(readability.vocabulary.len(), 6_usize);
+        assert_eq!(readability.average_sentence_length(), 3.5_f64);
+    }
+
+    #[test]
+    fn scores_of_empty_text_are_zero() {
+        let readability = Readability::new("  ");
+        assert_eq!(readability.flesch_reading_ease(), 0_f64);
+        assert_eq!(readability.smog(), 0_f64);
+        assert_eq!(readability.lexical_diversity(), 0_f64);
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("Chapter One"), "Chapter One");
+        assert_eq!(csv_field("One, Two"), "\"One, Two\"");
+        assert_eq!(csv_field("The \"End\""), "\"The \"\"End\"\"\"");
+        assert_eq!(csv_field("Line\r\nbreak"), "\"Line\r\nbreak\"");
+    }
+}
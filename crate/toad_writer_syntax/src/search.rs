@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Search module.
+//!
+//! To find and replace text in a document. Plain text patterns are escaped and run through the
+//! same [`Regex`] engine as regular expressions so that every option behaves the same for both.
+
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+use thiserror::Error;
+
+/// Options that change how a [`Search`] pattern is matched.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Match the letter case exactly.
+    pub case_sensitive: bool,
+    /// Only match when the pattern is not part of a bigger word.
+    pub whole_word: bool,
+    /// Treat the pattern as a regular expression. Replacements can then refer to capture groups
+    /// with `$1` or `${name}`.
+    pub regex: bool,
+}
+
+/// Errors that can occur while building a [`Search`].
+#[derive(Debug, Error)]
+pub enum SearchError {
+    /// The search pattern is empty.
+    #[error("The search pattern is empty.")]
+    EmptyPattern,
+    /// The regular expression could not be compiled.
+    #[error("Invalid regular expression: {0}")]
+    InvalidRegex(#[from] regex::Error),
+}
+
+/// A single match found by [`Search::find_all`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Byte range of the match in the searched text.
+    pub bytes: Range<usize>,
+    /// Character range of the match in the searched text. Useful for text buffers that count
+    /// offsets in characters.
+    pub chars: Range<usize>,
+}
+
+/// A compiled search pattern along with its [`SearchOptions`].
+#[derive(Debug, Clone)]
+pub struct Search {
+    regex: Regex,
+    options: SearchOptions,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Search {
+    /// Compiles `pattern` with the given `options`.
+    /// An [`Err`] is returned if the pattern is empty or is not a valid regular expression.
+    pub fn new(pattern: &str, options: SearchOptions) -> Result<Self, SearchError> {
+        if pattern.is_empty() {
+            return Err(SearchError::EmptyPattern);
+        }
+
+        let source = match options.regex {
+            true => pattern.to_string(),
+            false => regex::escape(pattern),
+        };
+
+        let regex = RegexBuilder::new(source.as_str())
+            .case_insensitive(!options.case_sensitive)
+            .multi_line(true)
+            .build()?;
+
+        Ok(Self { regex, options })
+    }
+
+    /// Returns the options the pattern was compiled with.
+    pub fn options(&self) -> SearchOptions {
+        self.options
+    }
+
+    /// Returns every non-empty match in `text` in the order they appear. With
+    /// [`SearchOptions::whole_word`], a match must not be next to a letter or a digit on either
+    /// edge, so that patterns starting or ending with punctuation such as `Mr.` match too.
+    pub fn find_all(&self, text: &str) -> Vec<SearchMatch> {
+        let mut matches = Vec::<SearchMatch>::new();
+        let mut chars = 0_usize;
+        let mut last = 0_usize;
+        let mut position = 0_usize;
+
+        while let Some(found) = self.regex.find_at(text, position) {
+            if found.is_empty() || !self.is_whole(text, found.range()) {
+                match text[found.start()..].chars().next() {
+                    Some(next) => position = found.start() + next.len_utf8(),
+                    None => break,
+                }
+                continue;
+            }
+
+            chars += text[last..found.start()].chars().count();
+            let start = chars;
+            chars += found.as_str().chars().count();
+            last = found.end();
+            position = found.end();
+
+            matches.push(SearchMatch {
+                bytes: found.range(),
+                chars: start..chars,
+            });
+        }
+        matches
+    }
+
+    /// Whether the match at `range` of `text` stands as a whole word when the options ask for it.
+    fn is_whole(&self, text: &str, range: Range<usize>) -> bool {
+        if !self.options.whole_word {
+            return true;
+        }
+        !text[..range.start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+            && !text[range.end..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphanumeric)
+    }
+
+    /// Returns the text that should replace `found` in `text`. Capture groups in `replacement`
+    /// are expanded only when the search is a regular expression.
+    pub fn expand(&self, text: &str, found: &SearchMatch, replacement: &str) -> String {
+        if !self.options.regex {
+            return replacement.to_string();
+        }
+
+        let mut expanded = String::new();
+        if let Some(captures) = self.regex.captures_at(text, found.bytes.start) {
+            captures.expand(replacement, &mut expanded);
+        }
+        expanded
+    }
+
+    /// Replaces every match in `text` and returns the new text along with the number of
+    /// replacements made.
+    pub fn replace_all(&self, text: &str, replacement: &str) -> (String, usize) {
+        let matches = self.find_all(text);
+        let mut replaced = String::with_capacity(text.len());
+        let mut last = 0_usize;
+
+        for found in matches.iter() {
+            replaced.push_str(&text[last..found.bytes.start]);
+            replaced.push_str(self.expand(text, found, replacement).as_str());
+            last = found.bytes.end;
+        }
+        replaced.push_str(&text[last..]);
+
+        (replaced, matches.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Vec<Range<usize>> {
+        let options = SearchOptions {
+            whole_word: true,
+            ..SearchOptions::default()
+        };
+        Search::new(pattern, options)
+            .unwrap()
+            .find_all(text)
+            .into_iter()
+            .map(|found| found.bytes)
+            .collect()
+    }
+
+    #[test]
+    fn whole_words_can_end_with_punctuation() {
+        assert_eq!(find("Mr.", "Mr. Tom met Mr.Lee"), vec![0_usize..3_usize]);
+        assert_eq!(
+            find("Mr.", "Mr. Tom met Mr. Lee"),
+            vec![0_usize..3_usize, 12_usize..15_usize]
+        );
+    }
+
+    #[test]
+    fn whole_words_can_start_with_punctuation() {
+        assert_eq!(
+            find("\"Hi", "\"Hi,\" she said. \"High"),
+            vec![0_usize..3_usize]
+        );
+    }
+
+    #[test]
+    fn parts_of_words_are_skipped() {
+        assert_eq!(find("ana", "banana ana"), vec![7_usize..10_usize]);
+        assert_eq!(find("an", "can an"), vec![4_usize..6_usize]);
+    }
+}
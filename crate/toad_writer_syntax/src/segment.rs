@@ -0,0 +1,252 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Segment module.
+//!
+//! Unicode aware word segmentation. Contractions such as `don't` and hyphenated words such as
+//! `well-known` count as a single word, numbers keep their separators, and every CJK ideograph or
+//! kana counts as a word of its own since those scripts do not separate words with spaces.
+//! Sentences end at terminal punctuation unless it follows a common abbreviation.
+
+use std::ops::Range;
+
+/// Characters that join two parts of a word when they sit between letters.
+const JOINERS: [char; 5_usize] = ['\'', '’', '-', '\u{2010}', '\u{2011}'];
+/// Characters that join two parts of a number such as `3.14` or `1,000`.
+const NUMBER_JOINERS: [char; 2_usize] = ['.', ','];
+/// Characters that end a sentence.
+const TERMINATORS: [char; 6_usize] = ['.', '!', '?', '…', '。', '！'];
+/// Characters that may close a sentence after its terminator, such as a closing quote.
+const CLOSERS: [char; 7_usize] = ['"', '\'', '”', '’', ')', ']', '»'];
+/// Abbreviations whose full stop does not end a sentence.
+const ABBREVIATIONS: [&str; 12_usize] = [
+    "mr", "mrs", "ms", "dr", "st", "mt", "prof", "sr", "jr", "vs", "etc", "e.g",
+];
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// Returns the byte range of every word in `text` in the order they appear.
+pub fn word_ranges(text: &str) -> Vec<Range<usize>> {
+    let chars = text.char_indices().collect::<Vec<(usize, char)>>();
+    let mut words = Vec::new();
+    let mut index = 0_usize;
+
+    while index < chars.len() {
+        let (start, ch) = chars[index];
+        if is_cjk(ch) {
+            words.push(start..start + ch.len_utf8());
+            index += 1_usize;
+            continue;
+        }
+        if !is_word_char(ch) {
+            index += 1_usize;
+            continue;
+        }
+
+        let mut end = index + 1_usize;
+        while end < chars.len() {
+            if is_word_char(chars[end].1) {
+                end += 1_usize;
+            } else if joins(&chars, end) {
+                end += 2_usize;
+            } else {
+                break;
+            }
+        }
+
+        let (last, ch) = chars[end - 1_usize];
+        words.push(start..last + ch.len_utf8());
+        index = end;
+    }
+
+    words
+}
+
+/// Returns the number of words in `text`.
+pub fn word_count(text: &str) -> usize {
+    word_ranges(text).len()
+}
+
+/// Returns the words of `text` in the order they appear.
+pub fn words(text: &str) -> impl Iterator<Item = &str> {
+    word_ranges(text).into_iter().map(move |range| &text[range])
+}
+
+/// Returns the byte range of every sentence in `text`, without the whitespace around it.
+pub fn sentence_ranges(text: &str) -> Vec<Range<usize>> {
+    let chars = text.char_indices().collect::<Vec<(usize, char)>>();
+    let mut sentences = Vec::new();
+    let mut start = 0_usize;
+    let mut index = 0_usize;
+
+    while index < chars.len() {
+        let (position, ch) = chars[index];
+        index += 1_usize;
+        if !TERMINATORS.contains(&ch) || (ch == '.' && is_abbreviation(&text[..position])) {
+            continue;
+        }
+
+        while index < chars.len()
+            && (TERMINATORS.contains(&chars[index].1) || CLOSERS.contains(&chars[index].1))
+        {
+            index += 1_usize;
+        }
+        if index < chars.len() && !chars[index].1.is_whitespace() {
+            continue;
+        }
+
+        let end = chars.get(index).map_or(text.len(), |(end, _)| *end);
+        push_trimmed(text, start..end, &mut sentences);
+        start = end;
+    }
+    push_trimmed(text, start..text.len(), &mut sentences);
+
+    sentences
+}
+
+/// Returns the sentences of `text` in the order they appear.
+pub fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    sentence_ranges(text)
+        .into_iter()
+        .map(move |range| &text[range])
+}
+
+/// Returns the lines of `text` the way a text buffer numbers them. Lines end at `\n`, `\r`,
+/// `\r\n` or the paragraph separator `U+2029`, and a text ending with a line break ends with an
+/// empty line.
+pub fn lines(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = Some(text);
+    std::iter::from_fn(move || {
+        let text = rest?;
+        let Some(end) = text.find(is_line_break) else {
+            rest = None;
+            return Some(text);
+        };
+        let next = match text[end..].starts_with("\r\n") {
+            true => end + 2_usize,
+            false => end + text[end..].chars().next().map_or(1_usize, char::len_utf8),
+        };
+        rest = Some(&text[next..]);
+        Some(&text[..end])
+    })
+}
+
+/// Returns true for the characters that end a line of a text buffer.
+pub fn is_line_break(ch: char) -> bool {
+    matches!(ch, '\n' | '\r' | '\u{2029}')
+}
+
+/// Returns true for the Han ideographs and the Japanese kana.
+pub fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{3040}'..='\u{309F}'
+            | '\u{30A0}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+/// Returns true for the characters that make up a word outside of the CJK scripts.
+#[inline]
+fn is_word_char(ch: char) -> bool {
+    (ch.is_alphanumeric() && !is_cjk(ch)) || is_combining_mark(ch)
+}
+
+/// Returns true for the combining diacritical marks that belong to the letter before them.
+#[inline]
+fn is_combining_mark(ch: char) -> bool {
+    matches!(ch, '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{20D0}'..='\u{20FF}')
+}
+
+/// Returns true if the character at `index` joins the word before it with the one after it.
+fn joins(chars: &[(usize, char)], index: usize) -> bool {
+    let (Some((_, before)), Some((_, joiner)), Some((_, after))) = (
+        chars.get(index - 1_usize),
+        chars.get(index),
+        chars.get(index + 1_usize),
+    ) else {
+        return false;
+    };
+
+    let letters = JOINERS.contains(joiner) && is_word_char(*before) && is_word_char(*after);
+    let digits = NUMBER_JOINERS.contains(joiner) && before.is_numeric() && after.is_numeric();
+    letters || digits
+}
+
+/// Returns true if `before`, the text before a full stop, ends with a known abbreviation.
+fn is_abbreviation(before: &str) -> bool {
+    let word = before
+        .rsplit(|ch: char| ch.is_whitespace() || ch == '(')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    ABBREVIATIONS.contains(&word.as_str())
+}
+
+/// To add `range` of `text` to `ranges` without the whitespace around it, unless it is blank.
+fn push_trimmed(text: &str, range: Range<usize>, ranges: &mut Vec<Range<usize>>) {
+    let slice = &text[range.clone()];
+    let trimmed = slice.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    ranges.push(start..start + trimmed.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joined_words_and_numbers_count_once() {
+        let text = "Don't stop the well-known 3.14 and 1,000 cats.";
+        assert_eq!(
+            words(text).collect::<Vec<&str>>(),
+            vec![
+                "Don't",
+                "stop",
+                "the",
+                "well-known",
+                "3.14",
+                "and",
+                "1,000",
+                "cats"
+            ]
+        );
+        assert_eq!(word_count("café and cafe\u{301}"), 3_usize);
+    }
+
+    #[test]
+    fn every_ideograph_is_a_word() {
+        assert_eq!(word_count("我爱你 ok"), 4_usize);
+        assert_eq!(word_count("ひらがな"), 4_usize);
+    }
+
+    #[test]
+    fn sentences_skip_abbreviations_and_keep_closing_quotes() {
+        let text = "Mr. Smith arrived. He sat down!  \"Really?\" she asked";
+        assert_eq!(
+            sentences(text).collect::<Vec<&str>>(),
+            vec![
+                "Mr. Smith arrived.",
+                "He sat down!",
+                "\"Really?\"",
+                "she asked"
+            ]
+        );
+        assert!(sentences(" \n ").next().is_none());
+    }
+
+    #[test]
+    fn lines_split_like_a_text_buffer() {
+        assert_eq!(
+            lines("a\r\nb\rc\u{2029}d\n").collect::<Vec<&str>>(),
+            vec!["a", "b", "c", "d", ""]
+        );
+        assert_eq!(lines("").collect::<Vec<&str>>(), vec![""]);
+    }
+}
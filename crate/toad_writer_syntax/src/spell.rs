@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Spell module.
+//!
+//! To check the spelling of words against a Hunspell [`Dictionary`] along with the words of the
+//! project dictionary and the names of the lore.
+
+mod hunspell;
+
+use crate::segment::word_ranges;
+pub use hunspell::{Dictionary, DictionaryError};
+use std::collections::{BTreeSet, HashSet};
+use std::ops::Range;
+
+/// Characters tried for suggestions when the dictionary has no `TRY` option.
+const ALPHABET: &str = "esianrtolcdugmphbyfvkwzxjq'";
+/// Suffixes that make a known word possessive.
+const POSSESSIVES: [&str; 2_usize] = ["'s", "’s"];
+/// Words longer than this only get suggestions one edit away.
+const LONG_WORD: usize = 12_usize;
+
+/// Checks words against a [`Dictionary`] and the words accepted by the writer.
+#[derive(Debug, Clone, Default)]
+pub struct SpellChecker {
+    dictionary: Option<Dictionary>,
+    /// Words added by the writer to the project dictionary.
+    personal: BTreeSet<String>,
+    /// Every word of the names and aliases of the lore.
+    names: HashSet<String>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl SpellChecker {
+    /// Returns a checker that accepts every word until a [`Dictionary`] is set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_dictionary(&mut self, dictionary: Option<Dictionary>) {
+        self.dictionary = dictionary;
+    }
+
+    /// Returns true once a [`Dictionary`] is set.
+    pub fn is_enabled(&self) -> bool {
+        self.dictionary.is_some()
+    }
+
+    pub fn personal(&self) -> &BTreeSet<String> {
+        &self.personal
+    }
+
+    pub fn set_personal(&mut self, words: BTreeSet<String>) {
+        self.personal = words;
+    }
+
+    /// To accept `word` from now on.
+    pub fn add_word(&mut self, word: &str) {
+        self.personal.insert(word.to_string());
+    }
+
+    /// To accept every word of `names`, replacing the names accepted before.
+    pub fn set_names<'a>(&mut self, names: impl IntoIterator<Item = &'a str>) {
+        self.names = names
+            .into_iter()
+            .flat_map(|name| {
+                word_ranges(name)
+                    .into_iter()
+                    .map(|range| name[range].to_string())
+                    .collect::<Vec<String>>()
+            })
+            .collect();
+    }
+
+    /// Returns true if `word` is spelled correctly. Words with digits are always accepted, and
+    /// so is the possessive of a known word.
+    pub fn check(&self, word: &str) -> bool {
+        let Some(dictionary) = self.dictionary.as_ref() else {
+            return true;
+        };
+        if word.chars().any(|ch| ch.is_numeric()) || self.knows(dictionary, word) {
+            return true;
+        }
+        POSSESSIVES
+            .iter()
+            .filter_map(|suffix| word.strip_suffix(suffix))
+            .any(|base| self.knows(dictionary, base))
+    }
+
+    /// Returns the byte ranges of the misspelled words in `text`.
+    pub fn misspelled(&self, text: &str) -> Vec<Range<usize>> {
+        if !self.is_enabled() {
+            return Vec::new();
+        }
+        word_ranges(text)
+            .into_iter()
+            .filter(|range| !self.check(&text[range.clone()]))
+            .collect()
+    }
+
+    /// Returns up to `limit` correctly spelled words that `word` may have been meant to be, the
+    /// closest first.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let Some(dictionary) = self.dictionary.as_ref() else {
+            return Vec::new();
+        };
+        let mut suggestions = Vec::new();
+        let push = |candidate: String, suggestions: &mut Vec<String>| {
+            if candidate != word && self.check(&candidate) && !suggestions.contains(&candidate) {
+                suggestions.push(candidate);
+            }
+        };
+
+        for (from, to) in dictionary.replacements() {
+            for (index, _) in word.match_indices(from.as_str()) {
+                let mut candidate = word.to_string();
+                candidate.replace_range(index..index + from.len(), to);
+                push(candidate, &mut suggestions);
+            }
+        }
+        push(capitalise(word), &mut suggestions);
+
+        let alphabet = match dictionary.try_chars().is_empty() {
+            true => ALPHABET.chars().collect(),
+            false => dictionary.try_chars().to_vec(),
+        };
+        let near = edits(word, &alphabet);
+        for candidate in near.iter() {
+            push(candidate.clone(), &mut suggestions);
+        }
+        if suggestions.len() < limit && word.chars().count() <= LONG_WORD {
+            for candidate in near.iter().flat_map(|near| edits(near, &alphabet)) {
+                push(candidate, &mut suggestions);
+                if suggestions.len() >= limit {
+                    break;
+                }
+            }
+        }
+
+        suggestions.truncate(limit);
+        suggestions
+    }
+
+    /// Returns true if `word` or one of its accepted capitalisations is known.
+    fn knows(&self, dictionary: &Dictionary, word: &str) -> bool {
+        let known = |word: &str| {
+            dictionary.contains(word) || self.personal.contains(word) || self.names.contains(word)
+        };
+        if known(word) {
+            return true;
+        }
+
+        // `Word` and `WORD` are fine if `word` is, and `WORD` is fine if `Word` is.
+        let lower = word.to_lowercase();
+        let capitalised = word == capitalise(&lower);
+        let upper = word.chars().all(|ch| !ch.is_lowercase());
+        ((capitalised || upper) && known(&lower)) || (upper && known(&capitalise(&lower)))
+    }
+}
+
+/// Returns every word one deletion, swap, replacement or insertion away from `word`.
+fn edits(word: &str, alphabet: &[char]) -> Vec<String> {
+    let chars = word.chars().collect::<Vec<char>>();
+    let splice = |start: usize, end: usize, middle: &[char]| -> String {
+        chars[..start]
+            .iter()
+            .chain(middle)
+            .chain(&chars[end..])
+            .collect()
+    };
+    let mut edits = Vec::new();
+
+    for index in 0_usize..chars.len() {
+        edits.push(splice(index, index + 1_usize, &[]));
+        if index + 1_usize < chars.len() {
+            edits.push(splice(
+                index,
+                index + 2_usize,
+                &[chars[index + 1_usize], chars[index]],
+            ));
+        }
+        for ch in alphabet.iter().filter(|ch| **ch != chars[index]) {
+            edits.push(splice(index, index + 1_usize, &[*ch]));
+        }
+    }
+    for index in 0_usize..=chars.len() {
+        for ch in alphabet {
+            edits.push(splice(index, index, &[*ch]));
+        }
+    }
+
+    edits
+}
+
+/// Returns `word` with its first letter in uppercase.
+fn capitalise(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
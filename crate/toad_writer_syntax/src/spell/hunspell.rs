@@ -0,0 +1,448 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Hunspell module.
+//!
+//! To read Hunspell dictionaries, a `.aff` file of affix rules along with a `.dic` file of root
+//! words. Every root is expanded with its prefixes and suffixes when the dictionary is loaded so
+//! that looking a word up is a single hash lookup. Compounding and morphology are not supported.
+
+use std::collections::{HashMap, HashSet};
+use std::{fs, io, path::Path};
+use thiserror::Error;
+
+/// Errors that can occur while reading a [`Dictionary`].
+#[derive(Debug, Error)]
+pub enum DictionaryError {
+    #[error("Cannot read the dictionary: {0}")]
+    Io(#[from] io::Error),
+    #[error("The dictionary encoding {0} is not supported.")]
+    Encoding(String),
+    #[error("Line {line} of the affix file is malformed: {reason}")]
+    Malformed { line: usize, reason: &'static str },
+}
+
+/// How flags are written in the affix and dictionary files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagType {
+    /// A single character.
+    Char,
+    /// Two characters.
+    Long,
+    /// Comma separated numbers.
+    Numeric,
+}
+
+/// One character of an affix condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Any,
+    Char(char),
+    /// A set of characters such as `[aeiou]`, or every other character if negated `[^aeiou]`.
+    Set {
+        chars: Vec<char>,
+        negated: bool,
+    },
+}
+
+/// A single prefix or suffix rule.
+#[derive(Debug, Clone)]
+struct AffixRule {
+    strip: String,
+    add: String,
+    condition: Vec<Condition>,
+}
+
+/// Every rule of a prefix or suffix flag.
+#[derive(Debug, Clone)]
+struct Affix {
+    /// Whether it can be combined with an affix of the other kind.
+    cross_product: bool,
+    rules: Vec<AffixRule>,
+}
+
+/// A Hunspell dictionary with every word form expanded.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    /// Characters to try when looking for suggestions, most common first.
+    try_chars: Vec<char>,
+    /// Common misspellings and what to replace them with.
+    replacements: Vec<(String, String)>,
+}
+
+/// The rules read from a `.aff` file.
+struct AffixFile {
+    flag_type: FlagType,
+    prefixes: HashMap<String, Affix>,
+    suffixes: HashMap<String, Affix>,
+    need_affix: Option<String>,
+    forbidden: Option<String>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Dictionary {
+    /// Reads the dictionary from the `aff` and `dic` files. The files may be in UTF-8 or in
+    /// ISO-8859-1 as given by the `SET` option of the affix file.
+    pub fn load(aff: &Path, dic: &Path) -> Result<Self, DictionaryError> {
+        let (aff, dic) = (fs::read(aff)?, fs::read(dic)?);
+        let encoding = latin1(&aff)
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix("SET")
+                    .map(|set| set.trim().to_uppercase())
+            })
+            .unwrap_or_else(|| "ISO8859-1".to_string());
+
+        match encoding.as_str() {
+            "UTF-8" => Self::parse(
+                String::from_utf8_lossy(&aff).as_ref(),
+                String::from_utf8_lossy(&dic).as_ref(),
+            ),
+            "ISO8859-1" | "ISO-8859-1" => Self::parse(&latin1(&aff), &latin1(&dic)),
+            _ => Err(DictionaryError::Encoding(encoding)),
+        }
+    }
+
+    /// Returns the dictionary made from the text of the `aff` and `dic` files.
+    pub fn parse(aff: &str, dic: &str) -> Result<Self, DictionaryError> {
+        let affixes = AffixFile::parse(aff)?;
+        let mut words = HashSet::new();
+
+        // The first line holds the number of words.
+        for line in dic.lines().skip(1_usize) {
+            let entry = line.split_whitespace().next().unwrap_or_default();
+            if entry.is_empty() {
+                continue;
+            }
+            let (root, flags) = match entry.split_once('/') {
+                Some((root, flags)) => (root, affixes.flags(flags)),
+                None => (entry, Vec::new()),
+            };
+            if affixes
+                .forbidden
+                .as_ref()
+                .is_some_and(|flag| flags.contains(flag))
+            {
+                continue;
+            }
+            if !affixes
+                .need_affix
+                .as_ref()
+                .is_some_and(|flag| flags.contains(flag))
+            {
+                words.insert(root.to_string());
+            }
+            affixes.expand(root, &flags, &mut words);
+        }
+
+        Ok(Self {
+            words,
+            try_chars: affixes.try_chars,
+            replacements: affixes.replacements,
+        })
+    }
+
+    /// Returns true if `word` is in the dictionary exactly as written.
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn try_chars(&self) -> &[char] {
+        &self.try_chars
+    }
+
+    pub fn replacements(&self) -> &[(String, String)] {
+        &self.replacements
+    }
+}
+
+impl AffixFile {
+    fn parse(text: &str) -> Result<Self, DictionaryError> {
+        let mut file = Self {
+            flag_type: FlagType::Char,
+            prefixes: HashMap::new(),
+            suffixes: HashMap::new(),
+            need_affix: None,
+            forbidden: None,
+            try_chars: Vec::new(),
+            replacements: Vec::new(),
+        };
+
+        for (index, line) in text.lines().enumerate() {
+            let malformed = |reason| DictionaryError::Malformed {
+                line: index + 1_usize,
+                reason,
+            };
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            match fields.as_slice() {
+                ["FLAG", "long", ..] => file.flag_type = FlagType::Long,
+                ["FLAG", "num", ..] => file.flag_type = FlagType::Numeric,
+                ["NEEDAFFIX", flag, ..] => file.need_affix = Some(flag.to_string()),
+                ["FORBIDDENWORD", flag, ..] => file.forbidden = Some(flag.to_string()),
+                ["TRY", chars, ..] => file.try_chars = chars.chars().collect(),
+                ["REP", from, to, ..] => file
+                    .replacements
+                    .push((from.replace('_', "\x20"), to.replace('_', "\x20"))),
+                [kind @ ("PFX" | "SFX"), flag, cross, count] if count.parse::<usize>().is_ok() => {
+                    let affixes = match *kind {
+                        "PFX" => &mut file.prefixes,
+                        _ => &mut file.suffixes,
+                    };
+                    affixes.insert(
+                        flag.to_string(),
+                        Affix {
+                            cross_product: *cross == "Y",
+                            rules: Vec::new(),
+                        },
+                    );
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let affixes = match *kind {
+                        "PFX" => &mut file.prefixes,
+                        _ => &mut file.suffixes,
+                    };
+                    let affix = affixes
+                        .get_mut(*flag)
+                        .ok_or_else(|| malformed("the affix rule has no header"))?;
+                    let condition = parse_condition(rest.first().copied().unwrap_or("."))
+                        .ok_or_else(|| malformed("the affix condition is not closed"))?;
+                    // Continuation flags after the added text are not supported.
+                    let add = add.split('/').next().unwrap_or_default();
+
+                    affix.rules.push(AffixRule {
+                        strip: empty_if_zero(strip),
+                        add: empty_if_zero(add),
+                        condition,
+                    });
+                }
+                ["PFX" | "SFX", ..] => return Err(malformed("the affix rule has too few fields")),
+                _ => (),
+            }
+        }
+
+        Ok(file)
+    }
+
+    /// Returns the flags written in `text`.
+    fn flags(&self, text: &str) -> Vec<String> {
+        match self.flag_type {
+            FlagType::Char => text.chars().map(String::from).collect(),
+            FlagType::Long => text
+                .chars()
+                .collect::<Vec<char>>()
+                .chunks(2_usize)
+                .map(|flag| flag.iter().collect())
+                .collect(),
+            FlagType::Numeric => text.split(',').map(str::to_string).collect(),
+        }
+    }
+
+    /// To add every form of `root` with the affixes in `flags` to `words`.
+    fn expand(&self, root: &str, flags: &[String], words: &mut HashSet<String>) {
+        let prefixes = flags
+            .iter()
+            .filter_map(|flag| self.prefixes.get(flag))
+            .collect::<Vec<&Affix>>();
+
+        for suffix in flags.iter().filter_map(|flag| self.suffixes.get(flag)) {
+            for form in suffix.rules.iter().filter_map(|rule| rule.suffix(root)) {
+                if suffix.cross_product {
+                    prefixes
+                        .iter()
+                        .filter(|prefix| prefix.cross_product)
+                        .flat_map(|prefix| prefix.rules.iter())
+                        .filter_map(|rule| rule.prefix(&form))
+                        .for_each(|both| {
+                            words.insert(both);
+                        });
+                }
+                words.insert(form);
+            }
+        }
+        for prefix in prefixes {
+            for form in prefix.rules.iter().filter_map(|rule| rule.prefix(root)) {
+                words.insert(form);
+            }
+        }
+    }
+}
+
+impl AffixRule {
+    /// Returns `word` with this rule applied as a prefix, if the rule allows it.
+    fn prefix(&self, word: &str) -> Option<String> {
+        let chars = word.chars().collect::<Vec<char>>();
+        if !word.starts_with(self.strip.as_str()) || !matches(&self.condition, &chars) {
+            return None;
+        }
+        Some(format!("{}{}", self.add, &word[self.strip.len()..]))
+    }
+
+    /// Returns `word` with this rule applied as a suffix, if the rule allows it.
+    fn suffix(&self, word: &str) -> Option<String> {
+        let chars = word.chars().collect::<Vec<char>>();
+        let end = chars.len().checked_sub(self.condition.len())?;
+        if !word.ends_with(self.strip.as_str()) || !matches(&self.condition, &chars[end..]) {
+            return None;
+        }
+        Some(format!(
+            "{}{}",
+            &word[..word.len() - self.strip.len()],
+            self.add
+        ))
+    }
+}
+
+/// Returns true if `chars` starts with the characters of `condition`.
+fn matches(condition: &[Condition], chars: &[char]) -> bool {
+    condition.len() <= chars.len()
+        && condition
+            .iter()
+            .zip(chars)
+            .all(|(condition, ch)| match condition {
+                Condition::Any => true,
+                Condition::Char(expected) => expected == ch,
+                Condition::Set { chars, negated } => chars.contains(ch) != *negated,
+            })
+}
+
+/// Returns the condition written as `text` such as `[^aeiou]y`, or [`None`] if a set is not
+/// closed.
+fn parse_condition(text: &str) -> Option<Vec<Condition>> {
+    let mut condition = Vec::new();
+    let mut chars = text.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '.' => condition.push(Condition::Any),
+            '[' => {
+                let mut set = Vec::new();
+                loop {
+                    match chars.next()? {
+                        ']' => break,
+                        ch => set.push(ch),
+                    }
+                }
+                let negated = set.first() == Some(&'^');
+                if negated {
+                    set.remove(0_usize);
+                }
+                condition.push(Condition::Set {
+                    chars: set,
+                    negated,
+                });
+            }
+            ch => condition.push(Condition::Char(ch)),
+        }
+    }
+
+    Some(condition)
+}
+
+/// Hunspell writes an empty strip or affix as `0`.
+fn empty_if_zero(text: &str) -> String {
+    match text {
+        "0" => String::new(),
+        text => text.to_string(),
+    }
+}
+
+/// Decodes ISO-8859-1 text, where every byte is the character of the same code point.
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| *byte as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "SET UTF-8
+TRY esianrt
+REP 1
+REP alot a_lot
+PFX U Y 1
+PFX U 0 un .
+SFX S Y 2
+SFX S y ies [^aeiou]y
+SFX S 0 s [^y]
+NEEDAFFIX X
+FORBIDDENWORD F
+";
+    const DIC: &str = "5
+happy/US
+do/U
+cat/S
+catz/F
+walk/XS
+";
+
+    fn contains_all(dictionary: &Dictionary, words: &[&str]) -> bool {
+        words.iter().all(|word| dictionary.contains(word))
+    }
+
+    #[test]
+    fn roots_are_expanded_with_their_affixes() {
+        let dictionary = Dictionary::parse(AFF, DIC).unwrap();
+        assert!(contains_all(
+            &dictionary,
+            &[
+                "happy",
+                "happies",
+                "unhappy",
+                "unhappies",
+                "do",
+                "undo",
+                "cat",
+                "cats",
+                "walks"
+            ],
+        ));
+        assert!(!dictionary.contains("happys"));
+        assert!(!dictionary.contains("uncat"));
+        assert_eq!(dictionary.try_chars(), &['e', 's', 'i', 'a', 'n', 'r', 't']);
+        assert_eq!(
+            dictionary.replacements(),
+            &[("alot".to_string(), "a lot".to_string())]
+        );
+    }
+
+    #[test]
+    fn forbidden_words_and_bare_roots_needing_an_affix_are_left_out() {
+        let dictionary = Dictionary::parse(AFF, DIC).unwrap();
+        assert!(!dictionary.contains("catz"));
+        assert!(!dictionary.contains("walk"));
+    }
+
+    #[test]
+    fn long_and_numeric_flags() {
+        let long = Dictionary::parse("FLAG long\nSFX Aa Y 1\nSFX Aa 0 ed .\n", "1\nwalk/AaBb\n");
+        assert!(contains_all(&long.unwrap(), &["walk", "walked"]));
+        let numeric = Dictionary::parse("FLAG num\nSFX 12 Y 1\nSFX 12 0 ing .\n", "1\nsing/7,12\n");
+        assert!(contains_all(&numeric.unwrap(), &["sing", "singing"]));
+    }
+
+    #[test]
+    fn malformed_rules_report_their_line() {
+        let headless = Dictionary::parse("SFX S Y 1\nSFX Q 0 s .\n", "0\n");
+        assert!(matches!(
+            headless,
+            Err(DictionaryError::Malformed { line: 2_usize, .. })
+        ));
+        let unclosed = Dictionary::parse("SFX S Y 1\nSFX S 0 s [^y\n", "0\n");
+        assert!(matches!(
+            unclosed,
+            Err(DictionaryError::Malformed { line: 2_usize, .. })
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Statistics module.
+//!
+//! To count the words, characters and paragraphs of a text. [`LineStatistics`] keeps the counts
+//! of every line so that an edit only recounts the lines it touched.
+
+use crate::segment::{is_line_break, lines, word_count};
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// Average silent reading speed of an adult in words per minute.
+pub const WORDS_PER_MINUTE: f64 = 238_f64;
+
+/// Counts of a text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Statistics {
+    pub words: usize,
+    /// Characters including spaces but not line breaks.
+    pub characters: usize,
+    /// Characters that are not whitespace.
+    pub characters_no_spaces: usize,
+    /// Lines that hold anything other than whitespace.
+    pub paragraphs: usize,
+}
+
+/// [`Statistics`] of a text kept per line along with their total. Lines are split by
+/// [`lines`] the same way a text buffer numbers them.
+#[derive(Debug, Clone)]
+pub struct LineStatistics {
+    lines: Vec<Statistics>,
+    total: Statistics,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Statistics {
+    /// Counts the statistics of `text`.
+    pub fn new(text: &str) -> Self {
+        let mut statistics = Self {
+            words: word_count(text),
+            ..Self::default()
+        };
+
+        for ch in text.chars().filter(|ch| !is_line_break(*ch)) {
+            statistics.characters += 1_usize;
+            if !ch.is_whitespace() {
+                statistics.characters_no_spaces += 1_usize;
+            }
+        }
+        statistics.paragraphs = lines(text).filter(|line| !line.trim().is_empty()).count();
+
+        statistics
+    }
+
+    /// Returns the estimated number of pages that hold `words_per_page` words each.
+    pub fn pages(&self, words_per_page: f64) -> f64 {
+        if words_per_page <= 0_f64 {
+            return 0_f64;
+        }
+        self.words as f64 / words_per_page
+    }
+
+    /// Returns the estimated reading time in minutes at [`WORDS_PER_MINUTE`].
+    pub fn reading_minutes(&self) -> f64 {
+        self.words as f64 / WORDS_PER_MINUTE
+    }
+}
+
+impl Add for Statistics {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            words: self.words + rhs.words,
+            characters: self.characters + rhs.characters,
+            characters_no_spaces: self.characters_no_spaces + rhs.characters_no_spaces,
+            paragraphs: self.paragraphs + rhs.paragraphs,
+        }
+    }
+}
+
+impl AddAssign for Statistics {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Statistics {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            words: self.words.saturating_sub(rhs.words),
+            characters: self.characters.saturating_sub(rhs.characters),
+            characters_no_spaces: self
+                .characters_no_spaces
+                .saturating_sub(rhs.characters_no_spaces),
+            paragraphs: self.paragraphs.saturating_sub(rhs.paragraphs),
+        }
+    }
+}
+
+impl SubAssign for Statistics {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl LineStatistics {
+    /// Counts every line of `text`.
+    pub fn new(text: &str) -> Self {
+        let lines = lines(text)
+            .map(Statistics::new)
+            .collect::<Vec<Statistics>>();
+        let total = lines
+            .iter()
+            .fold(Statistics::default(), |total, line| total + *line);
+
+        Self { lines, total }
+    }
+
+    /// Returns the statistics of the whole text.
+    pub fn total(&self) -> Statistics {
+        self.total
+    }
+
+    /// Returns the number of lines in the text.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Replaces the `removed` lines starting at `first` with `lines`. Only the given lines are
+    /// counted so the cost depends on the size of the edit and not on the size of the text.
+    pub fn splice(&mut self, first: usize, removed: usize, lines: &[&str]) {
+        let first = first.min(self.lines.len());
+        let last = (first + removed).min(self.lines.len());
+        let counted = lines
+            .iter()
+            .map(|line| Statistics::new(line))
+            .collect::<Vec<Statistics>>();
+
+        for line in self.lines.splice(first..last, counted.iter().copied()) {
+            self.total -= line;
+        }
+        for line in counted {
+            self.total += line;
+        }
+    }
+}
+
+impl Default for LineStatistics {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_end_at_every_buffer_line_break() {
+        let text = "One two.\rThree.\r\nFour five six.\u{2029}Seven.\n";
+        let statistics = LineStatistics::new(text);
+        assert_eq!(statistics.line_count(), 5_usize);
+        assert_eq!(statistics.total(), Statistics::new(text));
+        assert_eq!(statistics.total().words, 7_usize);
+        assert_eq!(statistics.total().paragraphs, 4_usize);
+        assert_eq!(statistics.total().characters, 34_usize);
+    }
+
+    #[test]
+    fn splicing_matches_counting_again() {
+        let mut statistics = LineStatistics::new("One.\nTwo three.\nFour.");
+        statistics.splice(1_usize, 1_usize, &["Two.", "Three and a half."]);
+        assert_eq!(statistics.line_count(), 4_usize);
+        assert_eq!(
+            statistics.total(),
+            Statistics::new("One.\nTwo.\nThree and a half.\nFour.")
+        );
+    }
+}
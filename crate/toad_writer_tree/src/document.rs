@@ -0,0 +1,397 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Document module.
+//!
+//! The [`DocumentTree`] that holds the chapters and scenes of a manuscript.
+
+use crate::{
+    metadata::{SceneFilter, SceneMetadata},
+    node::{Node, NodeId, NodeKind},
+    paragraph::{split_paragraphs, Paragraph},
+};
+use std::ops::Range;
+use thiserror::Error;
+
+/// Lines that separate two scenes in a chapter.
+const SCENE_BREAKS: [&str; 3_usize] = ["***", "* * *", "#"];
+
+/// Errors that can occur while changing a [`DocumentTree`].
+#[derive(Debug, Error)]
+pub enum TreeError {
+    /// The node does not exist in the tree.
+    #[error("Node {0} does not exist in the document tree.")]
+    NodeNotFound(NodeId),
+    /// The node cannot hold the requested child.
+    #[error("A {child:?} cannot be added to a {parent:?}.")]
+    InvalidChild { parent: NodeKind, child: NodeKind },
+    /// The text of a node changed since the edit was created.
+    #[error("The text of node {0} does not match the edit at {1:?}.")]
+    StaleEdit(NodeId, Range<usize>),
+    /// Only scenes hold metadata.
+    #[error("Node {0} is not a scene.")]
+    NotAScene(NodeId),
+    /// Two edits change the same text.
+    #[error("Edits overlap in node {0} at {1:?}.")]
+    OverlappingEdits(NodeId, Range<usize>),
+}
+
+/// A change of text in a single node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The node whose text is changed.
+    pub node: NodeId,
+    /// Byte range of the replaced text in the node.
+    pub range: Range<usize>,
+    /// The text expected in `range`. The edit is refused if it does not match.
+    pub old: String,
+    /// The text that replaces `old`.
+    pub new: String,
+}
+
+/// Arena based tree of [`Node`]s. The root is always a [`NodeKind::Book`] that holds chapters,
+/// and every chapter holds scenes.
+#[derive(Debug, Clone)]
+pub struct DocumentTree {
+    nodes: Vec<Node>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl TextEdit {
+    /// Returns the character range of the edit in `text`, the text of the edited node.
+    pub fn chars(&self, text: &str) -> Range<usize> {
+        let start = text[..self.range.start].chars().count();
+        start..start + self.old.chars().count()
+    }
+}
+
+impl DocumentTree {
+    /// Returns a new tree with an empty book titled `title`.
+    pub fn new(title: &str) -> Self {
+        Self {
+            nodes: vec![Node::new(NodeId(0_usize), NodeKind::Book, None, title)],
+        }
+    }
+
+    /// Returns the id of the root book.
+    pub fn root(&self) -> NodeId {
+        NodeId(0_usize)
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0)
+    }
+
+    /// Adds an empty chapter at the end of the book and returns its id.
+    pub fn add_chapter(&mut self, title: &str) -> NodeId {
+        self.push(self.root(), NodeKind::Chapter, title)
+            .expect("The root is always a book")
+    }
+
+    /// Adds a scene with `text` at the end of `chapter` and returns its id.
+    pub fn add_scene(
+        &mut self,
+        chapter: NodeId,
+        title: &str,
+        text: &str,
+    ) -> Result<NodeId, TreeError> {
+        let scene = self.push(chapter, NodeKind::Scene, title)?;
+        self.nodes[scene.0].text = text.to_string();
+        Ok(scene)
+    }
+
+    /// Adds a chapter with the scenes found in `text`. Scenes are separated by lines that only
+    /// hold a scene break such as `***`. The offset of every scene in `text` is kept so that
+    /// changes can be mapped back to the source.
+    pub fn add_chapter_text(&mut self, title: &str, text: &str) -> NodeId {
+        let chapter = self.add_chapter(title);
+
+        for (index, (offset, scene)) in split_scenes(text).into_iter().enumerate() {
+            let scene = self
+                .add_scene(
+                    chapter,
+                    format!("Scene {}", index + 1_usize).as_str(),
+                    scene,
+                )
+                .expect("A chapter can always hold scenes");
+            self.nodes[scene.0].offset = offset;
+        }
+
+        chapter
+    }
+
+    /// Returns the children of `id`, or nothing if the node does not exist.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.node(id).map(|node| node.children()).unwrap_or(&[])
+    }
+
+    /// Returns every chapter in reading order.
+    pub fn chapters(&self) -> Vec<NodeId> {
+        self.children(self.root()).to_vec()
+    }
+
+    /// Returns every scene in reading order.
+    pub fn scenes(&self) -> Vec<NodeId> {
+        self.chapters()
+            .iter()
+            .flat_map(|chapter| self.children(*chapter).to_vec())
+            .collect()
+    }
+
+    /// Returns the scenes whose metadata `filter` picks, in reading order.
+    pub fn scenes_where(&self, filter: &SceneFilter) -> Vec<NodeId> {
+        self.scenes()
+            .into_iter()
+            .filter(|scene| {
+                self.node(*scene)
+                    .is_some_and(|node| filter.matches(&node.metadata))
+            })
+            .collect()
+    }
+
+    /// To give `scene` the values of `metadata`.
+    pub fn set_metadata(
+        &mut self,
+        scene: NodeId,
+        metadata: SceneMetadata,
+    ) -> Result<(), TreeError> {
+        let node = self.node_mut(scene).ok_or(TreeError::NodeNotFound(scene))?;
+        if node.kind != NodeKind::Scene {
+            return Err(TreeError::NotAScene(scene));
+        }
+        node.metadata = metadata;
+        Ok(())
+    }
+
+    /// Returns the paragraphs of `scene` in order, or nothing if it is not a scene.
+    pub fn paragraphs(&self, scene: NodeId) -> Vec<Paragraph<'_>> {
+        self.node(scene)
+            .filter(|node| node.kind == NodeKind::Scene)
+            .map(|node| split_paragraphs(scene, &node.text))
+            .unwrap_or_default()
+    }
+
+    /// Returns the paragraphs of every scene in reading order.
+    pub fn all_paragraphs(&self) -> Vec<Paragraph<'_>> {
+        self.scenes()
+            .into_iter()
+            .flat_map(|scene| self.paragraphs(scene))
+            .collect()
+    }
+
+    /// Returns the character offset in the source of the byte at `byte` in the text of `node`.
+    pub fn source_offset(&self, node: NodeId, byte: usize) -> Option<usize> {
+        let node = self.node(node)?;
+        Some(node.offset + node.text.get(..byte)?.chars().count())
+    }
+
+    /// Returns the chapter that holds `scene`.
+    pub fn chapter_of(&self, scene: NodeId) -> Option<NodeId> {
+        self.node(scene)
+            .filter(|node| node.kind == NodeKind::Scene)
+            .and_then(|node| node.parent)
+    }
+
+    /// Applies every edit or none of them. All edits are checked against the current text before
+    /// anything is changed.
+    pub fn apply_edits(&mut self, edits: &[TextEdit]) -> Result<(), TreeError> {
+        let mut sorted = edits.iter().collect::<Vec<&TextEdit>>();
+        sorted.sort_by_key(|edit| (edit.node, edit.range.start));
+
+        for (index, edit) in sorted.iter().enumerate() {
+            let node = self
+                .node(edit.node)
+                .ok_or(TreeError::NodeNotFound(edit.node))?;
+            if node.text.get(edit.range.clone()) != Some(edit.old.as_str()) {
+                return Err(TreeError::StaleEdit(edit.node, edit.range.clone()));
+            }
+            let Some(next) = sorted.get(index + 1_usize) else {
+                continue;
+            };
+            if next.node == edit.node && next.range.start < edit.range.end {
+                return Err(TreeError::OverlappingEdits(edit.node, next.range.clone()));
+            }
+        }
+
+        // Apply from the back so that earlier ranges stay valid.
+        for edit in sorted.iter().rev() {
+            self.nodes[edit.node.0]
+                .text
+                .replace_range(edit.range.clone(), edit.new.as_str());
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, parent: NodeId, kind: NodeKind, title: &str) -> Result<NodeId, TreeError> {
+        let parent_kind = self
+            .node(parent)
+            .ok_or(TreeError::NodeNotFound(parent))?
+            .kind;
+        let allowed = matches!(
+            (parent_kind, kind),
+            (NodeKind::Book, NodeKind::Chapter) | (NodeKind::Chapter, NodeKind::Scene)
+        );
+        if !allowed {
+            return Err(TreeError::InvalidChild {
+                parent: parent_kind,
+                child: kind,
+            });
+        }
+
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node::new(id, kind, Some(parent), title));
+        self.nodes[parent.0].children.push(id);
+        Ok(id)
+    }
+}
+
+/// Splits `text` at the scene break lines. Returns the character offset and text of every scene.
+fn split_scenes(text: &str) -> Vec<(usize, &str)> {
+    let mut scenes = Vec::new();
+    let (mut start, mut scene_chars) = (0_usize, 0_usize);
+    let (mut bytes, mut chars) = (0_usize, 0_usize);
+
+    for line in text.split_inclusive('\n') {
+        bytes += line.len();
+        chars += line.chars().count();
+        if SCENE_BREAKS.contains(&line.trim()) {
+            scenes.push((scene_chars, &text[start..bytes - line.len()]));
+            start = bytes;
+            scene_chars = chars;
+        }
+    }
+    scenes.push((scene_chars, &text[start..]));
+
+    scenes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(node: NodeId, text: &str, old: &str, new: &str) -> TextEdit {
+        let start = text.find(old).unwrap();
+        TextEdit {
+            node,
+            range: start..start + old.len(),
+            old: old.to_string(),
+            new: new.to_string(),
+        }
+    }
+
+    fn tree(text: &str) -> (DocumentTree, NodeId) {
+        let mut tree = DocumentTree::new("Book");
+        let chapter = tree.add_chapter("Chapter");
+        let scene = tree.add_scene(chapter, "Scene", text).unwrap();
+        (tree, scene)
+    }
+
+    #[test]
+    fn scenes_are_split_at_scene_breaks() {
+        let text = "Début.\n***\nMiddle.\n  * * *  \nEnd.";
+        assert_eq!(
+            split_scenes(text),
+            vec![
+                (0_usize, "Début.\n"),
+                (11_usize, "Middle.\n"),
+                (29_usize, "End.")
+            ]
+        );
+        assert_eq!(split_scenes("No break."), vec![(0_usize, "No break.")]);
+        assert_eq!(split_scenes("#\n"), vec![(0_usize, ""), (2_usize, "")]);
+    }
+
+    #[test]
+    fn source_offsets_count_characters_from_the_chapter() {
+        let mut tree = DocumentTree::new("Book");
+        let chapter = tree.add_chapter_text("One", "Début.\n***\nÉté here.");
+        let scenes = tree.scenes();
+        assert_eq!(tree.chapter_of(scenes[1_usize]), Some(chapter));
+        assert_eq!(tree.node(scenes[1_usize]).unwrap().title, "Scene 2");
+        assert_eq!(
+            tree.source_offset(scenes[1_usize], "Été ".len()),
+            Some(15_usize)
+        );
+        assert_eq!(tree.source_offset(scenes[1_usize], 1_usize), None);
+        assert_eq!(tree.source_offset(scenes[0_usize], 0_usize), Some(0_usize));
+    }
+
+    #[test]
+    fn children_only_fit_their_parent() {
+        let (mut tree, scene) = tree("Text.");
+        assert!(matches!(
+            tree.add_scene(tree.root(), "Scene", ""),
+            Err(TreeError::InvalidChild { .. })
+        ));
+        assert!(matches!(
+            tree.add_scene(scene, "Scene", ""),
+            Err(TreeError::InvalidChild { .. })
+        ));
+        let chapter = tree.chapters()[0_usize];
+        assert!(matches!(
+            tree.set_metadata(chapter, SceneMetadata::default()),
+            Err(TreeError::NotAScene(_))
+        ));
+    }
+
+    #[test]
+    fn edits_are_applied_from_the_back() {
+        let text = "Mara met Mara's cat.";
+        let (mut tree, scene) = tree(text);
+        let second = TextEdit {
+            node: scene,
+            range: 9_usize..13_usize,
+            old: "Mara".to_string(),
+            new: "Tomas".to_string(),
+        };
+        let edits = [second, edit(scene, text, "Mara", "Tom")];
+        tree.apply_edits(&edits).unwrap();
+        assert_eq!(tree.node(scene).unwrap().text, "Tom met Tomas's cat.");
+        assert_eq!(edits[1_usize].chars(text), 0_usize..4_usize);
+    }
+
+    #[test]
+    fn edits_may_cross_paragraphs() {
+        let text = "First line.\n\nSecond line.";
+        let (mut tree, scene) = tree(text);
+        tree.apply_edits(&[edit(scene, text, "line.\n\nSecond", "and")])
+            .unwrap();
+        assert_eq!(tree.node(scene).unwrap().text, "First and line.");
+        assert_eq!(tree.paragraphs(scene).len(), 1_usize);
+    }
+
+    #[test]
+    fn overlapping_edits_change_nothing() {
+        let text = "Mara Venn came.";
+        let (mut tree, scene) = tree(text);
+        let edits = [
+            edit(scene, text, "came", "left"),
+            edit(scene, text, "Mara Venn", "Tom"),
+            edit(scene, text, "Venn", "Ash"),
+        ];
+        assert!(matches!(
+            tree.apply_edits(&edits),
+            Err(TreeError::OverlappingEdits(_, _))
+        ));
+        assert_eq!(tree.node(scene).unwrap().text, text);
+    }
+
+    #[test]
+    fn stale_edits_change_nothing() {
+        let text = "Mara came.";
+        let (mut tree, scene) = tree(text);
+        let mut stale = edit(scene, text, "came", "left");
+        stale.old = "went".to_string();
+        let edits = [edit(scene, text, "Mara", "Tom"), stale];
+        assert!(matches!(
+            tree.apply_edits(&edits),
+            Err(TreeError::StaleEdit(_, _))
+        ));
+        assert_eq!(tree.node(scene).unwrap().text, text);
+    }
+}
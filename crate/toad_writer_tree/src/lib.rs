@@ -2,3 +2,13 @@
 // Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
 
 //! Library for handling the Toad Writer document tree.
+
+mod document;
+mod metadata;
+mod node;
+mod paragraph;
+
+pub use document::{DocumentTree, TextEdit, TreeError};
+pub use metadata::{SceneFilter, SceneMetadata, SceneStatus};
+pub use node::{Node, NodeId, NodeKind};
+pub use paragraph::Paragraph;
@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Metadata module.
+//!
+//! What the writer tracks about each scene besides its text: the point of view character, the
+//! date in the story, the setting and how far the draft is. A [`SceneFilter`] picks the scenes
+//! with given values.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// How far along a scene is.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum SceneStatus {
+    /// Planned but not written yet.
+    Outline,
+    #[default]
+    Draft,
+    Revised,
+    Final,
+}
+
+/// The metadata of a scene. Names are kept as written, so that they may refer to lore entities
+/// or not.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SceneMetadata {
+    /// Name of the point of view character.
+    #[serde(default)]
+    pub pov: String,
+    /// Date of the scene in the story, as written with a calendar or as a day count.
+    #[serde(default)]
+    pub date: String,
+    /// Name of the place the scene is set in.
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub status: SceneStatus,
+}
+
+/// The values a scene must have to be picked. Fields left as [`None`] pick every scene.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SceneFilter {
+    pub pov: Option<String>,
+    pub location: Option<String>,
+    pub status: Option<SceneStatus>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl SceneStatus {
+    /// Every status, from the earliest to the last.
+    pub const ALL: [SceneStatus; 4_usize] = [
+        SceneStatus::Outline,
+        SceneStatus::Draft,
+        SceneStatus::Revised,
+        SceneStatus::Final,
+    ];
+}
+
+impl Display for SceneStatus {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SceneStatus::Outline => write!(f, "Outline"),
+            SceneStatus::Draft => write!(f, "Draft"),
+            SceneStatus::Revised => write!(f, "Revised"),
+            SceneStatus::Final => write!(f, "Final"),
+        }
+    }
+}
+
+impl SceneMetadata {
+    /// Returns true if nothing was set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl SceneFilter {
+    /// Returns true if `metadata` has every value of the filter. Names are compared ignoring case
+    /// and surrounding spaces.
+    pub fn matches(&self, metadata: &SceneMetadata) -> bool {
+        let same = |wanted: &Option<String>, value: &str| {
+            wanted
+                .as_ref()
+                .is_none_or(|wanted| wanted.trim().to_lowercase() == value.trim().to_lowercase())
+        };
+        same(&self.pov, &metadata.pov)
+            && same(&self.location, &metadata.location)
+            && self.status.is_none_or(|status| status == metadata.status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(pov: &str, location: &str, status: SceneStatus) -> SceneMetadata {
+        SceneMetadata {
+            pov: pov.to_string(),
+            location: location.to_string(),
+            status,
+            ..SceneMetadata::default()
+        }
+    }
+
+    #[test]
+    fn filters_ignore_case_and_spaces() {
+        let scene = metadata("Mara", "Varn", SceneStatus::Revised);
+        let filter = SceneFilter {
+            pov: Some(" mara ".to_string()),
+            ..SceneFilter::default()
+        };
+        assert!(filter.matches(&scene));
+        assert!(SceneFilter::default().matches(&scene));
+        let filter = SceneFilter {
+            location: Some("Varn".to_string()),
+            status: Some(SceneStatus::Final),
+            ..SceneFilter::default()
+        };
+        assert!(!filter.matches(&scene));
+    }
+
+    #[test]
+    fn empty_metadata_is_the_default() {
+        assert!(SceneMetadata::default().is_empty());
+        assert!(!metadata("", "", SceneStatus::Final).is_empty());
+        assert_eq!(SceneStatus::default(), SceneStatus::Draft);
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Node module.
+//!
+//! The items that make up a [`crate::DocumentTree`].
+
+use crate::metadata::SceneMetadata;
+use std::fmt::{Display, Formatter};
+
+/// Index of a [`Node`] in its [`crate::DocumentTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub(crate) usize);
+
+/// What a [`Node`] stands for in the manuscript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// The root of the tree.
+    Book,
+    /// A chapter that holds scenes.
+    Chapter,
+    /// A scene that holds the actual text.
+    Scene,
+}
+
+/// A single item of the [`crate::DocumentTree`].
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub(crate) id: NodeId,
+    pub(crate) kind: NodeKind,
+    pub(crate) parent: Option<NodeId>,
+    pub(crate) children: Vec<NodeId>,
+    /// Title shown in the document tree.
+    pub title: String,
+    /// Text of the node. Only scenes hold text.
+    pub text: String,
+    /// Character offset of the text in the source it was read from. Used to map changes back to
+    /// the editor.
+    pub offset: usize,
+    /// What the writer tracks about the node. Only scenes hold metadata.
+    pub metadata: SceneMetadata,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl NodeId {
+    /// Returns the index of the node in the tree.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl Display for NodeId {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl Node {
+    pub(crate) fn new(id: NodeId, kind: NodeKind, parent: Option<NodeId>, title: &str) -> Self {
+        Self {
+            id,
+            kind,
+            parent,
+            children: Vec::new(),
+            title: title.to_string(),
+            text: String::new(),
+            offset: 0_usize,
+            metadata: SceneMetadata::default(),
+        }
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_nodes_are_empty() {
+        let node = Node::new(
+            NodeId(2_usize),
+            NodeKind::Scene,
+            Some(NodeId(1_usize)),
+            "One",
+        );
+        assert_eq!(node.id().index(), 2_usize);
+        assert_eq!(node.kind(), NodeKind::Scene);
+        assert_eq!(node.parent(), Some(NodeId(1_usize)));
+        assert!(node.children().is_empty());
+        assert!(node.text.is_empty() && node.metadata.is_empty());
+        assert_eq!(node.id().to_string(), "#2");
+    }
+}
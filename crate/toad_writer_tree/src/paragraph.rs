@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Paragraph module.
+//!
+//! The paragraphs of a scene. Every line that is not blank is a paragraph.
+
+use crate::node::NodeId;
+use std::ops::Range;
+
+/// A single paragraph borrowed from the text of a scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paragraph<'a> {
+    /// The scene that holds the paragraph.
+    pub scene: NodeId,
+    /// Byte offset of the paragraph in the scene text.
+    pub start: usize,
+    pub text: &'a str,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+impl Paragraph<'_> {
+    /// Returns the byte range of the paragraph in the scene text.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.start + self.text.len()
+    }
+
+    /// Returns `range`, a byte range in the paragraph, as a byte range in the scene text.
+    pub fn scene_range(&self, range: Range<usize>) -> Range<usize> {
+        self.start + range.start..self.start + range.end
+    }
+}
+
+/// Returns the paragraphs of `text`, the text of `scene`.
+pub(crate) fn split_paragraphs(scene: NodeId, text: &str) -> Vec<Paragraph<'_>> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0_usize;
+
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        if !content.trim().is_empty() {
+            paragraphs.push(Paragraph {
+                scene,
+                start,
+                text: content,
+            });
+        }
+        start += line.len();
+    }
+
+    paragraphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_lines_are_not_paragraphs() {
+        let text = "First.\r\n\n  \nSecond.";
+        let paragraphs = split_paragraphs(NodeId(1_usize), text);
+        let texts = paragraphs
+            .iter()
+            .map(|paragraph| paragraph.text)
+            .collect::<Vec<&str>>();
+        assert_eq!(texts, vec!["First.", "Second."]);
+        assert_eq!(&text[paragraphs[1_usize].range()], "Second.");
+        assert_eq!(
+            paragraphs[1_usize].scene_range(0_usize..3_usize),
+            12_usize..15_usize
+        );
+    }
+}
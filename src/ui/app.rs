@@ -55,6 +55,9 @@ mod imp {
             // Initialize CSS
             super::TWApplication::init_css("/com/github/feohr/ToadWriter/style.css");
 
+            // Keyboard shortcuts
+            self.obj().init_accels();
+
             // Main window
             let window = self.obj().create_window();
             info!(
@@ -120,6 +123,22 @@ impl TWApplication {
         window
     }
 
+    /// To set the keyboard shortcuts for the window actions.
+    fn init_accels(&self) {
+        let accels: [(&str, &[&str]); 7_usize] = [
+            ("win.document.new", &["<Ctrl>n"]),
+            ("win.document.close", &["<Ctrl>w"]),
+            ("win.find.open", &["<Ctrl>f", "<Ctrl>h"]),
+            ("win.view.split-horizontal", &["<Ctrl><Alt>h"]),
+            ("win.view.split-vertical", &["<Ctrl><Alt>v"]),
+            ("win.view.unsplit", &["<Ctrl><Alt>u"]),
+            ("win.view.focus-next", &["F6", "<Ctrl><Alt>Tab"]),
+        ];
+        for (action, keys) in accels {
+            self.set_accels_for_action(action, keys);
+        }
+    }
+
     /// To initialize and apply the CSS to the application.
     fn init_css(path: &str) {
         // Get the CssProvider
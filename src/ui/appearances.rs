@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Appearances module.
+//!
+//! Creates and returns a [`TWAppearanceWindow`] with a grid of how often every character and
+//! location is mentioned in each chapter of the opened documents. Chapters are numbered in the
+//! header and named in its tooltips to keep the grid narrow.
+
+use crate::ui::{export::export_text, window::TWApplicationWindow};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, Grid, Label, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::{AppearanceReport, Appearances, EntityKind};
+use std::cell::RefCell;
+use std::default::Default;
+
+/// Gaps of at least this many chapters are highlighted.
+const LONG_GAP: usize = 3_usize;
+/// Colour of the cells in a long gap.
+const GAP_COLOUR: &str = "#E06C75";
+/// File name suggested when exporting.
+const EXPORT_NAME: &str = "appearances.csv";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/appearances.ui")]
+    /// The appearances window struct.
+    pub struct TWAppearanceWindow {
+        /// A row for every entity and a column for every chapter.
+        #[template_child]
+        pub grid: TemplateChild<Grid>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        #[template_child]
+        pub export: TemplateChild<Button>,
+        /// The report shown, kept for the export.
+        pub report: RefCell<AppearanceReport>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWAppearanceWindow {
+        const NAME: &'static str = "TWAppearanceWindow";
+        type ParentType = Window;
+        type Type = super::TWAppearanceWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWAppearanceWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.export
+                .connect_clicked(clone!(@weak self as report => move |_| report.obj().export()));
+        }
+    }
+
+    impl WidgetImpl for TWAppearanceWindow {}
+
+    impl WindowImpl for TWAppearanceWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWAppearanceWindow(ObjectSubclass<imp::TWAppearanceWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWAppearanceWindow {
+    /// Returns a new instance of [`TWAppearanceWindow`] for the lore and the documents of
+    /// `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let appearances = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+
+        let (tree, _) = window.imp().tabs.document_tree();
+        let report = AppearanceReport::new(
+            &window.imp().lore.borrow(),
+            &tree,
+            &[EntityKind::Character, EntityKind::Location],
+        );
+        appearances.show_report(report);
+
+        appearances
+    }
+
+    /// To fill the grid with `report`.
+    fn show_report(&self, report: AppearanceReport) {
+        let grid = &self.imp().grid;
+        let chapters = report.chapters.len() as i32;
+
+        grid.attach(&Self::cell("<b>Entity</b>", 0_f32), 0_i32, 0_i32, 1_i32, 1_i32);
+        for (index, (_, title)) in report.chapters.iter().enumerate() {
+            let label = Self::cell(format!("<b>{}</b>", index + 1_usize).as_str(), 0.5_f32);
+            label.set_tooltip_text(Some(title.as_str()));
+            grid.attach(&label, index as i32 + 1_i32, 0_i32, 1_i32, 1_i32);
+        }
+        for (offset, heading) in ["First", "Last", "Longest gap"].iter().enumerate() {
+            let label = Self::cell(format!("<b>{heading}</b>").as_str(), 0_f32);
+            grid.attach(&label, chapters + 1_i32 + offset as i32, 0_i32, 1_i32, 1_i32);
+        }
+
+        for (row, appearances) in report.rows.iter().enumerate() {
+            let row = row as i32 + 1_i32;
+            let name = format!(
+                "{}\x20<small>({})</small>",
+                markup_escape_text(appearances.name.as_str()),
+                appearances.kind
+            );
+            grid.attach(&Self::cell(name.as_str(), 0_f32), 0_i32, row, 1_i32, 1_i32);
+
+            for (index, count) in appearances.counts.iter().enumerate() {
+                let cell = Self::count_cell(appearances, index, *count);
+                grid.attach(&cell, index as i32 + 1_i32, row, 1_i32, 1_i32);
+            }
+
+            let chapter = |index: Option<usize>| {
+                index
+                    .and_then(|index| report.chapters.get(index))
+                    .map(|(_, title)| markup_escape_text(title).to_string())
+                    .unwrap_or_else(|| "—".to_string())
+            };
+            let summary = [
+                chapter(appearances.first()),
+                chapter(appearances.last()),
+                appearances.longest_gap().to_string(),
+            ];
+            for (offset, text) in summary.iter().enumerate() {
+                let label = Self::cell(text.as_str(), 0_f32);
+                grid.attach(&label, chapters + 1_i32 + offset as i32, row, 1_i32, 1_i32);
+            }
+        }
+
+        if report.rows.is_empty() {
+            self.imp()
+                .status
+                .set_label("There are no characters or locations in the lore.");
+        }
+        self.imp().export.set_sensitive(!report.rows.is_empty());
+        self.imp().report.replace(report);
+    }
+
+    /// Returns the cell for the `count` mentions of `appearances` in chapter `index`. Chapters in
+    /// a gap are marked, and marked in colour if the gap is long.
+    fn count_cell(appearances: &Appearances, index: usize, count: usize) -> Label {
+        if count > 0_usize {
+            return Self::cell(count.to_string().as_str(), 0.5_f32);
+        }
+
+        let gap = appearances
+            .gaps()
+            .into_iter()
+            .find(|gap| gap.contains(&index));
+        let markup = match gap {
+            Some(gap) if gap.len() >= LONG_GAP => {
+                format!("<span foreground=\"{GAP_COLOUR}\">—</span>")
+            }
+            Some(_) => "—".to_string(),
+            None => "·".to_string(),
+        };
+        Self::cell(markup.as_str(), 0.5_f32)
+    }
+
+    /// Returns a label for a grid cell with `markup`.
+    fn cell(markup: &str, xalign: f32) -> Label {
+        let label = Label::new(None);
+        label.set_markup(markup);
+        label.set_xalign(xalign);
+        label
+    }
+
+    /// To ask where to export the report and write it there as CSV.
+    fn export(&self) {
+        let report = self.imp().report.borrow().clone();
+        export_text(
+            self,
+            "Export Appearances",
+            EXPORT_NAME,
+            &self.imp().status,
+            move || report.to_csv(),
+        );
+    }
+}
+
+impl Default for TWAppearanceWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWAppearanceWindow object")
+    }
+}
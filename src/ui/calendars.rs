@@ -0,0 +1,380 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Calendars module.
+//!
+//! Creates and returns a [`TWCalendarWindow`] to write the calendars of the setting as TOML, try
+//! them on a date and list the dates they find in the current document. Dates are shown with the
+//! day count timelines are ordered by.
+
+use crate::ui::{page::TWBuffer, window::TWApplicationWindow};
+use format::project::Project;
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, DropDown, Entry, Label, ListBox, StringList,
+    TextView, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::{Calendar, StoryTime};
+use std::cell::RefCell;
+use std::default::Default;
+use std::ops::Range;
+
+/// Definition of an added calendar, to be changed by the writer.
+const NEW_CALENDAR: &str = r#"name = "New Calendar"
+first_weekday = 0
+weekdays = ["Moonday", "Fireday", "Waterday", "Woodday", "Stoneday", "Restday"]
+
+[leap]
+every = 4
+
+[[months]]
+name = "Thaw"
+days = 90
+
+[[months]]
+name = "Bloom"
+days = 91
+
+[[months]]
+name = "Harvest"
+days = 91
+
+[[months]]
+name = "Frost"
+days = 90
+leap_days = 1
+
+[[eras]]
+name = "After Founding"
+abbreviation = "AF"
+start = 1
+
+[[moons]]
+name = "Moon"
+cycle = 28
+"#;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/calendars.ui")]
+    /// The calendars window struct.
+    pub struct TWCalendarWindow {
+        /// The calendar being edited.
+        #[template_child]
+        pub calendar: TemplateChild<DropDown>,
+        /// Names of the calendars of the project.
+        #[template_child]
+        pub calendars: TemplateChild<StringList>,
+        #[template_child]
+        pub add: TemplateChild<Button>,
+        #[template_child]
+        pub remove: TemplateChild<Button>,
+        /// The calendar as TOML.
+        #[template_child]
+        pub definition: TemplateChild<TextView>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        #[template_child]
+        pub save: TemplateChild<Button>,
+        /// A date or a day count to convert.
+        #[template_child]
+        pub date: TemplateChild<Entry>,
+        #[template_child]
+        pub converted: TemplateChild<Label>,
+        /// A row for every date found in the current document.
+        #[template_child]
+        pub dates: TemplateChild<ListBox>,
+        /// The window whose project holds the calendars.
+        pub window: WeakRef<TWApplicationWindow>,
+        /// The document the dates were found in, and their character ranges.
+        pub found: RefCell<Option<(TWBuffer, Vec<Range<i32>>)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWCalendarWindow {
+        const NAME: &'static str = "TWCalendarWindow";
+        type ParentType = Window;
+        type Type = super::TWCalendarWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWCalendarWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWCalendarWindow {}
+
+    impl WindowImpl for TWCalendarWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWCalendarWindow(ObjectSubclass<imp::TWCalendarWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWCalendarWindow {
+    /// Returns a new instance of [`TWCalendarWindow`] for the calendars of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let calendars = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        calendars.imp().window.set(Some(window));
+        calendars.list_calendars(0_usize);
+
+        calendars
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.calendar
+            .connect_selected_notify(clone!(@weak self as calendars => move |_| {
+                calendars.show_calendar();
+            }));
+        imp.add
+            .connect_clicked(clone!(@weak self as calendars => move |_| calendars.add()));
+        imp.remove
+            .connect_clicked(clone!(@weak self as calendars => move |_| calendars.remove()));
+        imp.save
+            .connect_clicked(clone!(@weak self as calendars => move |_| calendars.save()));
+        imp.date
+            .connect_changed(clone!(@weak self as calendars => move |_| calendars.convert()));
+        imp.dates
+            .connect_row_activated(clone!(@weak self as calendars => move |_, row| {
+                calendars.reveal(row.index() as usize);
+            }));
+    }
+
+    /// Returns the calendar chosen in the [`DropDown`].
+    fn calendar(&self) -> Option<Calendar> {
+        let window = self.imp().window.upgrade()?;
+        let index = self.imp().calendar.selected() as usize;
+        let calendar = window.imp().project.borrow().calendars.get(index).cloned();
+        calendar
+    }
+
+    /// To list the names of the calendars and choose the one at `index`.
+    fn list_calendars(&self, index: usize) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let names = window
+            .imp()
+            .project
+            .borrow()
+            .calendars
+            .iter()
+            .map(|calendar| calendar.name.clone())
+            .collect::<Vec<String>>();
+
+        imp.calendars.splice(
+            0_u32,
+            imp.calendars.n_items(),
+            &names.iter().map(String::as_str).collect::<Vec<&str>>(),
+        );
+        imp.calendar
+            .set_selected(index.min(names.len().saturating_sub(1_usize)) as u32);
+        imp.remove.set_sensitive(!names.is_empty());
+        self.show_calendar();
+    }
+
+    /// To show the definition of the chosen calendar and the dates it finds. Without calendars
+    /// the definition of a new one is shown, to be saved as the first.
+    fn show_calendar(&self) {
+        let imp = self.imp();
+        let definition = match self.calendar() {
+            Some(calendar) => Project::calendar_to_toml(&calendar).unwrap_or_else(|err| {
+                error!("{err}");
+                String::new()
+            }),
+            None => NEW_CALENDAR.to_string(),
+        };
+        imp.definition.buffer().set_text(definition.as_str());
+        imp.status.set_label("");
+        self.convert();
+        self.find_dates();
+    }
+
+    /// To add a calendar to the project and choose it.
+    fn add(&self) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        let Ok(calendar) = Project::calendar_from_toml(NEW_CALENDAR) else { return };
+
+        let index = {
+            let mut project = window.imp().project.borrow_mut();
+            project.calendars.push(calendar);
+            project.calendars.len() - 1_usize
+        };
+        window.save_project();
+        self.list_calendars(index);
+    }
+
+    /// To delete the chosen calendar from the project.
+    fn remove(&self) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        let index = self.imp().calendar.selected() as usize;
+
+        {
+            let mut project = window.imp().project.borrow_mut();
+            if index >= project.calendars.len() {
+                return;
+            }
+            project.calendars.remove(index);
+        }
+        window.save_project();
+        self.list_calendars(index);
+    }
+
+    /// To replace the chosen calendar with the definition, or add it if there is none. The
+    /// definition is kept for correction if it is not a valid calendar.
+    fn save(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let buffer = imp.definition.buffer();
+        let (start, end) = buffer.bounds();
+        let definition = buffer.text(&start, &end, false);
+
+        let calendar = match Project::calendar_from_toml(definition.as_str()) {
+            Ok(calendar) => calendar,
+            Err(err) => {
+                imp.status.set_label(err.to_string().as_str());
+                return;
+            }
+        };
+        if let Err(err) = calendar.validate() {
+            imp.status.set_label(err.to_string().as_str());
+            return;
+        }
+
+        let index = {
+            let mut project = window.imp().project.borrow_mut();
+            let index = imp.calendar.selected() as usize;
+            match project.calendars.get_mut(index) {
+                Some(saved) => {
+                    *saved = calendar;
+                    index
+                }
+                None => {
+                    project.calendars.push(calendar);
+                    project.calendars.len() - 1_usize
+                }
+            }
+        };
+        window.save_project();
+        self.list_calendars(index);
+        imp.status.set_label("Saved.");
+    }
+
+    /// To show the day count of the date in the entry, or the date of the day count, with its
+    /// weekday and moons.
+    fn convert(&self) {
+        let imp = self.imp();
+        let Some(calendar) = self.calendar() else {
+            imp.converted.set_label("Save the calendar to convert dates with it.");
+            return;
+        };
+        let text = imp.date.text();
+        if text.trim().is_empty() {
+            imp.converted.set_label("");
+            return;
+        }
+
+        let day = match text.trim().parse::<StoryTime>() {
+            Ok(day) => Some(day),
+            Err(_) => calendar
+                .parse(text.as_str())
+                .and_then(|date| calendar.to_day(date).ok()),
+        };
+        let label = match day {
+            Some(day) => Self::describe(&calendar, day),
+            None => format!("{:?} is not a date of {}.", text.trim(), calendar.name),
+        };
+        imp.converted.set_label(label.as_str());
+    }
+
+    /// To list the dates the chosen calendar finds in the document of the current tab.
+    fn find_dates(&self) {
+        let imp = self.imp();
+        while let Some(row) = imp.dates.row_at_index(0_i32) {
+            imp.dates.remove(&row);
+        }
+        imp.found.replace(None);
+        let Some(window) = imp.window.upgrade() else { return };
+        let Some(calendar) = self.calendar() else { return };
+        let Some(buffer) = window.imp().tabs.current_page().map(|page| page.tw_buffer()) else {
+            return;
+        };
+
+        let (start, end) = buffer.bounds();
+        let text = buffer.text(&start, &end, false);
+        let mut ranges = Vec::new();
+        for (range, date) in calendar.find_dates(text.as_str()) {
+            let Ok(day) = calendar.to_day(date) else { continue };
+            let label = Label::new(Some(
+                format!("{} — {}", &text[range.clone()], Self::describe(&calendar, day)).as_str(),
+            ));
+            label.set_xalign(0_f32);
+            imp.dates.append(&label);
+
+            let start = text[..range.start].chars().count() as i32;
+            let end = start + text[range].chars().count() as i32;
+            ranges.push(start..end);
+        }
+        if ranges.is_empty() {
+            let label = Label::new(Some("No dates found."));
+            label.set_xalign(0_f32);
+            imp.dates.append(&label);
+        }
+        imp.found.replace(Some((buffer, ranges)));
+    }
+
+    /// To select the date in row `index` in its document.
+    fn reveal(&self, index: usize) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        let found = self.imp().found.borrow().clone();
+        let Some((buffer, ranges)) = found else { return };
+        let Some(range) = ranges.get(index) else { return };
+
+        window.imp().tabs.reveal(&buffer, range.start, range.end);
+    }
+
+    /// Returns `day` as a date of `calendar` with its day count, weekday and moons.
+    fn describe(calendar: &Calendar, day: StoryTime) -> String {
+        let mut parts = vec![
+            calendar.format(calendar.from_day(day)),
+            format!("day {day}"),
+        ];
+        parts.extend(calendar.weekday(day).map(String::from));
+        parts.extend(
+            calendar
+                .moon_phases(day)
+                .into_iter()
+                .map(|(moon, phase)| format!("{moon}: {phase}")),
+        );
+        parts.join(" · ")
+    }
+}
+
+impl Default for TWCalendarWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWCalendarWindow object")
+    }
+}
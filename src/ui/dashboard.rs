@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Dashboard module.
+//!
+//! Creates and returns a [`TWDashboardWindow`] that shows the writing history of the project:
+//! streaks, a calendar heatmap and how much every chapter grew. The history is recorded from the
+//! word counts of the [`TWPage`] buffers.
+//! ['TWPage'] : [`crate::ui::page::TWPage`]
+
+mod heatmap;
+
+use crate::ui::window::{today, TWApplicationWindow};
+use format::history::History;
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, prelude::*,
+    subclass::prelude::*, CompositeTemplate, DrawingArea, Label, LevelBar, ListBox, Orientation,
+    Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::collections::BTreeMap;
+use std::default::Default;
+use time::Duration;
+
+/// Number of days the chapter growth is counted over.
+const GROWTH_DAYS: i64 = 30_i64;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/dashboard.ui")]
+    /// The dashboard window struct.
+    pub struct TWDashboardWindow {
+        /// Current writing streak.
+        #[template_child]
+        pub streak: TemplateChild<Label>,
+        /// Longest writing streak.
+        #[template_child]
+        pub longest: TemplateChild<Label>,
+        /// Words added and removed today.
+        #[template_child]
+        pub today: TemplateChild<Label>,
+        /// Time spent typing today.
+        #[template_child]
+        pub typing: TemplateChild<Label>,
+        /// Number of documents edited today.
+        #[template_child]
+        pub documents: TemplateChild<Label>,
+        #[template_child]
+        pub heatmap: TemplateChild<DrawingArea>,
+        /// A row for every chapter with its recent growth.
+        #[template_child]
+        pub growth: TemplateChild<ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWDashboardWindow {
+        const NAME: &'static str = "TWDashboardWindow";
+        type ParentType = Window;
+        type Type = super::TWDashboardWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWDashboardWindow {}
+
+    impl WidgetImpl for TWDashboardWindow {}
+
+    impl WindowImpl for TWDashboardWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWDashboardWindow(ObjectSubclass<imp::TWDashboardWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWDashboardWindow {
+    /// Returns a new instance of [`TWDashboardWindow`] for the history of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let dashboard = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+
+        let history = window.imp().project.borrow().history.clone();
+        let words = window
+            .imp()
+            .tabs
+            .pages()
+            .iter()
+            .map(|page| (page.document_title(), page.tw_buffer().statistics().words))
+            .collect::<BTreeMap<String, usize>>();
+        dashboard.show_summary(&history);
+        dashboard.show_growth(&history, &words);
+        dashboard
+            .imp()
+            .heatmap
+            .set_draw_func(move |_, cr, width, height| {
+                if let Err(err) = heatmap::draw(cr, width, height, &history, today()) {
+                    warn!("Cannot draw the writing heatmap: {err}");
+                }
+            });
+
+        dashboard
+    }
+
+    /// To show the streaks and what was written today.
+    fn show_summary(&self, history: &History) {
+        let imp = self.imp();
+        let today = today();
+        let day = history.day(today).cloned().unwrap_or_default();
+
+        imp.streak
+            .set_label(format!("Current streak: {} days", history.streak(today)).as_str());
+        imp.longest
+            .set_label(format!("Longest streak: {} days", history.longest_streak()).as_str());
+        imp.today
+            .set_label(format!("Today: +{} / −{} words", day.added, day.removed).as_str());
+        imp.typing
+            .set_label(format!("Typing: {} min", day.typing / 60_000_u64).as_str());
+        imp.documents
+            .set_label(format!("Documents touched: {}", day.documents.len()).as_str());
+    }
+
+    /// To list the growth of every chapter in the history or in `words`, the word count of the
+    /// opened documents by title.
+    fn show_growth(&self, history: &History, words: &BTreeMap<String, usize>) {
+        let mut growth = history.growth(today() - Duration::days(GROWTH_DAYS - 1_i64));
+        for title in words.keys() {
+            growth.entry(title.clone()).or_default();
+        }
+        let most = growth
+            .values()
+            .copied()
+            .max()
+            .unwrap_or_default()
+            .max(1_i64);
+
+        for (title, grown) in growth {
+            let row = gtk::Box::new(Orientation::Horizontal, 10_i32);
+            let label = Label::new(Some(title.as_str()));
+            label.set_hexpand(true);
+            label.set_xalign(0_f32);
+
+            let bar = LevelBar::new();
+            bar.set_width_request(200_i32);
+            bar.set_valign(gtk::Align::Center);
+            bar.set_value(grown.max(0_i64) as f64 / most as f64);
+
+            let count = match words.get(&title) {
+                Some(words) => format!("{grown:+} words ({words} now)"),
+                None => format!("{grown:+} words"),
+            };
+            row.append(&label);
+            row.append(&bar);
+            row.append(&Label::new(Some(count.as_str())));
+            self.imp().growth.append(&row);
+        }
+    }
+}
+
+impl Default for TWDashboardWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWDashboardWindow object")
+    }
+}
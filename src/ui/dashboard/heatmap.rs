@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Heatmap module.
+//!
+//! To draw a calendar of the last year with a square for every day, darker the more words were
+//! written on it. Every column is a week starting on Monday.
+
+use format::history::History;
+use gtk::cairo::{Context, Error};
+use time::{Date, Duration};
+
+/// Number of weeks shown.
+const WEEKS: i64 = 53_i64;
+/// Space between two squares in pixels.
+const GAP: f64 = 2_f64;
+/// Colour of a day without words.
+const EMPTY: (f64, f64, f64) = (0.88_f64, 0.88_f64, 0.85_f64);
+/// Colours from a few words to the most words written on a day.
+const LEVELS: [(f64, f64, f64); 4_usize] = [
+    (0.72_f64, 0.80_f64, 0.60_f64),
+    (0.53_f64, 0.65_f64, 0.42_f64),
+    (0.35_f64, 0.48_f64, 0.28_f64),
+    (0.20_f64, 0.30_f64, 0.16_f64),
+];
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// Draws the days of `history` up to `today` to fit `width` and `height`.
+pub fn draw(
+    cr: &Context,
+    width: i32,
+    height: i32,
+    history: &History,
+    today: Date,
+) -> Result<(), Error> {
+    let size = (width as f64 / WEEKS as f64).min(height as f64 / 7_f64) - GAP;
+    if size <= 0_f64 {
+        return Ok(());
+    }
+
+    let monday = today - Duration::days(today.weekday().number_days_from_monday() as i64);
+    let first = monday - Duration::weeks(WEEKS - 1_i64);
+    let most = (0_i64..=(today - first).whole_days())
+        .map(|day| history.words_on(first + Duration::days(day)))
+        .max()
+        .unwrap_or_default();
+
+    let mut date = first;
+    while date <= today {
+        let week = (date - first).whole_weeks() as f64;
+        let weekday = date.weekday().number_days_from_monday() as f64;
+        let (red, green, blue) = colour(history.words_on(date), most);
+
+        cr.set_source_rgb(red, green, blue);
+        cr.rectangle(week * (size + GAP), weekday * (size + GAP), size, size);
+        cr.fill()?;
+        date += Duration::DAY;
+    }
+    Ok(())
+}
+
+/// Returns the colour of a day with `words` when the most words written on a day is `most`.
+fn colour(words: i64, most: i64) -> (f64, f64, f64) {
+    if words <= 0_i64 || most <= 0_i64 {
+        return EMPTY;
+    }
+    let level = (words as f64 / most as f64 * LEVELS.len() as f64).ceil() as usize;
+    LEVELS[level.clamp(1_usize, LEVELS.len()) - 1_usize]
+}
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Dialogue module.
+//!
+//! Creates and returns a [`TWDialogueWindow`] that lists the dialogue of the opened documents
+//! along with who speaks it. The list can be filtered to the lines of a single character, and
+//! choosing a line shows it in its document.
+
+use crate::ui::{page::TWBuffer, window::TWApplicationWindow};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, CompositeTemplate, DropDown, Label, ListBox, StringList, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::EntityKind;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::default::Default;
+use syntax::dialogue::SpeakerFinder;
+
+/// Filter entry that shows every line.
+const EVERYONE: &str = "Everyone";
+/// Filter entry and label for lines without a known speaker.
+const UNKNOWN: &str = "Unknown speaker";
+
+/// A line of dialogue along with where to find it.
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub speaker: Option<String>,
+    pub chapter: String,
+    pub text: String,
+    pub buffer: TWBuffer,
+    /// Character offsets of the line in the buffer.
+    pub start: i32,
+    pub end: i32,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/dialogue.ui")]
+    /// The dialogue window struct.
+    pub struct TWDialogueWindow {
+        /// Chooses whose lines are listed.
+        #[template_child]
+        pub speaker: TemplateChild<DropDown>,
+        #[template_child]
+        pub speakers: TemplateChild<StringList>,
+        #[template_child]
+        pub lines: TemplateChild<ListBox>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        /// Every line of dialogue in reading order.
+        pub all: RefCell<Vec<Line>>,
+        /// The lines listed, in the order of the rows.
+        pub shown: RefCell<Vec<Line>>,
+        /// The window whose documents are listed.
+        pub window: WeakRef<TWApplicationWindow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWDialogueWindow {
+        const NAME: &'static str = "TWDialogueWindow";
+        type ParentType = Window;
+        type Type = super::TWDialogueWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWDialogueWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.speaker
+                .connect_selected_notify(clone!(@weak self as dialogue => move |_| {
+                    dialogue.obj().show_lines();
+                }));
+            self.lines
+                .connect_row_activated(clone!(@weak self as dialogue => move |_, row| {
+                    dialogue.obj().reveal(row.index());
+                }));
+        }
+    }
+
+    impl WidgetImpl for TWDialogueWindow {}
+
+    impl WindowImpl for TWDialogueWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWDialogueWindow(ObjectSubclass<imp::TWDialogueWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWDialogueWindow {
+    /// Returns a new instance of [`TWDialogueWindow`] with the dialogue of the documents opened in
+    /// `window`. The characters of the lore are used to recognise speakers.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let dialogue = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        dialogue.imp().window.set(Some(window));
+
+        let lines = Self::find_lines(window);
+        let speakers = lines
+            .iter()
+            .filter_map(|line| line.speaker.clone())
+            .collect::<BTreeSet<String>>();
+        let imp = dialogue.imp();
+        imp.speakers.append(EVERYONE);
+        speakers
+            .iter()
+            .for_each(|speaker| imp.speakers.append(speaker.as_str()));
+        imp.speakers.append(UNKNOWN);
+        imp.all.replace(lines);
+        dialogue.show_lines();
+
+        dialogue
+    }
+
+    /// Returns every line of dialogue in the documents of `window`.
+    fn find_lines(window: &TWApplicationWindow) -> Vec<Line> {
+        let lore = window.imp().lore.borrow();
+        let finder = SpeakerFinder::new(
+            lore.of_kind(EntityKind::Character)
+                .flat_map(|entity| entity.names().map(|name| (name, entity.name.as_str()))),
+        );
+        let (tree, chapters) = window.imp().tabs.document_tree();
+
+        finder
+            .lines(&tree)
+            .into_iter()
+            .filter_map(|line| {
+                let chapter = tree.chapter_of(line.scene)?;
+                let (_, buffer) = chapters.iter().find(|(id, _)| *id == chapter)?;
+                let scene = tree.node(line.scene)?;
+                Some(Line {
+                    chapter: tree.node(chapter)?.title.clone(),
+                    text: line.text(scene.text.as_str()).to_string(),
+                    buffer: buffer.clone(),
+                    start: tree.source_offset(line.scene, line.range.start)? as i32,
+                    end: tree.source_offset(line.scene, line.range.end)? as i32,
+                    speaker: line.speaker,
+                })
+            })
+            .collect()
+    }
+
+    /// To list the lines of the chosen speaker.
+    fn show_lines(&self) {
+        let imp = self.imp();
+        while let Some(row) = imp.lines.row_at_index(0_i32) {
+            imp.lines.remove(&row);
+        }
+
+        let filter = imp
+            .speaker
+            .selected_item()
+            .and_then(|item| item.downcast::<gtk::StringObject>().ok())
+            .map(|item| item.string().to_string())
+            .unwrap_or_else(|| EVERYONE.to_string());
+        let shown = imp
+            .all
+            .borrow()
+            .iter()
+            .filter(|line| match (filter.as_str(), line.speaker.as_deref()) {
+                (EVERYONE, _) => true,
+                (UNKNOWN, speaker) => speaker.is_none(),
+                (filter, speaker) => speaker == Some(filter),
+            })
+            .cloned()
+            .collect::<Vec<Line>>();
+
+        for line in shown.iter() {
+            let speaker = line.speaker.as_deref().unwrap_or(UNKNOWN);
+            let label = Label::new(None);
+            label.set_markup(
+                format!(
+                    "<b>{}</b>\x20<small>({})</small>\n{}",
+                    markup_escape_text(speaker),
+                    markup_escape_text(line.chapter.as_str()),
+                    markup_escape_text(line.text.as_str()),
+                )
+                .as_str(),
+            );
+            label.set_xalign(0_f32);
+            label.set_wrap(true);
+            label.set_margin_top(4_i32);
+            label.set_margin_bottom(4_i32);
+            imp.lines.append(&label);
+        }
+
+        imp.status
+            .set_label(format!("{} of {} lines", shown.len(), imp.all.borrow().len()).as_str());
+        imp.shown.replace(shown);
+    }
+
+    /// To show the line in the row at `index` in its document.
+    fn reveal(&self, index: i32) {
+        let Some(window) = self.imp().window.upgrade() else {
+            return;
+        };
+        let Some(line) = self.imp().shown.borrow().get(index as usize).cloned() else {
+            return;
+        };
+        window.imp().tabs.reveal(&line.buffer, line.start, line.end);
+    }
+}
+
+impl Default for TWDialogueWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWDialogueWindow object")
+    }
+}
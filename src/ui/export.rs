@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Export module.
+//!
+//! To ask where a report should be saved and write it there.
+
+use gtk::{glib::*, prelude::*, FileChooserAction, FileChooserNative, Label, ResponseType, Window};
+#[allow(unused_imports)]
+use log::*;
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// To ask for a file to save to, suggesting `name`, and write the text returned by `contents`
+/// there. The outcome is shown on `status`.
+pub fn export_text(
+    parent: &impl IsA<Window>,
+    title: &str,
+    name: &str,
+    status: &Label,
+    contents: impl Fn() -> String + 'static,
+) {
+    let chooser = FileChooserNative::new(
+        Some(title),
+        Some(parent),
+        FileChooserAction::Save,
+        Some("_Export"),
+        Some("_Cancel"),
+    );
+    chooser.set_current_name(name);
+
+    // The chooser has to be kept alive until it answers.
+    let kept = Rc::new(RefCell::new(Some(chooser.clone())));
+    chooser.connect_response(clone!(@weak status => move |chooser, response| {
+        kept.replace(None);
+        if response != ResponseType::Accept { return }
+        let Some(path) = chooser.file().and_then(|file| file.path()) else { return };
+
+        let message = match fs::write(&path, contents()) {
+            Ok(_) => format!("Exported to {}.", path.display()),
+            Err(err) => {
+                error!("{}: {err}", path.display());
+                format!("Cannot export to {}: {err}", path.display())
+            }
+        };
+        status.set_label(message.as_str());
+    }));
+
+    chooser.show();
+}
@@ -0,0 +1,465 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Find bar module.
+//!
+//! To find and replace text in the opened [`TWPage`] documents. The matching itself is done by
+//! [`Search`] from the syntax library. The highlights follow the text as it changes.
+
+use crate::ui::{page::TWBuffer, page::TWPage, tabview::TWTabView};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*,
+    prelude::*, subclass::prelude::*, Button, CheckButton, CompositeTemplate, DropDown, Entry,
+    Label, SearchEntry, TextIter, TextMark,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::cell::RefCell;
+use std::default::Default;
+use std::time::Duration;
+use syntax::search::{Search, SearchMatch, SearchOptions};
+
+/// How long typing has to pause before the matches are highlighted again.
+const HIGHLIGHT_DELAY: Duration = Duration::from_millis(150_u64);
+
+/// Where the [`TWFindBar`] looks for matches. Follows the order of the scope [`DropDown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchScope {
+    /// The whole active document.
+    Document,
+    /// The text that was selected when the scope was chosen.
+    Selection,
+    /// Every opened document.
+    Project,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/findbar.ui")]
+    /// Struct that holds the find and replace controls.
+    pub struct TWFindBar {
+        #[template_child]
+        pub find_entry: TemplateChild<SearchEntry>,
+        #[template_child]
+        pub replace_entry: TemplateChild<Entry>,
+        #[template_child]
+        pub previous: TemplateChild<Button>,
+        #[template_child]
+        pub next: TemplateChild<Button>,
+        #[template_child]
+        pub replace: TemplateChild<Button>,
+        #[template_child]
+        pub replace_all: TemplateChild<Button>,
+        #[template_child]
+        pub close: TemplateChild<Button>,
+        #[template_child]
+        pub case_sensitive: TemplateChild<CheckButton>,
+        #[template_child]
+        pub whole_word: TemplateChild<CheckButton>,
+        #[template_child]
+        pub regex: TemplateChild<CheckButton>,
+        #[template_child]
+        pub scope: TemplateChild<DropDown>,
+        /// To show the number of matches or the pattern error.
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        /// Reference to the tabs to search in.
+        pub tabs: WeakRef<TWTabView>,
+        /// Bounds of the selection when [`SearchScope::Selection`] was chosen.
+        pub selection: RefCell<Option<(TextMark, TextMark)>>,
+        /// The highlighted buffers, with the handlers that highlight them again when they change.
+        pub watched: RefCell<Vec<(TWBuffer, SignalHandlerId)>>,
+        /// The scheduled highlight, pushed back by every edit.
+        pub highlight_source: RefCell<Option<SourceId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWFindBar {
+        const NAME: &'static str = "TWFindBar";
+        type ParentType = gtk::Box;
+        type Type = super::TWFindBar;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWFindBar {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWFindBar {}
+
+    impl BoxImpl for TWFindBar {}
+}
+
+glib::wrapper! {
+    pub struct TWFindBar(ObjectSubclass<imp::TWFindBar>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl TWFindBar {
+    /// To set the [`TWTabView`] whose documents are searched.
+    pub fn set_tabs(&self, tabs: &TWTabView) {
+        self.imp().tabs.set(Some(tabs));
+    }
+
+    /// To show the bar and focus the find entry. The current selection is used as the scope if
+    /// [`SearchScope::Selection`] is chosen.
+    pub fn open(&self) {
+        self.set_visible(true);
+        self.capture_selection();
+        self.imp().find_entry.grab_focus();
+        self.highlight();
+    }
+
+    /// To hide the bar, clear the highlights and give the focus back to the page.
+    pub fn close(&self) {
+        self.set_visible(false);
+        self.watch(&[]);
+        self.buffers().iter().for_each(Self::clear_highlight);
+        if let Some(page) = self.focused_page() {
+            page.grab_focus();
+        }
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.find_entry
+            .connect_search_changed(clone!(@weak self as bar => move |_| bar.highlight()));
+        imp.find_entry
+            .connect_activate(clone!(@weak self as bar => move |_| bar.find(true)));
+        imp.find_entry
+            .connect_stop_search(clone!(@weak self as bar => move |_| bar.close()));
+        imp.next
+            .connect_clicked(clone!(@weak self as bar => move |_| bar.find(true)));
+        imp.previous
+            .connect_clicked(clone!(@weak self as bar => move |_| bar.find(false)));
+        imp.replace
+            .connect_clicked(clone!(@weak self as bar => move |_| bar.replace()));
+        imp.replace_all
+            .connect_clicked(clone!(@weak self as bar => move |_| bar.replace_all()));
+        imp.close
+            .connect_clicked(clone!(@weak self as bar => move |_| bar.close()));
+
+        for toggle in [&imp.case_sensitive, &imp.whole_word, &imp.regex] {
+            toggle.connect_toggled(clone!(@weak self as bar => move |_| bar.highlight()));
+        }
+        imp.scope.connect_selected_notify(clone!(@weak self as bar => move |_| {
+            bar.capture_selection();
+            bar.highlight();
+        }));
+    }
+
+    /// Returns the chosen [`SearchScope`].
+    fn scope(&self) -> SearchScope {
+        match self.imp().scope.selected() {
+            1_u32 => SearchScope::Selection,
+            2_u32 => SearchScope::Project,
+            _ => SearchScope::Document,
+        }
+    }
+
+    /// Compiles the pattern in the find entry. Shows the error in the status label if it fails.
+    fn search(&self) -> Option<Search> {
+        let imp = self.imp();
+        let options = SearchOptions {
+            case_sensitive: imp.case_sensitive.is_active(),
+            whole_word: imp.whole_word.is_active(),
+            regex: imp.regex.is_active(),
+        };
+
+        match Search::new(imp.find_entry.text().as_str(), options) {
+            Ok(search) => {
+                imp.find_entry.remove_css_class("error");
+                Some(search)
+            }
+            Err(err) => {
+                if !imp.find_entry.text().is_empty() {
+                    imp.find_entry.add_css_class("error");
+                }
+                imp.status.set_label(err.to_string().as_str());
+                None
+            }
+        }
+    }
+
+    /// Returns the [`TWPage`] that has the focus in the active tab.
+    fn focused_page(&self) -> Option<TWPage> {
+        self.imp()
+            .tabs
+            .upgrade()
+            .and_then(|tabs| tabs.current_split())
+            .and_then(|split| split.focused())
+    }
+
+    /// Returns the buffers that the chosen [`SearchScope`] covers.
+    fn buffers(&self) -> Vec<TWBuffer> {
+        let Some(tabs) = self.imp().tabs.upgrade() else { return Vec::new() };
+        match self.scope() {
+            SearchScope::Project => tabs.pages().iter().map(|page| page.tw_buffer()).collect(),
+            _ => tabs
+                .current_page()
+                .map(|page| vec![page.tw_buffer()])
+                .unwrap_or_default(),
+        }
+    }
+
+    /// To remember the current selection as the bounds of [`SearchScope::Selection`].
+    fn capture_selection(&self) {
+        if self.scope() != SearchScope::Selection { return }
+        let Some(page) = self.focused_page() else { return };
+        let buffer = page.buffer();
+        let Some((start, end)) = buffer.selection_bounds() else { return };
+
+        let marks = (
+            buffer.create_mark(None, &start, true),
+            buffer.create_mark(None, &end, false),
+        );
+        if let Some((start, end)) = self.imp().selection.replace(Some(marks)) {
+            for mark in [start, end] {
+                if let Some(buffer) = mark.buffer() {
+                    buffer.delete_mark(&mark);
+                }
+            }
+        }
+    }
+
+    /// Returns the iterators that bound the searched text in `buffer`.
+    fn bounds(&self, buffer: &TWBuffer) -> (TextIter, TextIter) {
+        if self.scope() == SearchScope::Selection {
+            if let Some((start, end)) = self.imp().selection.borrow().as_ref() {
+                if start.buffer().as_ref() == Some(buffer.upcast_ref()) {
+                    return (buffer.iter_at_mark(start), buffer.iter_at_mark(end));
+                }
+            }
+        }
+        buffer.bounds()
+    }
+
+    /// Returns the searched text of `buffer` with its matches and the offset of the text.
+    fn matches(&self, search: &Search, buffer: &TWBuffer) -> (String, Vec<SearchMatch>, i32) {
+        let (start, end) = self.bounds(buffer);
+        let text = buffer.slice(&start, &end, true).to_string();
+        let matches = search.find_all(text.as_str());
+
+        (text, matches, start.offset())
+    }
+
+    /// To highlight every match in the scope with the search [`gtk::TextTag`].
+    fn highlight(&self) {
+        let buffers = self.buffers();
+        buffers.iter().for_each(Self::clear_highlight);
+        if !self.is_visible() { return }
+        self.watch(&buffers);
+        let Some(search) = self.search() else { return };
+
+        let mut total = 0_usize;
+        for buffer in buffers.iter() {
+            let tag = buffer.search_tag();
+            let (_, matches, offset) = self.matches(&search, buffer);
+            for found in matches.iter() {
+                let (start, end) = Self::iters(buffer, found, offset);
+                buffer.apply_tag(&tag, &start, &end);
+            }
+            total += matches.len();
+        }
+        self.imp().status.set_label(format!("{total} matches").as_str());
+    }
+
+    /// To highlight the matches again once typing pauses in one of the highlighted buffers.
+    fn queue_highlight(&self) {
+        if let Some(source) = self.imp().highlight_source.take() {
+            source.remove();
+        }
+        let source = glib::timeout_add_local_once(
+            HIGHLIGHT_DELAY,
+            clone!(@weak self as bar => move || {
+                bar.imp().highlight_source.replace(None);
+                bar.highlight();
+            }),
+        );
+        self.imp().highlight_source.replace(Some(source));
+    }
+
+    /// To follow the changes of `buffers` instead of the buffers followed so far.
+    fn watch(&self, buffers: &[TWBuffer]) {
+        let mut watched = self.imp().watched.borrow_mut();
+        if watched.iter().map(|(buffer, _)| buffer).eq(buffers.iter()) { return }
+
+        for (buffer, handler) in watched.drain(..) {
+            buffer.disconnect(handler);
+        }
+        if buffers.is_empty() {
+            if let Some(source) = self.imp().highlight_source.take() {
+                source.remove();
+            }
+        }
+        for buffer in buffers {
+            let handler = buffer.connect_changed(clone!(@weak self as bar => move |_| {
+                bar.queue_highlight();
+            }));
+            watched.push((buffer.clone(), handler));
+        }
+    }
+
+    /// To remove the search highlights from the whole `buffer`.
+    fn clear_highlight(buffer: &TWBuffer) {
+        let (start, end) = buffer.bounds();
+        buffer.remove_tag(&buffer.search_tag(), &start, &end);
+    }
+
+    /// To select the next match after the cursor, or the previous one before it when `forward`
+    /// is false. Goes on in the next documents of the scope, and wraps around it.
+    fn find(&self, forward: bool) {
+        let Some(search) = self.search() else { return };
+        let Some(page) = self.focused_page() else { return };
+        let buffer = page.tw_buffer();
+
+        let (_, matches, offset) = self.matches(&search, &buffer);
+        let (start, end) = buffer
+            .selection_bounds()
+            .unwrap_or_else(|| {
+                let cursor = buffer.iter_at_mark(&buffer.get_insert());
+                (cursor.clone(), cursor)
+            });
+
+        let found = match forward {
+            true => matches
+                .iter()
+                .find(|found| found.chars.start as i32 + offset >= end.offset()),
+            false => matches
+                .iter()
+                .rev()
+                .find(|found| (found.chars.end as i32 + offset) <= start.offset()),
+        };
+        if let Some(found) = found {
+            Self::select(&page, found, offset);
+            return;
+        }
+
+        // The scope is searched in tab order from the next document on, ending with the
+        // beginning of this one.
+        let buffers = self.buffers();
+        let count = buffers.len();
+        let index = buffers
+            .iter()
+            .position(|other| *other == buffer)
+            .unwrap_or_default();
+        for step in 1_usize..=count {
+            let other = match forward {
+                true => &buffers[(index + step) % count],
+                false => &buffers[(index + count - step) % count],
+            };
+            let (_, matches, offset) = self.matches(&search, other);
+            let found = match forward {
+                true => matches.first(),
+                false => matches.last(),
+            };
+            let Some(found) = found else { continue };
+
+            if *other == buffer {
+                Self::select(&page, found, offset);
+            } else if let Some(tabs) = self.imp().tabs.upgrade() {
+                let start = offset + found.chars.start as i32;
+                tabs.reveal(other, start, offset + found.chars.end as i32);
+            }
+            return;
+        }
+    }
+
+    /// To select `found`, a match in the text that starts at `offset`, in `page`.
+    fn select(page: &TWPage, found: &SearchMatch, offset: i32) {
+        let buffer = page.tw_buffer();
+        let (start, end) = Self::iters(&buffer, found, offset);
+        buffer.select_range(&start, &end);
+        page.scroll_to_mark(&buffer.get_insert(), 0_f64, true, 0.5_f64, 0.5_f64);
+    }
+
+    /// To replace the selected match and move to the next one.
+    fn replace(&self) {
+        let Some(search) = self.search() else { return };
+        let Some(page) = self.focused_page() else { return };
+        let buffer = page.tw_buffer();
+        let Some((start, end)) = buffer.selection_bounds() else {
+            self.find(true);
+            return
+        };
+
+        // Only a selection that is exactly a match is replaced.
+        let (text, matches, offset) = self.matches(&search, &buffer);
+        let selected = matches.iter().find(|found| {
+            found.chars.start as i32 + offset == start.offset()
+                && found.chars.end as i32 + offset == end.offset()
+        });
+        if let Some(found) = selected {
+            let replacement = self.imp().replace_entry.text();
+            let expanded = search.expand(text.as_str(), found, replacement.as_str());
+            buffer.untyped_action(|| {
+                self.replace_match(&buffer, found, offset, expanded.as_str());
+            });
+        }
+        self.highlight();
+        self.find(true);
+    }
+
+    /// To replace every match in the scope. Each buffer is changed in a single user action so
+    /// that one undo reverts the whole replacement.
+    fn replace_all(&self) {
+        let Some(search) = self.search() else { return };
+        let replacement = self.imp().replace_entry.text();
+
+        let mut total = 0_usize;
+        for buffer in self.buffers().iter() {
+            let (text, matches, offset) = self.matches(&search, buffer);
+            if matches.is_empty() { continue }
+
+            buffer.untyped_action(|| {
+                // Replace from the end so the earlier offsets stay valid.
+                for found in matches.iter().rev() {
+                    let expanded = search.expand(text.as_str(), found, replacement.as_str());
+                    self.replace_match(buffer, found, offset, expanded.as_str());
+                }
+            });
+            total += matches.len();
+        }
+
+        self.highlight();
+        self.imp().status.set_label(format!("{total} replaced").as_str());
+    }
+
+    fn replace_match(&self, buffer: &TWBuffer, found: &SearchMatch, offset: i32, text: &str) {
+        let (mut start, mut end) = Self::iters(buffer, found, offset);
+        buffer.delete(&mut start, &mut end);
+        buffer.insert(&mut start, text);
+    }
+
+    /// Converts a [`SearchMatch`] in the text that starts at `offset` into buffer iterators.
+    #[inline]
+    fn iters(buffer: &TWBuffer, found: &SearchMatch, offset: i32) -> (TextIter, TextIter) {
+        (
+            buffer.iter_at_offset(offset + found.chars.start as i32),
+            buffer.iter_at_offset(offset + found.chars.end as i32),
+        )
+    }
+}
+
+impl Default for TWFindBar {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWFindBar object")
+    }
+}
@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Goals module.
+//!
+//! Creates and returns a [`TWGoalsWindow`] to set the word targets and deadline of the project.
+
+use crate::ui::window::TWApplicationWindow;
+use format::goals::{parse_date, Goals};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, Entry, Label, SpinButton, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::default::Default;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/goalswindow.ui")]
+    /// The goals window struct.
+    pub struct TWGoalsWindow {
+        #[template_child]
+        pub session: TemplateChild<SpinButton>,
+        #[template_child]
+        pub daily: TemplateChild<SpinButton>,
+        #[template_child]
+        pub project: TemplateChild<SpinButton>,
+        #[template_child]
+        pub deadline: TemplateChild<Entry>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        #[template_child]
+        pub save: TemplateChild<Button>,
+        /// The window whose project goals are edited.
+        pub window: WeakRef<TWApplicationWindow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWGoalsWindow {
+        const NAME: &'static str = "TWGoalsWindow";
+        type ParentType = Window;
+        type Type = super::TWGoalsWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWGoalsWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.save
+                .connect_clicked(clone!(@weak self as goals => move |_| goals.obj().save()));
+        }
+    }
+
+    impl WidgetImpl for TWGoalsWindow {}
+
+    impl WindowImpl for TWGoalsWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWGoalsWindow(ObjectSubclass<imp::TWGoalsWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWGoalsWindow {
+    /// Returns a new instance of [`TWGoalsWindow`] filled with the goals of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let goals = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        goals.imp().window.set(Some(window));
+        goals.load(&window.imp().project.borrow().goals);
+
+        goals
+    }
+
+    fn load(&self, goals: &Goals) {
+        let imp = self.imp();
+        imp.session
+            .set_value(goals.session.unwrap_or_default() as f64);
+        imp.daily.set_value(goals.daily.unwrap_or_default() as f64);
+        imp.project
+            .set_value(goals.project.unwrap_or_default() as f64);
+        if let Some(deadline) = goals.deadline {
+            imp.deadline.set_text(deadline.to_string().as_str());
+        }
+    }
+
+    /// To store the goals in the project of the window and close. The window stays open if the
+    /// deadline is not a valid date.
+    fn save(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else {
+            return;
+        };
+
+        let deadline = imp.deadline.text();
+        let deadline = match deadline.trim().is_empty() {
+            true => None,
+            false => match parse_date(deadline.as_str()) {
+                Some(date) => Some(date),
+                None => {
+                    imp.status
+                        .set_label("The deadline has to be a date such as 2023-12-31.");
+                    return;
+                }
+            },
+        };
+
+        window.imp().project.borrow_mut().goals = Goals {
+            session: Self::target(&imp.session),
+            daily: Self::target(&imp.daily),
+            project: Self::target(&imp.project),
+            deadline,
+        };
+        window.save_project();
+        window.refresh_goals();
+        self.close();
+    }
+
+    /// Returns the target in `spin`, or [`None`] for `0`.
+    fn target(spin: &SpinButton) -> Option<usize> {
+        Some(spin.value_as_int() as usize).filter(|target| *target > 0_usize)
+    }
+}
+
+impl Default for TWGoalsWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWGoalsWindow object")
+    }
+}
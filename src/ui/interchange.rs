@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Interchange module.
+//!
+//! Creates and returns a [`TWInterchangeWindow`] to export the lore as JSON or CSV and to merge
+//! such files into it, listing what the merge added, updated and left alone.
+
+use crate::ui::{export::export_text, lore::LORE_CHANGED, window::TWApplicationWindow};
+use format::interchange::{lore_from_csv, lore_from_json, lore_to_csv, lore_to_json, LORE_SCHEMA};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, DropDown, FileChooserAction,
+    FileChooserNative, FileFilter, Label, ListBox, ResponseType, StringList, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::{EntityId, EntityKind, Incoming, MergeReport};
+use std::cell::RefCell;
+use std::default::Default;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+/// Name suggested for the exported lore.
+const JSON_NAME: &str = "lore.json";
+/// Name suggested for the schema of the exported lore.
+const SCHEMA_NAME: &str = "lore.schema.json";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/interchange.ui")]
+    /// The interchange window struct.
+    pub struct TWInterchangeWindow {
+        #[template_child]
+        pub export_json: TemplateChild<Button>,
+        #[template_child]
+        pub import_json: TemplateChild<Button>,
+        #[template_child]
+        pub schema: TemplateChild<Button>,
+        /// The kind of entity CSV files hold.
+        #[template_child]
+        pub kind: TemplateChild<DropDown>,
+        #[template_child]
+        pub kinds: TemplateChild<StringList>,
+        #[template_child]
+        pub export_csv: TemplateChild<Button>,
+        #[template_child]
+        pub import_csv: TemplateChild<Button>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        /// A row for every change and conflict of the last import.
+        #[template_child]
+        pub report: TemplateChild<ListBox>,
+        /// The window whose lore is imported and exported.
+        pub window: WeakRef<TWApplicationWindow>,
+        /// The entity of every row of the report.
+        pub targets: RefCell<Vec<Option<EntityId>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWInterchangeWindow {
+        const NAME: &'static str = "TWInterchangeWindow";
+        type ParentType = Window;
+        type Type = super::TWInterchangeWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWInterchangeWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.kinds
+                .splice(0_u32, 0_u32, &EntityKind::ALL.map(|kind| kind.as_str()));
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWInterchangeWindow {}
+
+    impl WindowImpl for TWInterchangeWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWInterchangeWindow(ObjectSubclass<imp::TWInterchangeWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWInterchangeWindow {
+    /// Returns a new instance of [`TWInterchangeWindow`] for the lore of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let interchange = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        interchange.imp().window.set(Some(window));
+
+        interchange
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.export_json
+            .connect_clicked(clone!(@weak self as interchange => move |_| {
+                interchange.export_json();
+            }));
+        imp.import_json
+            .connect_clicked(clone!(@weak self as interchange => move |_| {
+                interchange.import(false);
+            }));
+        imp.schema
+            .connect_clicked(clone!(@weak self as interchange => move |_| {
+                interchange.export_schema();
+            }));
+        imp.export_csv
+            .connect_clicked(clone!(@weak self as interchange => move |_| {
+                interchange.export_csv();
+            }));
+        imp.import_csv
+            .connect_clicked(clone!(@weak self as interchange => move |_| {
+                interchange.import(true);
+            }));
+        imp.report
+            .connect_row_activated(clone!(@weak self as interchange => move |_, row| {
+                let entity = interchange
+                    .imp()
+                    .targets
+                    .borrow()
+                    .get(row.index() as usize)
+                    .copied()
+                    .flatten();
+                let Some(window) = interchange.imp().window.upgrade() else { return };
+                if let Some(entity) = entity {
+                    window.show_entity(entity);
+                }
+            }));
+    }
+
+    /// Returns the kind of entity chosen for CSV files.
+    fn kind(&self) -> EntityKind {
+        let index = self.imp().kind.selected() as usize;
+        EntityKind::ALL
+            .get(index)
+            .copied()
+            .unwrap_or(EntityKind::Character)
+    }
+
+    /// To ask where to export the whole lore and write it there as JSON.
+    fn export_json(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let json = match lore_to_json(&window.imp().lore.borrow()) {
+            Ok(json) => json,
+            Err(err) => {
+                imp.status.set_label(err.to_string().as_str());
+                return;
+            }
+        };
+        export_text(self, "Export Lore", JSON_NAME, &imp.status, move || {
+            json.clone()
+        });
+    }
+
+    /// To ask where to export the entities of the chosen kind and write them there as CSV.
+    fn export_csv(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let kind = self.kind();
+        let csv = match lore_to_csv(&window.imp().lore.borrow(), kind) {
+            Ok(csv) => csv,
+            Err(err) => {
+                imp.status.set_label(err.to_string().as_str());
+                return;
+            }
+        };
+        let name = format!("{}.csv", kind.as_str().to_lowercase());
+        export_text(self, "Export Lore", name.as_str(), &imp.status, move || {
+            csv.clone()
+        });
+    }
+
+    /// To ask where to save the JSON Schema of the exported lore.
+    fn export_schema(&self) {
+        export_text(
+            self,
+            "Save Lore Schema",
+            SCHEMA_NAME,
+            &self.imp().status,
+            || LORE_SCHEMA.to_string(),
+        );
+    }
+
+    /// To ask for a JSON file, or a CSV file of the chosen kind if `csv`, and merge it into the
+    /// lore.
+    fn import(&self, csv: bool) {
+        let chooser = FileChooserNative::new(
+            Some("Import Lore"),
+            Some(self),
+            FileChooserAction::Open,
+            Some("_Import"),
+            Some("_Cancel"),
+        );
+        let filter = FileFilter::new();
+        match csv {
+            true => {
+                filter.set_name(Some("CSV"));
+                filter.add_suffix("csv");
+            }
+            false => {
+                filter.set_name(Some("JSON"));
+                filter.add_suffix("json");
+            }
+        }
+        chooser.add_filter(&filter);
+
+        // The chooser has to be kept alive until it answers.
+        let kept = Rc::new(RefCell::new(Some(chooser.clone())));
+        let kind = self.kind();
+        chooser.connect_response(
+            clone!(@weak self as interchange => move |chooser, response| {
+                kept.replace(None);
+                if response != ResponseType::Accept { return }
+                let Some(path) = chooser.file().and_then(|file| file.path()) else { return };
+
+                interchange.import_file(&path, csv, kind);
+            }),
+        );
+
+        chooser.show();
+    }
+
+    /// To read the entities of the file at `path`, as CSV of `kind` if `csv`, and merge them.
+    fn import_file(&self, path: &Path, csv: bool, kind: EntityKind) {
+        let incoming = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                let incoming = match csv {
+                    true => lore_from_csv(text.as_str(), kind),
+                    false => lore_from_json(text.as_str()),
+                };
+                incoming.map_err(|err| err.to_string())
+            });
+        match incoming {
+            Ok(incoming) => self.merge(path, incoming),
+            Err(err) => {
+                error!("{}: {err}", path.display());
+                let message = format!("Cannot import {}: {err}", path.display());
+                self.imp().status.set_label(message.as_str());
+            }
+        }
+    }
+
+    /// To merge the entities read from `path` into the lore and list what changed.
+    fn merge(&self, path: &Path, incoming: Vec<Incoming>) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let report = window.imp().lore.borrow_mut().merge(incoming);
+        if !report.added.is_empty() || !report.updated.is_empty() {
+            window.save_project();
+            window
+                .imp()
+                .lore_sidebar
+                .emit_by_name::<()>(LORE_CHANGED, &[]);
+        }
+
+        let message = format!(
+            "Imported {}: {} added, {} updated, {} conflicts.",
+            path.display(),
+            report.added.len(),
+            report.updated.len(),
+            report.conflicts.len(),
+        );
+        imp.status.set_label(message.as_str());
+        self.show_report(&report);
+    }
+
+    /// To list the entities `report` added and updated, then its conflicts and the links it could
+    /// not follow.
+    fn show_report(&self, report: &MergeReport) {
+        let imp = self.imp();
+        while let Some(row) = imp.report.row_at_index(0_i32) {
+            imp.report.remove(&row);
+        }
+        let Some(window) = imp.window.upgrade() else { return };
+        let lore = window.imp().lore.borrow();
+        let name = |id: &EntityId| {
+            lore.get(*id)
+                .map(|entity| entity.name.clone())
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        let mut rows = Vec::new();
+        rows.extend(
+            report
+                .added
+                .iter()
+                .map(|id| (format!("Added {}.", name(id)), Some(*id))),
+        );
+        rows.extend(
+            report
+                .updated
+                .iter()
+                .map(|id| (format!("Updated {}.", name(id)), Some(*id))),
+        );
+        rows.extend(
+            report
+                .conflicts
+                .iter()
+                .map(|conflict| (conflict.describe(&lore), Some(conflict.entity))),
+        );
+        rows.extend(report.unresolved.iter().map(|(id, missing)| {
+            let text = format!(
+                "{}: no entity of the file has the id {}, the link was skipped.",
+                name(id),
+                missing.value(),
+            );
+            (text, Some(*id))
+        }));
+        if rows.is_empty() {
+            rows.push((
+                "The lore already holds everything in the file.".to_string(),
+                None,
+            ));
+        }
+
+        let mut targets = Vec::new();
+        for (text, target) in rows {
+            let label = Label::new(Some(text.as_str()));
+            label.set_xalign(0_f32);
+            label.set_wrap(true);
+            imp.report.append(&label);
+            targets.push(target);
+        }
+        imp.targets.replace(targets);
+    }
+}
+
+impl Default for TWInterchangeWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWInterchangeWindow object")
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Lint module.
+//!
+//! Creates and returns a [`TWLintWindow`] to turn the rules of the prose linter on or off and to
+//! choose the severity of what they find.
+
+use crate::ui::window::TWApplicationWindow;
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, CompositeTemplate, DropDown, Label, ListBox, Orientation, Switch, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::default::Default;
+use syntax::lint::{Rule, RuleConfig, Severity};
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/lintwindow.ui")]
+    /// The lint settings window struct.
+    pub struct TWLintWindow {
+        /// A row with a [`Switch`] and a severity [`DropDown`] for every rule.
+        #[template_child]
+        pub rules: TemplateChild<ListBox>,
+        /// The window whose project lint settings are edited.
+        pub window: WeakRef<TWApplicationWindow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWLintWindow {
+        const NAME: &'static str = "TWLintWindow";
+        type ParentType = Window;
+        type Type = super::TWLintWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWLintWindow {}
+
+    impl WidgetImpl for TWLintWindow {}
+
+    impl WindowImpl for TWLintWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWLintWindow(ObjectSubclass<imp::TWLintWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWLintWindow {
+    /// Returns a new instance of [`TWLintWindow`] filled with the lint settings of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let lint = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        lint.imp().window.set(Some(window));
+
+        for rule in window.imp().linter.rules() {
+            let config = window.imp().project.borrow().lint.get(rule);
+            lint.imp().rules.append(&lint.rule_row(rule, config));
+        }
+
+        lint
+    }
+
+    /// Returns a row that changes the [`RuleConfig`] of `rule`, starting from `config`.
+    fn rule_row(&self, rule: &dyn Rule, config: RuleConfig) -> gtk::Box {
+        let label = Label::new(Some(rule.name()));
+        label.set_hexpand(true);
+        label.set_xalign(0_f32);
+
+        let severities = Severity::ALL.map(|severity| severity.as_str());
+        let severity = DropDown::from_strings(&severities);
+        let selected = Severity::ALL
+            .iter()
+            .position(|severity| *severity == config.severity);
+        severity.set_selected(selected.unwrap_or_default() as u32);
+        severity.set_sensitive(config.enabled);
+
+        let enabled = Switch::new();
+        enabled.set_active(config.enabled);
+        enabled.set_valign(gtk::Align::Center);
+
+        let id = rule.id();
+        enabled.connect_active_notify(
+            clone!(@weak self as lint, @weak severity => move |enabled| {
+                severity.set_sensitive(enabled.is_active());
+                lint.update(id, enabled.is_active(), severity.selected());
+            }),
+        );
+        severity.connect_selected_notify(
+            clone!(@weak self as lint, @weak enabled => move |severity| {
+                lint.update(id, enabled.is_active(), severity.selected());
+            }),
+        );
+
+        let row = gtk::Box::new(Orientation::Horizontal, 10_i32);
+        row.set_margin_start(6_i32);
+        row.set_margin_end(6_i32);
+        row.set_margin_top(6_i32);
+        row.set_margin_bottom(6_i32);
+        row.append(&label);
+        row.append(&severity);
+        row.append(&enabled);
+        row
+    }
+
+    /// To store the settings of the rule with `id` in the project and lint the documents again.
+    fn update(&self, id: &str, enabled: bool, severity: u32) {
+        let Some(window) = self.imp().window.upgrade() else {
+            return;
+        };
+        let Some(severity) = Severity::ALL.get(severity as usize).copied() else {
+            return;
+        };
+
+        let Some(rule) = window.imp().linter.rules().find(|rule| rule.id() == id) else {
+            return;
+        };
+        window
+            .imp()
+            .project
+            .borrow_mut()
+            .lint
+            .set(rule, RuleConfig { enabled, severity });
+        window.save_project();
+        window.queue_lint();
+    }
+}
+
+impl Default for TWLintWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWLintWindow object")
+    }
+}
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Lore module.
+//!
+//! The [`TWLoreSidebar`] that lists the lore entities of the window grouped by kind. Choosing an
+//! entity, or clicking a mention of it in a [`TWPage`], opens its [`TWEntityCard`].
+//! [`TWPage`] : [`crate::ui::page::TWPage`]
+
+mod card;
+
+use crate::ui::page::SharedLore;
+use card::{TWEntityCard, CARD_CLOSED};
+pub use card::LORE_CHANGED;
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, DropDown, Label, ListBox, ListBoxRow,
+    SearchEntry, Stack, StringList,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::{EntityId, EntityKind};
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::default::Default;
+
+/// Name of the [`Stack`] page with the list of entities.
+const LIST_PAGE: &str = "list";
+/// Name of the [`Stack`] page with the [`TWEntityCard`].
+const CARD_PAGE: &str = "card";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/loresidebar.ui")]
+    /// The lore sidebar struct.
+    pub struct TWLoreSidebar {
+        /// Switches between the list and the card.
+        #[template_child]
+        pub stack: TemplateChild<Stack>,
+        #[template_child]
+        pub search: TemplateChild<SearchEntry>,
+        /// A heading for every kind followed by its entities.
+        #[template_child]
+        pub entities: TemplateChild<ListBox>,
+        /// The kind of the entities added.
+        #[template_child]
+        pub kind: TemplateChild<DropDown>,
+        #[template_child]
+        pub kinds: TemplateChild<StringList>,
+        #[template_child]
+        pub add: TemplateChild<Button>,
+        #[template_child]
+        pub card: TemplateChild<TWEntityCard>,
+        /// The registry that is listed.
+        pub lore: RefCell<Option<SharedLore>>,
+        /// The entity of every row, [`None`] for headings.
+        pub rows: RefCell<Vec<Option<EntityId>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWLoreSidebar {
+        const NAME: &'static str = "TWLoreSidebar";
+        type ParentType = gtk::Box;
+        type Type = super::TWLoreSidebar;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWLoreSidebar {
+        /// [`LORE_CHANGED`] is emitted again for the [`TWEntityCard`] and after an entity is
+        /// added.
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> =
+                Lazy::new(|| vec![Signal::builder(LORE_CHANGED).build()]);
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            EntityKind::ALL
+                .iter()
+                .for_each(|kind| self.kinds.append(kind.as_str()));
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWLoreSidebar {}
+
+    impl BoxImpl for TWLoreSidebar {}
+}
+
+glib::wrapper! {
+    pub struct TWLoreSidebar(ObjectSubclass<imp::TWLoreSidebar>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl TWLoreSidebar {
+    /// To list the entities of `lore`.
+    pub fn set_lore(&self, lore: &SharedLore) {
+        self.imp().lore.replace(Some(lore.clone()));
+        self.imp().card.set_lore(lore);
+        self.refresh();
+    }
+
+    /// To open the card of the entity with `id`.
+    pub fn show_entity(&self, id: EntityId) {
+        if self.imp().card.show_entity(id) {
+            self.imp().stack.set_visible_child_name(CARD_PAGE);
+        }
+    }
+
+    /// To list the entities matching the search again, such as after the lore changed. An open
+    /// card is closed if its entity no longer exists.
+    pub fn refresh(&self) {
+        let imp = self.imp();
+        while let Some(row) = imp.entities.row_at_index(0_i32) {
+            imp.entities.remove(&row);
+        }
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let lore = lore.borrow();
+
+        let mut rows = Vec::new();
+        for kind in EntityKind::ALL {
+            let mut entities = lore
+                .search(imp.search.text().as_str())
+                .filter(|entity| entity.kind == kind)
+                .collect::<Vec<_>>();
+            if entities.is_empty() {
+                continue;
+            }
+            entities.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+            imp.entities
+                .append(&Self::heading(format!("{kind} ({})", entities.len()).as_str()));
+            rows.push(None);
+            for entity in entities {
+                let label = Label::new(Some(entity.name.as_str()));
+                label.set_xalign(0_f32);
+                label.set_margin_start(12_i32);
+                if !entity.aliases.is_empty() {
+                    label.set_tooltip_text(Some(entity.aliases.join(", ").as_str()));
+                }
+                imp.entities.append(&label);
+                rows.push(Some(entity.id()));
+            }
+        }
+        if rows.is_empty() {
+            let empty = Self::heading("No lore entities");
+            imp.entities.append(&empty);
+            rows.push(None);
+        }
+        imp.rows.replace(rows);
+
+        let open = imp.card.entity();
+        if open.is_some_and(|id| lore.get(id).is_none()) {
+            imp.stack.set_visible_child_name(LIST_PAGE);
+        }
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.search
+            .connect_search_changed(clone!(@weak self as sidebar => move |_| sidebar.refresh()));
+        imp.search.connect_activate(clone!(@weak self as sidebar => move |_| {
+            let first = sidebar.imp().rows.borrow().iter().flatten().next().copied();
+            if let Some(id) = first {
+                sidebar.show_entity(id);
+            }
+        }));
+        imp.entities
+            .connect_row_activated(clone!(@weak self as sidebar => move |_, row| {
+                let id = sidebar.imp().rows.borrow().get(row.index() as usize).copied().flatten();
+                if let Some(id) = id {
+                    sidebar.show_entity(id);
+                }
+            }));
+        imp.add
+            .connect_clicked(clone!(@weak self as sidebar => move |_| sidebar.add_entity()));
+
+        imp.card.connect_local(
+            LORE_CHANGED,
+            false,
+            clone!(@weak self as sidebar => @default-return None, move |_| {
+                sidebar.refresh();
+                sidebar.emit_by_name::<()>(LORE_CHANGED, &[]);
+                None
+            }),
+        );
+        imp.card.connect_local(
+            CARD_CLOSED,
+            false,
+            clone!(@weak self as sidebar => @default-return None, move |_| {
+                sidebar.imp().stack.set_visible_child_name(LIST_PAGE);
+                None
+            }),
+        );
+    }
+
+    /// To add an entity of the chosen kind named after the search, or after its kind if nothing
+    /// is searched, and open its card.
+    fn add_entity(&self) {
+        let imp = self.imp();
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let kind = EntityKind::ALL
+            .get(imp.kind.selected() as usize)
+            .copied()
+            .unwrap_or(EntityKind::Character);
+        let name = match imp.search.text().trim() {
+            "" => format!("New {kind}"),
+            name => name.to_string(),
+        };
+
+        let added = lore.borrow_mut().add(kind, name.as_str());
+        match added {
+            Ok(id) => {
+                imp.search.set_text("");
+                self.refresh();
+                self.emit_by_name::<()>(LORE_CHANGED, &[]);
+                self.show_entity(id);
+                imp.card.imp().name.grab_focus();
+            }
+            Err(err) => error!("{err}"),
+        }
+    }
+
+    /// Returns a row that cannot be chosen with `text` in bold.
+    fn heading(text: &str) -> ListBoxRow {
+        let label = Label::new(None);
+        label.set_markup(format!("<b>{}</b>", markup_escape_text(text)).as_str());
+        label.set_xalign(0_f32);
+        label.set_margin_top(6_i32);
+
+        let row = ListBoxRow::new();
+        row.set_child(Some(&label));
+        row.set_activatable(false);
+        row.set_selectable(false);
+        row
+    }
+}
+
+impl Default for TWLoreSidebar {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWLoreSidebar object")
+    }
+}
@@ -0,0 +1,695 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Card module.
+//!
+//! The [`TWEntityCard`] that edits a single lore entity. Changes are made to a copy of the entity
+//! and only reach the registry when saved.
+
+use crate::ui::{page::SharedLore, window::TWApplicationWindow};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, DropDown, Entry, FileChooserAction,
+    FileChooserNative, FileFilter, Label, ListBox, Orientation, Picture, ResponseType, StringList,
+    TextView,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::{
+    parse_time, AttributeChange, Calendar, Entity, EntityId, EntityKind, LoreError, Relation,
+    RelationKind, Route, StoryTime, TravelMode,
+};
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::default::Default;
+use std::rc::Rc;
+
+/// Signal emitted after the entity is saved or deleted.
+pub const LORE_CHANGED: &str = "lore-changed";
+/// Signal emitted when the card should be put away.
+pub const CARD_CLOSED: &str = "card-closed";
+/// Separator of the aliases in their entry.
+const ALIAS_SEPARATOR: char = ',';
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/entitycard.ui")]
+    /// The entity card struct.
+    pub struct TWEntityCard {
+        #[template_child]
+        pub back: TemplateChild<Button>,
+        /// Shows the kind of the entity.
+        #[template_child]
+        pub kind: TemplateChild<Label>,
+        #[template_child]
+        pub delete: TemplateChild<Button>,
+        #[template_child]
+        pub image: TemplateChild<Picture>,
+        #[template_child]
+        pub choose_image: TemplateChild<Button>,
+        #[template_child]
+        pub clear_image: TemplateChild<Button>,
+        #[template_child]
+        pub name: TemplateChild<Entry>,
+        #[template_child]
+        pub aliases: TemplateChild<Entry>,
+        #[template_child]
+        pub description: TemplateChild<TextView>,
+        /// A row with a remove button for every attribute, then every later value of one.
+        #[template_child]
+        pub attributes: TemplateChild<ListBox>,
+        #[template_child]
+        pub attribute_key: TemplateChild<Entry>,
+        #[template_child]
+        pub attribute_value: TemplateChild<Entry>,
+        /// When the value is taken, for an attribute that changes.
+        #[template_child]
+        pub attribute_since: TemplateChild<Entry>,
+        #[template_child]
+        pub add_attribute: TemplateChild<Button>,
+        /// A row with a remove button for every relation of the entity, followed by the relations
+        /// of other entities to it.
+        #[template_child]
+        pub related: TemplateChild<ListBox>,
+        /// To choose the kind of the relation to add.
+        #[template_child]
+        pub relation_kind: TemplateChild<DropDown>,
+        #[template_child]
+        pub relation_kinds: TemplateChild<StringList>,
+        /// To choose an entity to relate.
+        #[template_child]
+        pub relation: TemplateChild<DropDown>,
+        /// Names of the entities in the [`DropDown`].
+        #[template_child]
+        pub relations: TemplateChild<StringList>,
+        #[template_child]
+        pub since: TemplateChild<Entry>,
+        #[template_child]
+        pub until: TemplateChild<Entry>,
+        #[template_child]
+        pub add_related: TemplateChild<Button>,
+        /// Only shown for locations.
+        #[template_child]
+        pub routes_section: TemplateChild<gtk::Box>,
+        /// A row with a remove button for every route of the location, followed by the routes of
+        /// other locations to it.
+        #[template_child]
+        pub routes: TemplateChild<ListBox>,
+        /// To choose the location a route leads to.
+        #[template_child]
+        pub route: TemplateChild<DropDown>,
+        #[template_child]
+        pub route_targets: TemplateChild<StringList>,
+        #[template_child]
+        pub distance: TemplateChild<Entry>,
+        /// To choose how the route is travelled, any way first.
+        #[template_child]
+        pub route_mode: TemplateChild<DropDown>,
+        #[template_child]
+        pub route_modes: TemplateChild<StringList>,
+        #[template_child]
+        pub add_route: TemplateChild<Button>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        #[template_child]
+        pub save: TemplateChild<Button>,
+        /// The registry the entity belongs to.
+        pub lore: RefCell<Option<SharedLore>>,
+        /// The copy of the entity being edited.
+        pub draft: RefCell<Option<Entity>>,
+        /// Ids of the entities in the same order as `relations`.
+        pub relation_ids: RefCell<Vec<EntityId>>,
+        /// Ids of the locations in the same order as `route_targets`.
+        pub route_ids: RefCell<Vec<EntityId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWEntityCard {
+        const NAME: &'static str = "TWEntityCard";
+        type ParentType = gtk::Box;
+        type Type = super::TWEntityCard;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWEntityCard {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder(LORE_CHANGED).build(),
+                    Signal::builder(CARD_CLOSED).build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            RelationKind::ALL
+                .iter()
+                .for_each(|kind| self.relation_kinds.append(kind.as_str()));
+            TravelMode::ALL
+                .iter()
+                .for_each(|mode| self.route_modes.append(mode.as_str()));
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWEntityCard {}
+
+    impl BoxImpl for TWEntityCard {}
+}
+
+glib::wrapper! {
+    pub struct TWEntityCard(ObjectSubclass<imp::TWEntityCard>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl TWEntityCard {
+    /// To edit the entities of `lore`.
+    pub fn set_lore(&self, lore: &SharedLore) {
+        self.imp().lore.replace(Some(lore.clone()));
+    }
+
+    /// Returns the id of the entity on the card.
+    pub fn entity(&self) -> Option<EntityId> {
+        self.imp().draft.borrow().as_ref().map(Entity::id)
+    }
+
+    /// To show the entity with `id`, dropping unsaved changes to the previous one. Returns false
+    /// if there is no such entity.
+    pub fn show_entity(&self, id: EntityId) -> bool {
+        let Some(lore) = self.imp().lore.borrow().clone() else { return false };
+        let Some(entity) = lore.borrow().get(id).cloned() else { return false };
+        let imp = self.imp();
+
+        imp.kind
+            .set_markup(format!("<b>{}</b>", entity.kind).as_str());
+        imp.name.set_text(entity.name.as_str());
+        imp.aliases.set_text(
+            entity
+                .aliases
+                .join(format!("{ALIAS_SEPARATOR}\x20").as_str())
+                .as_str(),
+        );
+        imp.description
+            .buffer()
+            .set_text(entity.description.as_str());
+        imp.attribute_key.set_text("");
+        imp.attribute_value.set_text("");
+        imp.attribute_since.set_text("");
+        imp.since.set_text("");
+        imp.until.set_text("");
+        imp.distance.set_text("");
+        imp.status.set_label("");
+        imp.draft.replace(Some(entity));
+
+        self.show_image();
+        self.show_attributes();
+        self.show_related();
+        self.show_routes();
+        true
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.back.connect_clicked(clone!(@weak self as card => move |_| {
+            card.emit_by_name::<()>(CARD_CLOSED, &[]);
+        }));
+        imp.save
+            .connect_clicked(clone!(@weak self as card => move |_| card.save()));
+        imp.delete
+            .connect_clicked(clone!(@weak self as card => move |_| card.delete()));
+        imp.choose_image
+            .connect_clicked(clone!(@weak self as card => move |_| card.choose_image()));
+        imp.clear_image.connect_clicked(clone!(@weak self as card => move |_| {
+            card.edit(|entity| entity.image = None);
+            card.show_image();
+        }));
+        imp.add_attribute
+            .connect_clicked(clone!(@weak self as card => move |_| card.add_attribute()));
+        imp.attribute_value
+            .connect_activate(clone!(@weak self as card => move |_| card.add_attribute()));
+        imp.attribute_since
+            .connect_activate(clone!(@weak self as card => move |_| card.add_attribute()));
+        imp.add_related
+            .connect_clicked(clone!(@weak self as card => move |_| card.add_related()));
+        imp.until
+            .connect_activate(clone!(@weak self as card => move |_| card.add_related()));
+        imp.add_route
+            .connect_clicked(clone!(@weak self as card => move |_| card.add_route()));
+        imp.distance
+            .connect_activate(clone!(@weak self as card => move |_| card.add_route()));
+    }
+
+    /// To change the copy of the entity with `change`.
+    fn edit(&self, change: impl FnOnce(&mut Entity)) {
+        if let Some(entity) = self.imp().draft.borrow_mut().as_mut() {
+            change(entity);
+        }
+    }
+
+    /// To show the image of the entity, hiding the [`Picture`] if there is none.
+    fn show_image(&self) {
+        let imp = self.imp();
+        let image = imp
+            .draft
+            .borrow()
+            .as_ref()
+            .and_then(|entity| entity.image.clone());
+
+        imp.image.set_filename(image.as_ref());
+        imp.image.set_visible(image.is_some());
+        imp.clear_image.set_sensitive(image.is_some());
+    }
+
+    /// To list the attributes of the entity, then the values they take later in the story, each
+    /// with a button to remove it.
+    fn show_attributes(&self) {
+        let imp = self.imp();
+        Self::clear(&imp.attributes);
+
+        let (attributes, mut changes) = imp
+            .draft
+            .borrow()
+            .as_ref()
+            .map(|entity| (entity.attributes.clone(), entity.changes.clone()))
+            .unwrap_or_default();
+        for (key, value) in attributes {
+            let label = format!(
+                "<b>{}</b>\x20{}",
+                markup_escape_text(key.as_str()),
+                markup_escape_text(value.as_str())
+            );
+            let remove = clone!(@weak self as card => move || {
+                card.edit(|entity| { entity.attributes.remove(key.as_str()); });
+                card.show_attributes();
+            });
+            imp.attributes.append(&Self::removable_row(label.as_str(), remove));
+        }
+
+        changes.sort_by(|lhs, rhs| lhs.key.cmp(&rhs.key).then(lhs.since.cmp(&rhs.since)));
+        for change in changes {
+            let label = format!(
+                "<b>{}</b>\x20{}\x20<small>(from {})</small>",
+                markup_escape_text(change.key.as_str()),
+                markup_escape_text(change.value.as_str()),
+                markup_escape_text(self.show_time(change.since).as_str())
+            );
+            let remove = clone!(@weak self as card => move || {
+                card.edit(|entity| entity.changes.retain(|other| *other != change));
+                card.show_attributes();
+            });
+            imp.attributes.append(&Self::removable_row(label.as_str(), remove));
+        }
+    }
+
+    /// To list the relations of the entity, each with a button to remove it, then the relations
+    /// other entities have to it. Every other entity is offered in the [`DropDown`].
+    fn show_related(&self) {
+        let imp = self.imp();
+        Self::clear(&imp.related);
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let Some((id, relations)) = imp
+            .draft
+            .borrow()
+            .as_ref()
+            .map(|entity| (entity.id(), entity.relations.clone()))
+        else {
+            return;
+        };
+        let lore = lore.borrow();
+
+        for (index, relation) in relations.iter().enumerate() {
+            let Some(other) = lore.get(relation.to) else { continue };
+            let label = Self::relation_markup(relation.kind.as_str(), other, relation);
+            let remove = clone!(@weak self as card => move || {
+                card.edit(|entity| { entity.relations.remove(index); });
+                card.show_related();
+            });
+            imp.related.append(&Self::removable_row(label.as_str(), remove));
+        }
+        for (from, relation) in lore.relations_to(id) {
+            let Some(other) = lore.get(from) else { continue };
+            let markup = Self::relation_markup(relation.kind.inverse_str(), other, relation);
+            let label = Label::new(None);
+            label.set_markup(markup.as_str());
+            label.set_xalign(0_f32);
+            label.set_wrap(true);
+            label.set_tooltip_text(Some(format!("Set on the card of {}.", other.name).as_str()));
+            imp.related.append(&label);
+        }
+
+        let candidates = lore
+            .iter()
+            .filter(|other| other.id() != id)
+            .collect::<Vec<&Entity>>();
+        let names = candidates
+            .iter()
+            .map(|other| format!("{} ({})", other.name, other.kind))
+            .collect::<Vec<String>>();
+        imp.relations.splice(
+            0_u32,
+            imp.relations.n_items(),
+            &names.iter().map(String::as_str).collect::<Vec<&str>>(),
+        );
+        imp.relation_ids
+            .replace(candidates.iter().map(|other| other.id()).collect());
+        imp.add_related.set_sensitive(!candidates.is_empty());
+    }
+
+    /// To list the routes of a location, each with a button to remove it, then the routes other
+    /// locations have to it. Every other location is offered in the [`DropDown`].
+    fn show_routes(&self) {
+        let imp = self.imp();
+        Self::clear(&imp.routes);
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let Some((id, kind, routes)) = imp
+            .draft
+            .borrow()
+            .as_ref()
+            .map(|entity| (entity.id(), entity.kind, entity.routes.clone()))
+        else {
+            return;
+        };
+        imp.routes_section.set_visible(kind == EntityKind::Location);
+        let lore = lore.borrow();
+
+        for (index, route) in routes.iter().enumerate() {
+            let Some(other) = lore.get(route.to) else { continue };
+            let label = Self::route_markup(other, route);
+            let remove = clone!(@weak self as card => move || {
+                card.edit(|entity| { entity.routes.remove(index); });
+                card.show_routes();
+            });
+            imp.routes
+                .append(&Self::removable_row(label.as_str(), remove));
+        }
+        for other in lore.of_kind(EntityKind::Location) {
+            for route in other.routes.iter().filter(|route| route.to == id) {
+                let label = Label::new(None);
+                label.set_markup(Self::route_markup(other, route).as_str());
+                label.set_xalign(0_f32);
+                label.set_wrap(true);
+                label
+                    .set_tooltip_text(Some(format!("Set on the card of {}.", other.name).as_str()));
+                imp.routes.append(&label);
+            }
+        }
+
+        let candidates = lore
+            .of_kind(EntityKind::Location)
+            .filter(|other| other.id() != id)
+            .collect::<Vec<&Entity>>();
+        let names = candidates
+            .iter()
+            .map(|other| other.name.as_str())
+            .collect::<Vec<&str>>();
+        imp.route_targets
+            .splice(0_u32, imp.route_targets.n_items(), &names);
+        imp.route_ids
+            .replace(candidates.iter().map(|other| other.id()).collect());
+        imp.add_route.set_sensitive(!candidates.is_empty());
+    }
+
+    /// Returns the markup of a row that reads the route to `other` with its distance and modes.
+    fn route_markup(other: &Entity, route: &Route) -> String {
+        format!(
+            "To\x20<b>{}</b>\x20<small>({})</small>",
+            markup_escape_text(other.name.as_str()),
+            markup_escape_text(route.describe().as_str())
+        )
+    }
+
+    /// Returns the markup of a row that reads `kind` followed by `other` and the span of
+    /// `relation`.
+    fn relation_markup(kind: &str, other: &Entity, relation: &Relation) -> String {
+        let span = relation
+            .span()
+            .map(|span| format!("\x20<small>({span})</small>"))
+            .unwrap_or_default();
+        format!(
+            "{kind}\x20<b>{}</b>{span}",
+            markup_escape_text(other.name.as_str())
+        )
+    }
+
+    /// To add the attribute in the entries, replacing the value if the attribute is already set.
+    /// With a time, the value is a change taken from then on instead.
+    fn add_attribute(&self) {
+        let imp = self.imp();
+        let key = imp.attribute_key.text().trim().to_string();
+        if key.is_empty() {
+            imp.status.set_label("An attribute needs a name.");
+            return;
+        }
+        let value = imp.attribute_value.text().trim().to_string();
+        let Ok(since) = self.time(&imp.attribute_since) else {
+            imp.status
+                .set_label("From has to be a date of the calendar or a day number.");
+            return;
+        };
+
+        self.edit(|entity| match since {
+            Some(since) => {
+                entity
+                    .changes
+                    .retain(|change| change.key != key || change.since != since);
+                entity
+                    .changes
+                    .push(AttributeChange::new(key.as_str(), value.as_str(), since));
+            }
+            None => {
+                entity.attributes.insert(key, value);
+            }
+        });
+        imp.attribute_key.set_text("");
+        imp.attribute_value.set_text("");
+        imp.attribute_since.set_text("");
+        imp.status.set_label("");
+        imp.attribute_key.grab_focus();
+        self.show_attributes();
+    }
+
+    /// To relate the entity chosen in the [`DropDown`] with the chosen kind of relation and the
+    /// span in the entries.
+    fn add_related(&self) {
+        let imp = self.imp();
+        let Some(id) = self.entity() else { return };
+        let index = imp.relation.selected() as usize;
+        let Some(other) = imp.relation_ids.borrow().get(index).copied() else { return };
+        let kind = RelationKind::ALL
+            .get(imp.relation_kind.selected() as usize)
+            .copied()
+            .unwrap_or(RelationKind::Related);
+        let (Ok(since), Ok(until)) = (self.time(&imp.since), self.time(&imp.until)) else {
+            imp.status
+                .set_label("Since and until have to be dates of the calendar or day numbers.");
+            return;
+        };
+
+        let relation = Relation {
+            since,
+            until,
+            ..Relation::new(kind, other)
+        };
+        if let Err(err) = relation.validate(id) {
+            imp.status.set_label(err.to_string().as_str());
+            return;
+        }
+        self.edit(|entity| entity.relations.push(relation));
+        imp.since.set_text("");
+        imp.until.set_text("");
+        imp.status.set_label("");
+        self.show_related();
+    }
+
+    /// To add a route to the location chosen in the [`DropDown`] with the distance in the entry and
+    /// the chosen way of travelling.
+    fn add_route(&self) {
+        let imp = self.imp();
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let Some(id) = self.entity() else { return };
+        let index = imp.route.selected() as usize;
+        let Some(other) = imp.route_ids.borrow().get(index).copied() else { return };
+        let Ok(distance) = imp.distance.text().trim().parse::<u32>() else {
+            imp.status
+                .set_label("The distance has to be a whole number.");
+            return;
+        };
+        let modes = (imp.route_mode.selected() as usize)
+            .checked_sub(1_usize)
+            .and_then(|index| TravelMode::ALL.get(index))
+            .into_iter()
+            .copied()
+            .collect();
+
+        let route = Route {
+            modes,
+            ..Route::new(other, distance)
+        };
+        if let Err(err) = route.validate(id, &lore.borrow()) {
+            imp.status.set_label(err.to_string().as_str());
+            return;
+        }
+        self.edit(|entity| entity.routes.push(route));
+        imp.distance.set_text("");
+        imp.status.set_label("");
+        self.show_routes();
+    }
+
+    /// Returns the time in `entry`, a date of the project calendar or a day number, or [`None`]
+    /// if it is empty.
+    fn time(&self, entry: &Entry) -> Result<Option<StoryTime>, ()> {
+        match entry.text().trim() {
+            "" => Ok(None),
+            text => parse_time(text, self.calendar().as_ref())
+                .map(Some)
+                .ok_or(()),
+        }
+    }
+
+    /// Returns the time `time` as it is written in the entries.
+    fn show_time(&self, time: StoryTime) -> String {
+        match self.calendar() {
+            Some(calendar) => calendar.format(calendar.from_day(time)),
+            None => time.to_string(),
+        }
+    }
+
+    /// Returns the calendar of the project of the window the card is in.
+    fn calendar(&self) -> Option<Calendar> {
+        self.root()
+            .and_then(|root| root.downcast::<TWApplicationWindow>().ok())
+            .and_then(|window| window.calendar())
+    }
+
+    /// To ask for an image and show it on the card.
+    fn choose_image(&self) {
+        let parent = self
+            .root()
+            .and_then(|root| root.downcast::<gtk::Window>().ok());
+        let chooser = FileChooserNative::new(
+            Some("Choose Image"),
+            parent.as_ref(),
+            FileChooserAction::Open,
+            Some("_Open"),
+            Some("_Cancel"),
+        );
+        let filter = FileFilter::new();
+        filter.set_name(Some("Images"));
+        filter.add_pixbuf_formats();
+        chooser.add_filter(&filter);
+
+        // The chooser has to be kept alive until it answers.
+        let kept = Rc::new(RefCell::new(Some(chooser.clone())));
+        chooser.connect_response(clone!(@weak self as card => move |chooser, response| {
+            kept.replace(None);
+            if response != ResponseType::Accept { return }
+            let Some(path) = chooser.file().and_then(|file| file.path()) else { return };
+
+            card.edit(|entity| entity.image = Some(path));
+            card.show_image();
+        }));
+
+        chooser.show();
+    }
+
+    /// To write the card to the registry.
+    fn save(&self) {
+        let imp = self.imp();
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let name = imp.name.text().trim().to_string();
+        if name.is_empty() {
+            imp.status.set_label(LoreError::EmptyName.to_string().as_str());
+            return;
+        }
+        let aliases = imp
+            .aliases
+            .text()
+            .split(ALIAS_SEPARATOR)
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty() && *alias != name)
+            .map(String::from)
+            .collect::<Vec<String>>();
+        let buffer = imp.description.buffer();
+        let (start, end) = buffer.bounds();
+        let description = buffer.text(&start, &end, false).trim().to_string();
+
+        self.edit(|entity| {
+            entity.name = name;
+            entity.aliases = aliases;
+            entity.description = description;
+        });
+        let Some(draft) = imp.draft.borrow().clone() else { return };
+        let mut registry = lore.borrow_mut();
+        let Some(entity) = registry.get_mut(draft.id()) else {
+            imp.status
+                .set_label(LoreError::EntityNotFound(draft.id()).to_string().as_str());
+            return;
+        };
+        *entity = draft;
+        drop(registry);
+
+        imp.status.set_label("Saved.");
+        self.emit_by_name::<()>(LORE_CHANGED, &[]);
+    }
+
+    /// To remove the entity from the registry and close the card.
+    fn delete(&self) {
+        let imp = self.imp();
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let Some(id) = self.entity() else { return };
+
+        if let Err(err) = lore.borrow_mut().remove(id) {
+            error!("{err}");
+        }
+        imp.draft.replace(None);
+        self.emit_by_name::<()>(LORE_CHANGED, &[]);
+        self.emit_by_name::<()>(CARD_CLOSED, &[]);
+    }
+
+    /// Returns a row with `markup` and a button that calls `remove`.
+    fn removable_row(markup: &str, remove: impl Fn() + 'static) -> gtk::Box {
+        let label = Label::new(None);
+        label.set_markup(markup);
+        label.set_xalign(0_f32);
+        label.set_hexpand(true);
+        label.set_wrap(true);
+
+        let button = Button::from_icon_name("list-remove-symbolic");
+        button.set_has_frame(false);
+        button.connect_clicked(move |_| remove());
+
+        let row = gtk::Box::new(Orientation::Horizontal, 6_i32);
+        row.append(&label);
+        row.append(&button);
+        row
+    }
+
+    /// To remove every row of `list`.
+    fn clear(list: &ListBox) {
+        while let Some(row) = list.row_at_index(0_i32) {
+            list.remove(&row);
+        }
+    }
+}
+
+impl Default for TWEntityCard {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWEntityCard object")
+    }
+}
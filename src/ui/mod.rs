@@ -6,9 +6,26 @@
 //! Handles the UI elements of the application.
 
 pub(crate) mod app;
+pub(crate) mod appearances;
+pub(crate) mod calendars;
+pub(crate) mod dashboard;
+pub(crate) mod dialogue;
+pub(crate) mod export;
+pub(crate) mod findbar;
+pub(crate) mod goals;
+pub(crate) mod interchange;
 pub(crate) mod license;
+pub(crate) mod lint;
+pub(crate) mod lore;
 pub(crate) mod menubox;
 pub(crate) mod page;
+pub(crate) mod readability;
+pub(crate) mod relations;
+pub(crate) mod rename;
+pub(crate) mod scenes;
+pub(crate) mod series;
 pub(crate) mod statusbar;
+pub(crate) mod tabview;
+pub(crate) mod timeline;
 pub(crate) mod toolbar;
 pub(crate) mod window;
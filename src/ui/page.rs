@@ -6,17 +6,35 @@
 //! To handle the [`TextView`] object of the application.
 
 mod buffer;
+mod completion;
 mod dimensions;
 
-use crate::ui::statusbar::TWWordCount;
-pub use buffer::TWBuffer;
+use crate::ui::statusbar::TWStatusBar;
+pub use buffer::{
+    BufferDiagnostic, SharedLore, SharedSpellChecker, TWBuffer, ENTITY_ADDED, MENTION_ACTIVATED,
+    TEXT_EDITED,
+};
+use completion::{TWCompletion, COMPLETION_CHOSEN};
 use dimensions::*;
+pub use dimensions::PaperSize;
 use gtk::{
+    gdk::{Key, Rectangle},
+    gio::{Menu, MenuItem, MenuModel, SimpleAction, SimpleActionGroup},
     glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
-    subclass::prelude::*, CompositeTemplate, TextBuffer, TextView,
+    subclass::prelude::*, CompositeTemplate, EventControllerFocus, EventControllerKey, GestureClick,
+    PropagationPhase, TextBuffer, TextIter, TextMark, TextView, TextWindowType, Tooltip,
 };
 #[allow(unused_imports)]
 use log::*;
+use lore::{typed_prefix, Completion, EntityKind};
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use syntax::statistics::Statistics;
+
+/// How long typing has to pause before the [`TWStatusBar`] is refreshed.
+const REFRESH_DELAY: Duration = Duration::from_millis(150_u64);
+/// Number of spelling suggestions shown in the context menu.
+const SUGGESTIONS: usize = 6_usize;
 
 /*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
 
@@ -31,8 +49,24 @@ mod imp {
         pub count: usize,
         /// To keep track of page dimensions.
         pub size: Pixels,
-        /// To get a reference to [`TWWordCount`] label and update it accordingly.
-        pub count_label: WeakRef<TWWordCount>,
+        /// The scheduled [`TWStatusBar`] refresh, pushed back by every edit.
+        pub refresh_source: RefCell<Option<SourceId>>,
+        /// To get a reference to [`TWStatusBar`] and update it accordingly. Only set while the
+        /// page is the active tab.
+        pub statusbar: WeakRef<TWStatusBar>,
+        /// Title of the document shown in the tab and the document tree.
+        pub title: RefCell<String>,
+        /// Where the cursor of this view was left when it lost focus. Views sharing a
+        /// [`TWBuffer`] restore their own cursor from it when focused again.
+        pub cursor: RefCell<Option<TextMark>>,
+        /// Character offsets of the misspelled word the context menu was opened on.
+        pub misspelled: Cell<Option<(i32, i32)>>,
+        /// The unknown name the context menu was opened on.
+        pub unknown_name: RefCell<Option<String>>,
+        /// Suggests lore names for the word being typed.
+        pub completion: TWCompletion,
+        /// Handlers on the viewed buffer, disconnected when the page views another one.
+        pub handlers: RefCell<Vec<SignalHandlerId>>,
         /// The main [`TextView`] buffer.
         #[template_child]
         pub buffer: TemplateChild<TWBuffer>,
@@ -46,7 +80,6 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             klass.bind_template();
-            klass.bind_template_callbacks();
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -54,45 +87,92 @@ mod imp {
         }
     }
 
-    #[gtk::template_callbacks]
     impl TWPage {
         /// To move the view to follow the cursor in [`TWPage`].
-        #[template_callback]
-        fn scroll_to_cursor(&self, page: &TextBuffer) {
+        pub(super) fn scroll_to_cursor(&self, page: &TextBuffer) {
+            // Views sharing the buffer should keep their own scroll position.
+            if !self.obj().has_focus() { return }
             let mark = page.get_insert();
             self.obj()
                 .scroll_to_mark(&mark, 0_f64, true, 0.5_f64, 0.5_f64);
         }
+    }
 
-        #[template_callback]
-        fn update_count_label(&self, buffer: &TextBuffer) {
-            let Some(wordcount) = self.count_label.upgrade() else {
-                warn!("TWWordCount reference not set. Trying to set it again.");
-                self.obj().set_count_label_reference();
-                return
-            };
+    impl ObjectImpl for TWPage {
+        fn constructed(&self) {
+            self.parent_constructed();
 
-            let start = buffer.start_iter();
-            let end = buffer.end_iter();
-            let text = buffer.text(&start, &end, true);
+            let focus = EventControllerFocus::new();
+            focus.connect_enter(clone!(@weak self as page => move |_| {
+                page.obj().restore_cursor();
+            }));
+            focus.connect_leave(clone!(@weak self as page => move |_| {
+                page.obj().save_cursor();
+                page.completion.popdown();
+            }));
+            self.obj().add_controller(focus);
 
-            wordcount
-                .imp()
-                .label
-                .set_label(format!("{}", self.obj().parse_word_count(text.as_str())).as_str());
-        }
-    }
+            // Lore names are suggested as they are typed.
+            self.completion.set_parent(&*self.obj());
+            self.completion.connect_local(
+                COMPLETION_CHOSEN,
+                false,
+                clone!(@weak self as page => @default-return None, move |values| {
+                    let index = values[1_usize].get::<u32>().ok()?;
+                    let completion = page.completion.completion(index as usize)?;
+                    page.obj().accept_completion(&completion);
+                    None
+                }),
+            );
+            let keys = EventControllerKey::new();
+            keys.set_propagation_phase(PropagationPhase::Capture);
+            keys.connect_key_pressed(clone!(@weak self as page => @default-return Inhibit(false),
+                move |_, key, _, _| page.obj().completion_key(key)
+            ));
+            self.obj().add_controller(keys);
+            self.obj().connect_buffer(&self.buffer);
 
-    impl ObjectImpl for TWPage {}
+            // The context menu is filled with suggestions and lore entries before the TextView
+            // shows it.
+            let click = GestureClick::builder()
+                .button(3_u32)
+                .propagation_phase(PropagationPhase::Capture)
+                .build();
+            click.connect_pressed(clone!(@weak self as page => move |_, _, x, y| {
+                page.obj().prepare_context_menu(x, y);
+            }));
+            self.obj().add_controller(click);
+            self.obj().create_spell_actions();
+            self.obj().create_lore_actions();
 
-    impl WidgetImpl for TWPage {
-        /// Ran when the textview is mapped to the screen.
-        fn map(&self) {
-            self.parent_map();
-            self.obj().set_count_label_reference();
+            // A plain click on a mention opens the card of its lore entity.
+            let click = GestureClick::builder()
+                .button(1_u32)
+                .propagation_phase(PropagationPhase::Capture)
+                .build();
+            click.connect_released(clone!(@weak self as page => move |_, presses, x, y| {
+                page.completion.popdown();
+                if presses == 1_i32 {
+                    page.obj().activate_mention(x, y);
+                }
+            }));
+            self.obj().add_controller(click);
+
+            // Lint findings explain themselves when hovered.
+            self.obj().set_has_tooltip(true);
+            self.obj().connect_query_tooltip(|page, x, y, keyboard, tooltip| {
+                page.lint_tooltip(x, y, keyboard, tooltip)
+            });
+        }
+
+        fn dispose(&self) {
+            self.obj().disconnect_buffer();
+            self.completion.unparent();
         }
     }
 
+    impl WidgetImpl for TWPage {}
+
     impl TextViewImpl for TWPage {}
 }
 
@@ -102,46 +182,396 @@ glib::wrapper! {
 }
 
 impl TWPage {
-    /// A simple set function to fetch and set the reference to [`TWWordCount`].
-    fn set_count_label_reference(&self) {
-        let Some(wordcount) = self.count_label_reference() else {
-            error!("Error while getting TWWordCount reference from TWPage");
+    /// Returns a new instance of [`TWPage`] with its own [`TWBuffer`].
+    pub fn new() -> Self {
+        Object::new::<Self>()
+    }
+
+    /// Returns a new [`TWPage`] that views the given `buffer` instead of its own. Used to show the
+    /// same document in more than one pane.
+    pub fn with_buffer(buffer: &TWBuffer) -> Self {
+        let page = Self::new();
+        page.disconnect_buffer();
+        page.set_buffer(Some(buffer));
+        page.connect_buffer(buffer);
+
+        page
+    }
+
+    /// Returns the title of the document.
+    pub fn document_title(&self) -> String {
+        self.imp().title.borrow().clone()
+    }
+
+    /// To set the title of the document.
+    pub fn set_document_title(&self, title: &str) {
+        self.imp().title.replace(title.to_string());
+    }
+
+    /// Returns the whole text of the buffer. Non-text items are kept as `U+FFFC` so that
+    /// character offsets match the buffer.
+    pub fn text(&self) -> String {
+        let (start, end) = self.buffer().bounds();
+        self.buffer().slice(&start, &end, true).to_string()
+    }
+
+    /// Returns the [`TWBuffer`] this page is currently viewing.
+    pub fn tw_buffer(&self) -> TWBuffer {
+        self.buffer()
+            .downcast()
+            .expect("Error while downcasting TWPage buffer to TWBuffer")
+    }
+
+    /// To remember where the cursor of this view is before another view moves it.
+    fn save_cursor(&self) {
+        let buffer = self.buffer();
+        let insert = buffer.iter_at_mark(&buffer.get_insert());
+
+        let mut cursor = self.imp().cursor.borrow_mut();
+        match cursor.as_ref() {
+            Some(mark) => buffer.move_mark(mark, &insert),
+            None => *cursor = Some(buffer.create_mark(None, &insert, false)),
+        }
+    }
+
+    /// To place the cursor back where this view left it.
+    fn restore_cursor(&self) {
+        let buffer = self.buffer();
+        let Some(mark) = self.imp().cursor.borrow().clone() else { return };
+        if mark.buffer().as_ref() != Some(&buffer) { return }
+
+        buffer.place_cursor(&buffer.iter_at_mark(&mark));
+    }
+
+    /// To add the `spell.replace` and `spell.add` actions used by the context menu.
+    fn create_spell_actions(&self) {
+        let actions = SimpleActionGroup::new();
+
+        let action_replace = SimpleAction::new("replace", Some(VariantTy::STRING));
+        action_replace.connect_activate(clone!(@weak self as page => move |_, word| {
+            let Some(word) = word.and_then(|word| word.get::<String>()) else { return };
+            page.replace_misspelled(word.as_str());
+        }));
+        actions.add_action(&action_replace);
+
+        let action_add = SimpleAction::new("add", None);
+        action_add.connect_activate(clone!(@weak self as page => move |_, _| {
+            page.accept_misspelled();
+        }));
+        actions.add_action(&action_add);
+
+        self.insert_action_group("spell", Some(&actions));
+    }
+
+    /// To follow `buffer`, the one this page views: the view scrolls to the cursor and suggests
+    /// lore names after every edit, and the [`TWStatusBar`] follows the text and the selection.
+    fn connect_buffer(&self, buffer: &TWBuffer) {
+        let handlers = vec![
+            buffer.connect_end_user_action(clone!(@weak self as page => move |buffer| {
+                page.imp().scroll_to_cursor(buffer.upcast_ref());
+                page.update_completion();
+            })),
+            // The buffer keeps its statistics up to date on every edit, so only the status bar
+            // has to follow.
+            buffer.connect_changed(clone!(@weak self as page => move |_| {
+                page.queue_refresh();
+            })),
+            // Selection counts follow the selection bounds.
+            buffer.connect_mark_set(clone!(@weak self as page => move |buffer, _, mark| {
+                if *mark == buffer.get_insert() || *mark == buffer.selection_bound() {
+                    page.queue_refresh();
+                }
+            })),
+        ];
+        self.imp().handlers.replace(handlers);
+    }
+
+    /// To stop following the buffer this page views, before it views another one or goes away.
+    fn disconnect_buffer(&self) {
+        let buffer = self.buffer();
+        for handler in self.imp().handlers.take() {
+            buffer.disconnect(handler);
+        }
+    }
+
+    /// Returns the start of the word being typed before the cursor and the cursor, unless the
+    /// cursor is inside a word or there is a selection.
+    fn typed_prefix(&self) -> Option<(TextIter, TextIter)> {
+        let buffer = self.tw_buffer();
+        let cursor = buffer.iter_at_mark(&buffer.get_insert());
+        if buffer.has_selection() || cursor.char().is_alphanumeric() {
+            return None;
+        }
+
+        let mut line = cursor;
+        line.set_line_offset(0_i32);
+        let before = buffer.slice(&line, &cursor, true);
+        let prefix = typed_prefix(before.as_str()).chars().count() as i32;
+        Some((buffer.iter_at_offset(cursor.offset() - prefix), cursor))
+    }
+
+    /// To show the lore names that complete the word being typed, if the cursor is at the end of
+    /// one in this view.
+    fn update_completion(&self) {
+        let completion = &self.imp().completion;
+        let Some((start, cursor)) = self.typed_prefix().filter(|_| self.has_focus()) else {
+            completion.popdown();
             return
         };
-        self.imp().count_label.set(Some(&wordcount));
-    }
-
-    /// A simple function to get the reference to [`TWWordCount`].
-    fn count_label_reference(&self) -> Option<TWWordCount> {
-        self.parent()
-            .and_then(|scrolled| scrolled.parent())
-            .and_then(|gtkbox| gtkbox.last_child())
-            .and_then(|statbar| statbar.first_child())
-            .and_then(|wordcount| {
-                if let Ok(wordcount) = wordcount.downcast::<TWWordCount>() {
-                    return Some(wordcount);
-                }
-                None
-            })
-    }
-
-    /// Takes a buffer and calculates the word count.
-    fn parse_word_count(&self, text: &str) -> usize {
-        let mut word_count = 0_usize;
-        let mut is_white = true;
-
-        text
-            .chars()
-            .for_each(|ch| {
-                if ch.is_whitespace() || ch.is_ascii_punctuation() {
-                    is_white = true;
-                } else if is_white {
-                    is_white = false;
-                    word_count += 1_usize;
+
+        let buffer = self.tw_buffer();
+        let prefix = buffer.slice(&start, &cursor, true);
+        let completions = buffer.complete(prefix.as_str(), cursor.line());
+        let location = self.iter_location(&cursor);
+        let (x, y) =
+            self.buffer_to_window_coords(TextWindowType::Widget, location.x(), location.y());
+        completion.show_completions(completions, &Rectangle::new(x, y, 1_i32, location.height()));
+    }
+
+    /// To replace the word being typed with `completion`.
+    fn accept_completion(&self, completion: &Completion) {
+        let Some((mut start, mut cursor)) = self.typed_prefix() else { return };
+        let buffer = self.tw_buffer();
+
+        buffer.begin_user_action();
+        buffer.delete(&mut start, &mut cursor);
+        buffer.insert(&mut start, completion.text.as_str());
+        buffer.end_user_action();
+        buffer.accept_completion(completion.entity);
+        // Ending the user action suggests names for the inserted one.
+        self.imp().completion.popdown();
+    }
+
+    /// To move through and choose from the lore names with the keyboard while they are shown.
+    /// Returns whether the key was used.
+    fn completion_key(&self, key: Key) -> Inhibit {
+        let completion = &self.imp().completion;
+        if !completion.is_visible() {
+            return Inhibit(false);
+        }
+
+        match key {
+            Key::Down => completion.move_selection(1_i32),
+            Key::Up => completion.move_selection(-1_i32),
+            Key::Tab | Key::Return | Key::KP_Enter => {
+                if let Some(selected) = completion.selected() {
+                    self.accept_completion(&selected);
                 }
-            });
+            }
+            Key::Escape => completion.popdown(),
+            _ => return Inhibit(false),
+        }
+        Inhibit(true)
+    }
+
+    /// To add the `lore.add` action used by the context menu. Its target is the name of the
+    /// [`EntityKind`] to add.
+    fn create_lore_actions(&self) {
+        let actions = SimpleActionGroup::new();
+
+        let action_add = SimpleAction::new("add", Some(VariantTy::STRING));
+        action_add.connect_activate(clone!(@weak self as page => move |_, kind| {
+            let Some(kind) = kind.and_then(|kind| kind.get::<String>()) else { return };
+            let Some(kind) = EntityKind::ALL.into_iter().find(|known| known.as_str() == kind) else {
+                return
+            };
+            let Some(name) = page.imp().unknown_name.take() else { return };
+            page.tw_buffer().add_entity(kind, name.as_str());
+        }));
+        actions.add_action(&action_add);
+
+        self.insert_action_group("lore", Some(&actions));
+    }
+
+    /// To move the cursor to the pointer at `x` and `y` and fill the context menu with spelling
+    /// suggestions if it is on a misspelled word, and with a way to add it to the lore if it is on
+    /// an unknown name.
+    fn prepare_context_menu(&self, x: f64, y: f64) {
+        let buffer = self.tw_buffer();
+        let (x, y) = self.window_to_buffer_coords(TextWindowType::Widget, x as i32, y as i32);
+        let iter = self.iter_at_location(x, y);
+        if let Some(iter) = iter.as_ref() {
+            if !buffer.has_selection() {
+                buffer.place_cursor(iter);
+            }
+        }
+
+        let menu = Menu::new();
+        let spelling = iter.as_ref().and_then(|iter| self.spell_menu(iter));
+        let lore = iter.as_ref().and_then(|iter| self.lore_menu(iter));
+        for section in [spelling, lore].into_iter().flatten() {
+            menu.append_section(None, &section);
+        }
+
+        match menu.n_items() {
+            0_i32 => self.set_extra_menu(None::<&MenuModel>),
+            _ => self.set_extra_menu(Some(&menu)),
+        }
+    }
+
+    /// Returns the spelling suggestions for the misspelled word at `iter`, if there is one.
+    fn spell_menu(&self, iter: &TextIter) -> Option<Menu> {
+        let buffer = self.tw_buffer();
+        let word = buffer.misspelled_word_at(iter);
+        let (Some((start, end)), Some(spell)) = (word, buffer.spell_checker()) else {
+            self.imp().misspelled.set(None);
+            return None
+        };
+
+        let word = buffer.slice(&start, &end, true);
+        let suggestions = Menu::new();
+        for suggestion in spell.borrow().suggest(word.as_str(), SUGGESTIONS) {
+            let item = MenuItem::new(Some(suggestion.as_str()), None);
+            item.set_action_and_target_value(Some("spell.replace"), Some(&suggestion.to_variant()));
+            suggestions.append_item(&item);
+        }
+        if suggestions.n_items() == 0_i32 {
+            suggestions.append(Some("No Suggestions"), None);
+        }
+
+        let menu = Menu::new();
+        menu.append_section(None, &suggestions);
+        menu.append(
+            Some(format!("Add “{word}” to Dictionary").as_str()),
+            Some("spell.add"),
+        );
+        self.imp().misspelled.set(Some((start.offset(), end.offset())));
+        Some(menu)
+    }
+
+    /// Returns a submenu to add the unknown name at `iter` to the lore as any
+    /// [`EntityKind`], if there is one.
+    fn lore_menu(&self, iter: &TextIter) -> Option<Menu> {
+        let buffer = self.tw_buffer();
+        let Some((start, end)) = buffer.unknown_name_at(iter) else {
+            self.imp().unknown_name.replace(None);
+            return None
+        };
+
+        let name = buffer.slice(&start, &end, true);
+        let kinds = Menu::new();
+        for kind in EntityKind::ALL {
+            let item = MenuItem::new(Some(kind.as_str()), None);
+            item.set_action_and_target_value(Some("lore.add"), Some(&kind.as_str().to_variant()));
+            kinds.append_item(&item);
+        }
+
+        let menu = Menu::new();
+        menu.append_submenu(Some(format!("Add “{name}” to Lore").as_str()), &kinds);
+        self.imp().unknown_name.replace(Some(name.to_string()));
+        Some(menu)
+    }
+
+    /// To replace the misspelled word the context menu was opened on with `word`.
+    fn replace_misspelled(&self, word: &str) {
+        let Some((start, end)) = self.imp().misspelled.take() else { return };
+        let buffer = self.buffer();
+
+        buffer.begin_user_action();
+        let mut start = buffer.iter_at_offset(start);
+        buffer.delete(&mut start, &mut buffer.iter_at_offset(end));
+        buffer.insert(&mut start, word);
+        buffer.end_user_action();
+    }
+
+    /// To add the misspelled word the context menu was opened on to the project dictionary.
+    fn accept_misspelled(&self) {
+        let Some((start, end)) = self.imp().misspelled.take() else { return };
+        let buffer = self.tw_buffer();
+        let Some(spell) = buffer.spell_checker() else { return };
+
+        let word = buffer.slice(&buffer.iter_at_offset(start), &buffer.iter_at_offset(end), true);
+        spell.borrow_mut().add_word(word.as_str());
+        buffer.check_spelling();
+    }
+
+    /// To emit [`MENTION_ACTIVATED`] on the buffer if the pointer at `x` and `y` is on a mention
+    /// of a lore entity. Clicks that end a selection are ignored.
+    fn activate_mention(&self, x: f64, y: f64) {
+        let buffer = self.tw_buffer();
+        if buffer.has_selection() {
+            return;
+        }
+        let (x, y) = self.window_to_buffer_coords(TextWindowType::Widget, x as i32, y as i32);
+        let Some(iter) = self.iter_at_location(x, y) else { return };
+        let Some(entity) = buffer.entity_at(&iter) else { return };
+
+        buffer.emit_by_name::<()>(MENTION_ACTIVATED, &[&entity.value()]);
+    }
+
+    /// To show the messages of the lint findings under the pointer at `x` and `y`, or under the
+    /// cursor when the tooltip was asked for with the keyboard. Returns false if there are none.
+    fn lint_tooltip(&self, x: i32, y: i32, keyboard: bool, tooltip: &Tooltip) -> bool {
+        let buffer = self.tw_buffer();
+        let iter = if keyboard {
+            Some(buffer.iter_at_mark(&buffer.get_insert()))
+        } else {
+            let (x, y) = self.window_to_buffer_coords(TextWindowType::Widget, x, y);
+            self.iter_at_location(x, y)
+        };
+        let Some(iter) = iter else { return false };
+
+        let messages = buffer
+            .diagnostics_at(&iter)
+            .into_iter()
+            .map(|diagnostic| format!("{}:\x20{}", diagnostic.severity, diagnostic.message))
+            .collect::<Vec<String>>();
+        if messages.is_empty() {
+            return false;
+        }
+        tooltip.set_text(Some(messages.join("\n").as_str()));
+        true
+    }
+
+    /// To attach the [`TWStatusBar`] to this page and show its statistics.
+    pub fn attach_statusbar(&self, statusbar: &TWStatusBar) {
+        self.imp().statusbar.set(Some(statusbar));
+        self.refresh_statusbar();
+    }
+
+    /// To stop this page from updating the [`TWStatusBar`].
+    pub fn detach_statusbar(&self) {
+        self.imp().statusbar.set(None);
+    }
+
+    /// To refresh the [`TWStatusBar`] once typing pauses instead of on every keystroke.
+    fn queue_refresh(&self) {
+        if let Some(source) = self.imp().refresh_source.take() {
+            source.remove();
+        }
+        if self.imp().statusbar.upgrade().is_none() {
+            return;
+        }
+        let source = glib::timeout_add_local_once(
+            REFRESH_DELAY,
+            clone!(@weak self as page => move || {
+                page.imp().refresh_source.replace(None);
+                page.refresh_statusbar();
+            }),
+        );
+        self.imp().refresh_source.replace(Some(source));
+    }
+
+    /// To show the statistics of the buffer and of the selection on the attached
+    /// [`TWStatusBar`] if there is one.
+    fn refresh_statusbar(&self) {
+        let Some(statusbar) = self.imp().statusbar.upgrade() else { return };
+        let buffer = self.tw_buffer();
+        let selection = buffer
+            .selection_bounds()
+            .map(|(start, end)| Statistics::new(buffer.text(&start, &end, true).as_str()));
+
+        statusbar.show_statistics(
+            &buffer.statistics(),
+            selection.as_ref(),
+            buffer.paper().words_per_page(),
+        );
+    }
 
-        word_count
+    /// To compile the document to `paper` and count its pages in it.
+    pub fn set_paper(&self, paper: PaperSize) {
+        self.tw_buffer().set_paper(paper);
+        self.refresh_statusbar();
     }
 }
 
@@ -5,17 +5,71 @@
 //!
 //! Handles the buffer input directly.
 
+use super::dimensions::PaperSize;
 use gtk::{
-    glib, glib::subclass::object::ObjectImpl, glib::Object, prelude::*, subclass::prelude::*,
-    TextBuffer, TextIter,
+    gdk::RGBA, glib, glib::subclass::object::ObjectImpl, glib::subclass::Signal, glib::Object,
+    pango, prelude::*, subclass::prelude::*, TextBuffer, TextIter, TextTag,
 };
 #[allow(unused_imports)]
 use log::*;
+use lore::{Completer, Completion, EntityId, EntityKind, MentionFinder, MentionIndex, Registry};
+use once_cell::sync::Lazy;
+use std::{
+    cell::{Cell, RefCell},
+    ops::Range,
+    rc::Rc,
+};
+use syntax::{
+    annotation::{annotations, hide_annotations, strip_annotations, Annotation},
+    lint::Severity,
+    segment::word_ranges,
+    spell::SpellChecker,
+    statistics::{LineStatistics, Statistics},
+};
+
+/// A [`SpellChecker`] shared by every buffer of a window.
+pub type SharedSpellChecker = Rc<RefCell<SpellChecker>>;
+/// The lore [`Registry`] shared by every buffer of a window.
+pub type SharedLore = Rc<RefCell<Registry>>;
 
 /// To hold tab value.
 const TAB: &str = "\x09";
 /// To hold space value.
 const SPACE: &str = "\x20";
+/// Number of lore names suggested while typing.
+const COMPLETIONS: usize = 6_usize;
+/// Name of the [`TextTag`] that highlights search matches.
+const SEARCH_TAG: &str = "search-match";
+/// Name of the [`TextTag`] that underlines misspelled words.
+const MISSPELLED_TAG: &str = "misspelled";
+/// Prefix of the names of the [`TextTag`]s that underline lint findings, one per [`Severity`].
+const LINT_TAG: &str = "lint-";
+/// Name of the [`TextTag`] that marks mentions of lore entities.
+const MENTION_TAG: &str = "lore-mention";
+/// Name of the [`TextTag`] that marks capitalised names missing from the lore.
+const UNKNOWN_NAME_TAG: &str = "lore-unknown-name";
+/// Name of the [`TextTag`] that dims the markup of lore annotations.
+const ANNOTATION_TAG: &str = "lore-annotation";
+/// Signal emitted after every edit with the number of words typed, negative if removed. Edits
+/// made with [`TWBuffer::untyped_action`] and undoing or redoing count no words.
+pub const TEXT_EDITED: &str = "text-edited";
+/// Signal emitted when a view of the buffer activates a mention of a lore entity, with the raw
+/// value of its id.
+pub const MENTION_ACTIVATED: &str = "mention-activated";
+/// Signal emitted after an entity is added to the lore from the buffer, with the raw value of its
+/// id.
+pub const ENTITY_ADDED: &str = "entity-added";
+
+/// A lint finding placed in the buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferDiagnostic {
+    /// Character offset where the finding starts.
+    pub start: i32,
+    /// Character offset where the finding ends.
+    pub end: i32,
+    pub severity: Severity,
+    pub message: String,
+}
 
 /*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
 
@@ -27,6 +81,25 @@ mod imp {
     pub struct TWBuffer {
         /// To handle the tab size.
         pub tab_size: usize,
+        /// Statistics of every line, recounted only where the text changes.
+        pub statistics: RefCell<LineStatistics>,
+        /// To check the spelling of every edited line.
+        pub spell: RefCell<Option<SharedSpellChecker>>,
+        /// The lint findings underlined in the text.
+        pub diagnostics: RefCell<Vec<BufferDiagnostic>>,
+        /// To recognise the lore entities mentioned in the text.
+        pub lore: RefCell<Option<SharedLore>>,
+        /// Finds the names of the lore, rebuilt only when the lore changes.
+        pub finder: RefCell<MentionFinder>,
+        /// The mentions and unknown names of every line, searched again only where the text
+        /// changes.
+        pub mentions: RefCell<MentionIndex>,
+        /// Suggests lore names and remembers which were chosen.
+        pub completer: RefCell<Completer>,
+        /// How many edits that were not typed are running, such as a replacement or an undo.
+        pub untyped: Cell<u32>,
+        /// The paper size the document is compiled to, which the page estimate counts in.
+        pub paper: Cell<PaperSize>,
     }
 
     #[glib::object_subclass]
@@ -36,11 +109,39 @@ mod imp {
         type Type = super::TWBuffer;
 
         fn new() -> Self {
-            Self { tab_size: 4_usize }
+            Self {
+                tab_size: 4_usize,
+                statistics: RefCell::new(LineStatistics::default()),
+                spell: RefCell::new(None),
+                diagnostics: RefCell::new(Vec::new()),
+                lore: RefCell::new(None),
+                finder: RefCell::new(MentionFinder::default()),
+                mentions: RefCell::new(MentionIndex::default()),
+                completer: RefCell::new(Completer::new()),
+                untyped: Cell::new(0_u32),
+                paper: Cell::new(PaperSize::default()),
+            }
         }
     }
 
-    impl ObjectImpl for TWBuffer {}
+    impl ObjectImpl for TWBuffer {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder(TEXT_EDITED)
+                        .param_types([i64::static_type()])
+                        .build(),
+                    Signal::builder(MENTION_ACTIVATED)
+                        .param_types([u32::static_type()])
+                        .build(),
+                    Signal::builder(ENTITY_ADDED)
+                        .param_types([u32::static_type()])
+                        .build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+    }
 
     impl WidgetImpl for TWBuffer {}
 
@@ -48,7 +149,28 @@ mod imp {
         /// Called every time text is inserted to the text buffer. To convert the `tab` character
         /// with `spaces` of `tabsize` count.
         fn insert_text(&self, iter: &mut TextIter, new_text: &str) {
+            let first = iter.line();
             self.parent_insert_text(iter, &new_text.replace(TAB, &SPACE.repeat(self.tab_size)));
+            // The iter is moved to the end of the inserted text.
+            self.obj().recount(first, 1_usize, iter.line());
+        }
+
+        /// Called every time text is deleted from the text buffer. The lines in the range are
+        /// merged into the first one.
+        fn delete_range(&self, start: &mut TextIter, end: &mut TextIter) {
+            let (first, last) = (start.line(), end.line());
+            self.parent_delete_range(start, end);
+            self.obj()
+                .recount(first, (last - first) as usize + 1_usize, first);
+        }
+
+        /// The words an undo brings back or takes away were not typed.
+        fn undo(&self) {
+            self.obj().untyped(|| self.parent_undo());
+        }
+
+        fn redo(&self) {
+            self.obj().untyped(|| self.parent_redo());
         }
     }
 }
@@ -58,6 +180,431 @@ glib::wrapper! {
         @extends gtk::Widget, gtk::TextBuffer;
 }
 
+impl TWBuffer {
+    /// To make the edits of `edit` in a single user action whose words are not counted as typed,
+    /// such as replacing every match or renaming a lore entity.
+    pub fn untyped_action(&self, edit: impl FnOnce()) {
+        self.untyped(|| {
+            self.begin_user_action();
+            edit();
+            self.end_user_action();
+        });
+    }
+
+    /// To make the edits of `edit` without counting their words as typed.
+    fn untyped(&self, edit: impl FnOnce()) {
+        let untyped = &self.imp().untyped;
+        untyped.set(untyped.get() + 1_u32);
+        edit();
+        untyped.set(untyped.get() - 1_u32);
+    }
+
+    /// Returns the paper size the document is compiled to.
+    pub fn paper(&self) -> PaperSize {
+        self.imp().paper.get()
+    }
+
+    /// To set the paper size the document is compiled to.
+    pub fn set_paper(&self, paper: PaperSize) {
+        self.imp().paper.set(paper);
+    }
+
+    /// Returns the statistics of the whole text.
+    pub fn statistics(&self) -> Statistics {
+        self.imp().statistics.borrow().total()
+    }
+
+    /// Returns the text of `line` without the line break.
+    pub fn line_text(&self, line: i32) -> String {
+        let Some(start) = self.iter_at_line(line) else {
+            return String::new();
+        };
+        let mut end = start;
+        if !end.ends_line() {
+            end.forward_to_line_end();
+        }
+        self.slice(&start, &end, true).to_string()
+    }
+
+    /// To replace the counts of the `removed` lines starting at `first` with the lines
+    /// `first..=last` of the current text. Annotations are not counted, but the mentions they
+    /// make are.
+    fn recount(&self, first: i32, removed: usize, last: i32) {
+        let lines = (first..=last)
+            .map(|line| self.line_text(line))
+            .collect::<Vec<String>>();
+        let stripped = lines
+            .iter()
+            .map(|line| strip_annotations(line))
+            .collect::<Vec<String>>();
+        let lines = lines.iter().map(String::as_str).collect::<Vec<&str>>();
+        let stripped = stripped.iter().map(String::as_str).collect::<Vec<&str>>();
+        let before = self.statistics().words as i64;
+        self.imp()
+            .statistics
+            .borrow_mut()
+            .splice(first as usize, removed, &stripped);
+        self.imp().mentions.borrow_mut().splice(
+            &self.imp().finder.borrow(),
+            first as usize,
+            removed,
+            &lines,
+        );
+
+        let words = match self.imp().untyped.get() {
+            0_u32 => self.statistics().words as i64 - before,
+            _ => 0_i64,
+        };
+        self.check_lines(first, last);
+        self.tag_mentions(first, last);
+        self.tag_annotations(first, last);
+        self.emit_by_name::<()>(TEXT_EDITED, &[&words]);
+    }
+
+    /// To check the spelling with `spell` from now on.
+    pub fn set_spell_checker(&self, spell: &SharedSpellChecker) {
+        self.imp().spell.replace(Some(spell.clone()));
+        self.check_spelling();
+    }
+
+    pub fn spell_checker(&self) -> Option<SharedSpellChecker> {
+        self.imp().spell.borrow().clone()
+    }
+
+    /// To check the spelling of the whole text again, such as after a word is accepted.
+    pub fn check_spelling(&self) {
+        self.check_lines(0_i32, self.line_count() - 1_i32);
+    }
+
+    /// To underline the misspelled words of the lines `first..=last`, leaving out annotations.
+    fn check_lines(&self, first: i32, last: i32) {
+        let Some(spell) = self.spell_checker() else { return };
+        let spell = spell.borrow();
+        let tag = self.misspelled_tag();
+
+        for line in first..=last {
+            let Some(start) = self.iter_at_line(line) else { continue };
+            let mut end = start;
+            if !end.ends_line() {
+                end.forward_to_line_end();
+            }
+            self.remove_tag(&tag, &start, &end);
+
+            let text = hide_annotations(self.slice(&start, &end, true).as_str());
+            for range in spell.misspelled(text.as_str()) {
+                let offset = start.offset() + text[..range.start].chars().count() as i32;
+                let length = text[range].chars().count() as i32;
+                self.apply_tag(
+                    &tag,
+                    &self.iter_at_offset(offset),
+                    &self.iter_at_offset(offset + length),
+                );
+            }
+        }
+    }
+
+    /// To recognise the entities of `lore` in the text from now on.
+    pub fn set_lore(&self, lore: &SharedLore) {
+        self.imp().lore.replace(Some(lore.clone()));
+        self.refresh_mentions();
+    }
+
+    /// To search the whole text for mentions again, such as after the lore changed.
+    pub fn refresh_mentions(&self) {
+        let Some(lore) = self.imp().lore.borrow().clone() else { return };
+        let finder = MentionFinder::new(&lore.borrow());
+        let (start, end) = self.bounds();
+        let text = self.slice(&start, &end, true);
+
+        self.imp()
+            .mentions
+            .replace(MentionIndex::new(&finder, text.as_str()));
+        self.imp().finder.replace(finder);
+        self.tag_mentions(0_i32, self.line_count() - 1_i32);
+    }
+
+    /// To mark the mentions and unknown names of the lines `first..=last`.
+    fn tag_mentions(&self, first: i32, last: i32) {
+        let (mention_tag, unknown_tag) = (self.mention_tag(), self.unknown_name_tag());
+        let index = self.imp().mentions.borrow();
+
+        for line in first..=last {
+            let Some(start) = self.iter_at_line(line) else { continue };
+            let mut end = start;
+            if !end.ends_line() {
+                end.forward_to_line_end();
+            }
+            self.remove_tag(&mention_tag, &start, &end);
+            self.remove_tag(&unknown_tag, &start, &end);
+            let Some(found) = index.line(line as usize) else { continue };
+
+            let text = self.slice(&start, &end, true);
+            let ranges = found
+                .mentions
+                .iter()
+                .map(|mention| (&mention_tag, mention.full_range()))
+                .chain(found.unknown.iter().map(|name| (&unknown_tag, name.clone())));
+            for (tag, range) in ranges {
+                let range = char_range(text.as_str(), range);
+                self.apply_tag(
+                    tag,
+                    &self.iter_at_offset(start.offset() + range.start),
+                    &self.iter_at_offset(start.offset() + range.end),
+                );
+            }
+        }
+    }
+
+    /// To dim the parts of the annotations of the lines `first..=last` that are left out of the
+    /// story.
+    fn tag_annotations(&self, first: i32, last: i32) {
+        let tag = self.annotation_tag();
+
+        for line in first..=last {
+            let Some(start) = self.iter_at_line(line) else { continue };
+            let mut end = start;
+            if !end.ends_line() {
+                end.forward_to_line_end();
+            }
+            self.remove_tag(&tag, &start, &end);
+
+            let text = self.slice(&start, &end, true);
+            for range in annotations(text.as_str())
+                .iter()
+                .flat_map(Annotation::hidden)
+            {
+                let range = char_range(text.as_str(), range);
+                self.apply_tag(
+                    &tag,
+                    &self.iter_at_offset(start.offset() + range.start),
+                    &self.iter_at_offset(start.offset() + range.end),
+                );
+            }
+        }
+    }
+
+    /// Returns the lore entity mentioned at `iter`, if there is one.
+    pub fn entity_at(&self, iter: &TextIter) -> Option<EntityId> {
+        let line = self.line_text(iter.line());
+        let column = iter.line_offset();
+        let index = self.imp().mentions.borrow();
+
+        index
+            .line(iter.line() as usize)?
+            .mentions
+            .iter()
+            .find(|mention| char_range(line.as_str(), mention.full_range()).contains(&column))
+            .map(|mention| mention.entity)
+    }
+
+    /// Returns the bounds of the unknown name at `iter`, if there is one.
+    pub fn unknown_name_at(&self, iter: &TextIter) -> Option<(TextIter, TextIter)> {
+        let line = self.line_text(iter.line());
+        let column = iter.line_offset();
+        let index = self.imp().mentions.borrow();
+
+        let range = index
+            .line(iter.line() as usize)?
+            .unknown
+            .iter()
+            .map(|name| char_range(line.as_str(), name.clone()))
+            .find(|range| (range.start..=range.end).contains(&column))?;
+        let start = iter.offset() - column;
+        Some((
+            self.iter_at_offset(start + range.start),
+            self.iter_at_offset(start + range.end),
+        ))
+    }
+
+    /// Returns the lore names that complete `prefix`, typed on `line`.
+    pub fn complete(&self, prefix: &str, line: i32) -> Vec<Completion> {
+        let Some(lore) = self.imp().lore.borrow().clone() else { return Vec::new() };
+        let lore = lore.borrow();
+        self.imp().completer.borrow().complete(
+            &lore,
+            prefix,
+            &self.imp().mentions.borrow(),
+            line as usize,
+            COMPLETIONS,
+        )
+    }
+
+    /// To rank `entity` first the next time names are completed.
+    pub fn accept_completion(&self, entity: EntityId) {
+        self.imp().completer.borrow_mut().accepted(entity);
+    }
+
+    /// To add an entity of `kind` named `name` to the lore and emit [`ENTITY_ADDED`].
+    pub fn add_entity(&self, kind: EntityKind, name: &str) {
+        let Some(lore) = self.imp().lore.borrow().clone() else { return };
+        let added = lore.borrow_mut().add(kind, name);
+        match added {
+            Ok(id) => self.emit_by_name::<()>(ENTITY_ADDED, &[&id.value()]),
+            Err(err) => error!("{err}"),
+        }
+    }
+
+    /// Returns the bounds of the misspelled word at `iter`, if there is one.
+    pub fn misspelled_word_at(&self, iter: &TextIter) -> Option<(TextIter, TextIter)> {
+        let tag = self.misspelled_tag();
+        if !iter.has_tag(&tag) && !iter.ends_tag(Some(&tag)) {
+            return None;
+        }
+
+        let line = self.line_text(iter.line());
+        let column = iter.line_offset() as usize;
+        let range = word_ranges(line.as_str()).into_iter().find(|range| {
+            let start = line[..range.start].chars().count();
+            let end = start + line[range.clone()].chars().count();
+            (start..=end).contains(&column)
+        })?;
+
+        let start = iter.offset() - column as i32 + line[..range.start].chars().count() as i32;
+        let end = start + line[range].chars().count() as i32;
+        Some((self.iter_at_offset(start), self.iter_at_offset(end)))
+    }
+
+    /// Returns the [`TextTag`] used to underline misspelled words, adding it to the tag table on
+    /// first use.
+    pub fn misspelled_tag(&self) -> TextTag {
+        let table = self.tag_table();
+        if let Some(tag) = table.lookup(MISSPELLED_TAG) {
+            return tag;
+        }
+
+        let tag = TextTag::builder()
+            .name(MISSPELLED_TAG)
+            .underline(pango::Underline::Error)
+            .build();
+        table.add(&tag);
+        tag
+    }
+
+    /// To underline `diagnostics` in place of the previous lint findings.
+    pub fn set_diagnostics(&self, diagnostics: Vec<BufferDiagnostic>) {
+        let (start, end) = self.bounds();
+        for severity in Severity::ALL {
+            self.remove_tag(&self.lint_tag(severity), &start, &end);
+        }
+        for diagnostic in diagnostics.iter() {
+            self.apply_tag(
+                &self.lint_tag(diagnostic.severity),
+                &self.iter_at_offset(diagnostic.start),
+                &self.iter_at_offset(diagnostic.end),
+            );
+        }
+        self.imp().diagnostics.replace(diagnostics);
+    }
+
+    /// Returns the lint findings that cover `iter`.
+    pub fn diagnostics_at(&self, iter: &TextIter) -> Vec<BufferDiagnostic> {
+        let offset = iter.offset();
+        self.imp()
+            .diagnostics
+            .borrow()
+            .iter()
+            .filter(|diagnostic| (diagnostic.start..diagnostic.end).contains(&offset))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the [`TextTag`] used to underline lint findings of `severity`, adding it to the
+    /// tag table on first use.
+    fn lint_tag(&self, severity: Severity) -> TextTag {
+        let name = format!("{LINT_TAG}{severity}");
+        let table = self.tag_table();
+        if let Some(tag) = table.lookup(name.as_str()) {
+            return tag;
+        }
+
+        let (underline, colour) = match severity {
+            Severity::Hint => (pango::Underline::Single, "#61AFEF"),
+            Severity::Warning => (pango::Underline::Error, "#E5C07B"),
+            Severity::Error => (pango::Underline::Error, "#E06C75"),
+        };
+        let tag = TextTag::builder()
+            .name(name.as_str())
+            .underline(underline)
+            .underline_rgba(&RGBA::parse(colour).expect("Lint colours should be valid"))
+            .build();
+        table.add(&tag);
+        tag
+    }
+
+    /// Returns the [`TextTag`] used to mark mentions of lore entities, adding it to the tag table
+    /// on first use.
+    fn mention_tag(&self) -> TextTag {
+        let table = self.tag_table();
+        if let Some(tag) = table.lookup(MENTION_TAG) {
+            return tag;
+        }
+
+        let tag = TextTag::builder()
+            .name(MENTION_TAG)
+            .underline(pango::Underline::Single)
+            .underline_rgba(&RGBA::parse("#98C379").expect("Mention colour should be valid"))
+            .build();
+        table.add(&tag);
+        tag
+    }
+
+    /// Returns the [`TextTag`] used to mark names missing from the lore, adding it to the tag
+    /// table on first use.
+    fn unknown_name_tag(&self) -> TextTag {
+        let table = self.tag_table();
+        if let Some(tag) = table.lookup(UNKNOWN_NAME_TAG) {
+            return tag;
+        }
+
+        let tag = TextTag::builder()
+            .name(UNKNOWN_NAME_TAG)
+            .underline(pango::Underline::Low)
+            .underline_rgba(&RGBA::parse("#7F848E").expect("Unknown name colour should be valid"))
+            .build();
+        table.add(&tag);
+        tag
+    }
+
+    /// Returns the [`TextTag`] used to dim annotations, adding it to the tag table on first use.
+    fn annotation_tag(&self) -> TextTag {
+        let table = self.tag_table();
+        if let Some(tag) = table.lookup(ANNOTATION_TAG) {
+            return tag;
+        }
+
+        let tag = TextTag::builder()
+            .name(ANNOTATION_TAG)
+            .foreground("#7F848E")
+            .style(pango::Style::Italic)
+            .scale(0.8_f64)
+            .build();
+        table.add(&tag);
+        tag
+    }
+
+    /// Returns the [`TextTag`] used to highlight search matches, adding it to the tag table on
+    /// first use.
+    pub fn search_tag(&self) -> TextTag {
+        let table = self.tag_table();
+        if let Some(tag) = table.lookup(SEARCH_TAG) {
+            return tag;
+        }
+
+        let tag = TextTag::builder()
+            .name(SEARCH_TAG)
+            .background("#E5C07B")
+            .build();
+        table.add(&tag);
+        tag
+    }
+}
+
+/// Returns the character offsets of the byte `range` of `text`.
+fn char_range(text: &str, range: Range<usize>) -> Range<i32> {
+    let start = text[..range.start].chars().count() as i32;
+    start..start + text[range].chars().count() as i32
+}
+
 impl Default for TWBuffer {
     fn default() -> Self {
         Object::new::<Self>()
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Completion module.
+//!
+//! The [`TWCompletion`] popover that lists lore names completing the word typed in a
+//! [`TWPage`]. The keyboard focus stays on the page so that typing goes on while it is shown.
+//! [`TWPage`] : [`crate::ui::page::TWPage`]
+
+use gtk::{
+    gdk::Rectangle, glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object,
+    glib::*, prelude::*, subclass::prelude::*, CompositeTemplate, Label, ListBox, Popover,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::Completion;
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::default::Default;
+
+/// Signal emitted when a completion is chosen with the pointer, with its index.
+pub const COMPLETION_CHOSEN: &str = "completion-chosen";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/completion.ui")]
+    /// The completion popover struct.
+    pub struct TWCompletion {
+        /// A row for every completion.
+        #[template_child]
+        pub list: TemplateChild<ListBox>,
+        /// The completions listed, in the order of the rows.
+        pub completions: RefCell<Vec<Completion>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWCompletion {
+        const NAME: &'static str = "TWCompletion";
+        type ParentType = Popover;
+        type Type = super::TWCompletion;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWCompletion {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder(COMPLETION_CHOSEN)
+                    .param_types([u32::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.list
+                .connect_row_activated(clone!(@weak self as completion => move |_, row| {
+                    completion
+                        .obj()
+                        .emit_by_name::<()>(COMPLETION_CHOSEN, &[&(row.index() as u32)]);
+                }));
+        }
+    }
+
+    impl WidgetImpl for TWCompletion {}
+
+    impl PopoverImpl for TWCompletion {}
+}
+
+glib::wrapper! {
+    pub struct TWCompletion(ObjectSubclass<imp::TWCompletion>)
+        @extends gtk::Widget, gtk::Popover;
+}
+
+impl TWCompletion {
+    pub fn new() -> Self {
+        Object::new::<Self>()
+    }
+
+    /// To list `completions` with the first one selected and show them under `cursor`. The
+    /// popover is hidden if there are none.
+    pub fn show_completions(&self, completions: Vec<Completion>, cursor: &Rectangle) {
+        let list = &self.imp().list;
+        while let Some(row) = list.row_at_index(0_i32) {
+            list.remove(&row);
+        }
+        if completions.is_empty() {
+            self.imp().completions.replace(completions);
+            self.popdown();
+            return;
+        }
+
+        for completion in completions.iter() {
+            let markup = match completion.text == completion.name {
+                true => markup_escape_text(completion.text.as_str()).to_string(),
+                false => format!(
+                    "{}\x20<small>({})</small>",
+                    markup_escape_text(completion.text.as_str()),
+                    markup_escape_text(completion.name.as_str())
+                ),
+            };
+            let label = Label::new(None);
+            label.set_markup(markup.as_str());
+            label.set_xalign(0_f32);
+            list.append(&label);
+        }
+        list.select_row(list.row_at_index(0_i32).as_ref());
+        self.imp().completions.replace(completions);
+
+        self.set_pointing_to(Some(cursor));
+        self.popup();
+    }
+
+    /// Returns the completion at `index`.
+    pub fn completion(&self, index: usize) -> Option<Completion> {
+        self.imp().completions.borrow().get(index).cloned()
+    }
+
+    /// Returns the selected completion.
+    pub fn selected(&self) -> Option<Completion> {
+        let row = self.imp().list.selected_row()?;
+        self.completion(row.index() as usize)
+    }
+
+    /// To select the completion `offset` rows away from the selected one, wrapping around.
+    pub fn move_selection(&self, offset: i32) {
+        let list = &self.imp().list;
+        let count = self.imp().completions.borrow().len() as i32;
+        if count == 0_i32 {
+            return;
+        }
+        let current = list.selected_row().map_or(0_i32, |row| row.index());
+        let next = (current + offset).rem_euclid(count);
+        list.select_row(list.row_at_index(next).as_ref());
+    }
+}
+
+impl Default for TWCompletion {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWCompletion object")
+    }
+}
@@ -45,8 +45,11 @@ const ISO_DIMENSIONS: [(f64, f64); 9] = [
     // A8
     (2.0_f64, 2.9_f64),
 ];
+/// Words that fit on a square inch of a manuscript page. About 250 words on a Letter page.
+const WORDS_PER_SQUARE_INCH: f64 = 250_f64 / (8.5_f64 * 11_f64);
 /// North American Standard paper sizes.
 #[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NASDimensions {
     Letter,
     Legal,
@@ -55,6 +58,7 @@ pub enum NASDimensions {
 }
 /// International ISO A-Series paper sizes.
 #[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ISODimensions {
     A0,
     A1,
@@ -66,6 +70,12 @@ pub enum ISODimensions {
     A7,
     A8,
 }
+/// The paper size a document is compiled to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperSize {
+    NAS(NASDimensions),
+    ISO(ISODimensions),
+}
 
 /*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
 
@@ -81,6 +91,12 @@ impl Default for ISODimensions {
     }
 }
 
+impl Default for PaperSize {
+    fn default() -> Self {
+        Self::ISO(ISODimensions::default())
+    }
+}
+
 impl Default for Pixels {
     fn default() -> Self {
         ISODimensions::default().get().with_resolution()
@@ -90,7 +106,17 @@ impl Default for Pixels {
 impl ISODimensions {
     /// To get the [`ISODimensions`] value in [`Pixels`].
     pub fn get(&self) -> Pixels {
-        let (height, width) = match self {
+        let (height, width) = self.inches();
+
+        Pixels {
+            height: height as i32,
+            width: width as i32,
+        }
+    }
+
+    /// To get the [`ISODimensions`] value in inches.
+    pub fn inches(&self) -> (f64, f64) {
+        match self {
             ISODimensions::A0 => ISO_DIMENSIONS[0],
             ISODimensions::A1 => ISO_DIMENSIONS[1],
             ISODimensions::A2 => ISO_DIMENSIONS[2],
@@ -100,11 +126,6 @@ impl ISODimensions {
             ISODimensions::A6 => ISO_DIMENSIONS[6],
             ISODimensions::A7 => ISO_DIMENSIONS[7],
             ISODimensions::A8 => ISO_DIMENSIONS[8],
-        };
-
-        Pixels {
-            height: height as i32,
-            width: width as i32,
         }
     }
 }
@@ -113,18 +134,75 @@ impl NASDimensions {
     /// To get [`NASDimensions`] in [`Pixels`].
     #[allow(unused)]
     pub fn get(&self) -> Pixels {
-        let (height, width) = match self {
+        let (height, width) = self.inches();
+
+        Pixels {
+            height: height as i32,
+            width: width as i32,
+        }
+    }
+
+    /// To get [`NASDimensions`] in inches.
+    pub fn inches(&self) -> (f64, f64) {
+        match self {
             NASDimensions::Letter => NAS_DIMENSIONS[0],
             NASDimensions::Legal => NAS_DIMENSIONS[1],
             NASDimensions::Tabloid => NAS_DIMENSIONS[2],
             NASDimensions::Ledger => NAS_DIMENSIONS[3],
-        };
+        }
+    }
+}
 
-        Pixels {
-            height: height as i32,
-            width: width as i32,
+impl PaperSize {
+    /// Every paper size a document can be compiled to, in the order they are offered.
+    pub const ALL: [PaperSize; 13_usize] = [
+        PaperSize::NAS(NASDimensions::Letter),
+        PaperSize::NAS(NASDimensions::Legal),
+        PaperSize::NAS(NASDimensions::Tabloid),
+        PaperSize::NAS(NASDimensions::Ledger),
+        PaperSize::ISO(ISODimensions::A0),
+        PaperSize::ISO(ISODimensions::A1),
+        PaperSize::ISO(ISODimensions::A2),
+        PaperSize::ISO(ISODimensions::A3),
+        PaperSize::ISO(ISODimensions::A4),
+        PaperSize::ISO(ISODimensions::A5),
+        PaperSize::ISO(ISODimensions::A6),
+        PaperSize::ISO(ISODimensions::A7),
+        PaperSize::ISO(ISODimensions::A8),
+    ];
+
+    /// Returns the name of the paper size, as shown in menus.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaperSize::NAS(NASDimensions::Letter) => "Letter",
+            PaperSize::NAS(NASDimensions::Legal) => "Legal",
+            PaperSize::NAS(NASDimensions::Tabloid) => "Tabloid",
+            PaperSize::NAS(NASDimensions::Ledger) => "Ledger",
+            PaperSize::ISO(ISODimensions::A0) => "A0",
+            PaperSize::ISO(ISODimensions::A1) => "A1",
+            PaperSize::ISO(ISODimensions::A2) => "A2",
+            PaperSize::ISO(ISODimensions::A3) => "A3",
+            PaperSize::ISO(ISODimensions::A4) => "A4",
+            PaperSize::ISO(ISODimensions::A5) => "A5",
+            PaperSize::ISO(ISODimensions::A6) => "A6",
+            PaperSize::ISO(ISODimensions::A7) => "A7",
+            PaperSize::ISO(ISODimensions::A8) => "A8",
         }
     }
+
+    /// Returns the paper size named `name`, if there is one.
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|paper| paper.as_str() == name)
+    }
+
+    /// To estimate how many words of manuscript text fit on a page of this size.
+    pub fn words_per_page(&self) -> f64 {
+        let (height, width) = match self {
+            PaperSize::NAS(size) => size.inches(),
+            PaperSize::ISO(size) => size.inches(),
+        };
+        height * width * WORDS_PER_SQUARE_INCH
+    }
 }
 
 impl Pixels {
@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Readability module.
+//!
+//! Creates and returns a [`TWReadabilityWindow`] that reports readability scores for every
+//! chapter and scene of the opened documents and exports them as CSV.
+
+use crate::ui::{export::export_text, window::TWApplicationWindow};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, Grid, Label, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::cell::RefCell;
+use std::default::Default;
+use syntax::readability::{report, to_csv, ReportRow};
+
+/// Headings of the report columns.
+const COLUMNS: [&str; 8_usize] = [
+    "Chapter / Scene",
+    "Words",
+    "Words per sentence",
+    "Reading ease",
+    "Grade",
+    "Fog",
+    "Dialogue",
+    "Diversity",
+];
+/// File name suggested when exporting.
+const EXPORT_NAME: &str = "readability.csv";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/readability.ui")]
+    /// The readability report window struct.
+    pub struct TWReadabilityWindow {
+        /// A row of scores for every chapter and scene.
+        #[template_child]
+        pub table: TemplateChild<Grid>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        #[template_child]
+        pub export: TemplateChild<Button>,
+        /// The rows shown, kept for the export.
+        pub rows: RefCell<Vec<ReportRow>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWReadabilityWindow {
+        const NAME: &'static str = "TWReadabilityWindow";
+        type ParentType = Window;
+        type Type = super::TWReadabilityWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWReadabilityWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.export
+                .connect_clicked(clone!(@weak self as report => move |_| report.obj().export()));
+        }
+    }
+
+    impl WidgetImpl for TWReadabilityWindow {}
+
+    impl WindowImpl for TWReadabilityWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWReadabilityWindow(ObjectSubclass<imp::TWReadabilityWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWReadabilityWindow {
+    /// Returns a new instance of [`TWReadabilityWindow`] with the report of the documents opened
+    /// in `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let readability = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+
+        let (tree, _) = window.imp().tabs.document_tree();
+        readability.show_rows(report(&tree));
+
+        readability
+    }
+
+    /// To fill the table with `rows`. Chapter rows are bold and their scenes are indented below
+    /// them.
+    fn show_rows(&self, rows: Vec<ReportRow>) {
+        let table = &self.imp().table;
+        for (column, heading) in COLUMNS.iter().enumerate() {
+            let label = Self::cell(format!("<b>{heading}</b>").as_str(), column);
+            table.attach(&label, column as i32, 0_i32, 1_i32, 1_i32);
+        }
+
+        for (index, row) in rows.iter().enumerate() {
+            let readability = &row.readability;
+            let title = match row.scene.as_ref() {
+                Some(scene) => format!("\x20\x20\x20\x20{}", markup_escape_text(scene)),
+                None => format!("<b>{}</b>", markup_escape_text(row.chapter.as_str())),
+            };
+            let cells = [
+                title,
+                readability.words.to_string(),
+                format!("{:.1}", readability.average_sentence_length()),
+                format!("{:.1}", readability.flesch_reading_ease()),
+                format!("{:.1}", readability.flesch_kincaid_grade()),
+                format!("{:.1}", readability.gunning_fog()),
+                format!("{:.0}%", readability.dialogue_share() * 100_f64),
+                format!("{:.2}", readability.lexical_diversity()),
+            ];
+            for (column, cell) in cells.iter().enumerate() {
+                let label = Self::cell(cell.as_str(), column);
+                table.attach(&label, column as i32, index as i32 + 1_i32, 1_i32, 1_i32);
+            }
+        }
+
+        self.imp().export.set_sensitive(!rows.is_empty());
+        self.imp().rows.replace(rows);
+    }
+
+    /// Returns a label for a table cell with `markup`. Numbers are aligned to the right.
+    fn cell(markup: &str, column: usize) -> Label {
+        let label = Label::new(None);
+        label.set_markup(markup);
+        label.set_xalign(if column == 0_usize { 0_f32 } else { 1_f32 });
+        label
+    }
+
+    /// To ask where to export the report and write it there as CSV.
+    fn export(&self) {
+        let rows = self.imp().rows.borrow().clone();
+        export_text(
+            self,
+            "Export Readability Report",
+            EXPORT_NAME,
+            &self.imp().status,
+            move || to_csv(&rows),
+        );
+    }
+}
+
+impl Default for TWReadabilityWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWReadabilityWindow object")
+    }
+}
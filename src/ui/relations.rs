@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Relations module.
+//!
+//! Creates and returns a [`TWRelationWindow`] that draws the relationships between the lore
+//! entities of a window and lists the ones that cannot hold. Clicking an entity or a problem opens
+//! the card of the entity in the window, and the graph follows the changes made there.
+
+mod graph;
+
+use crate::ui::{lore::LORE_CHANGED, page::SharedLore, window::TWApplicationWindow};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, CompositeTemplate, DrawingArea, DropDown, Entry, GestureClick, Label,
+    ListBox, StringList, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::{check, EntityId, Inconsistency, RelationGraph, RelationKind, StoryTime};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::default::Default;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/relations.ui")]
+    /// The relations window struct.
+    pub struct TWRelationWindow {
+        /// The kind of relationships shown, all of them first.
+        #[template_child]
+        pub kind: TemplateChild<DropDown>,
+        #[template_child]
+        pub kinds: TemplateChild<StringList>,
+        /// The time the shown relationships hold at.
+        #[template_child]
+        pub at: TemplateChild<Entry>,
+        #[template_child]
+        pub graph: TemplateChild<DrawingArea>,
+        /// A row for every inconsistency.
+        #[template_child]
+        pub issues: TemplateChild<ListBox>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        /// The lore of the window.
+        pub lore: RefCell<Option<SharedLore>>,
+        /// The window that opens the cards.
+        pub window: WeakRef<TWApplicationWindow>,
+        /// The graph drawn.
+        pub layout: RefCell<RelationGraph>,
+        /// The inconsistencies listed, in the order of the rows.
+        pub inconsistencies: RefCell<Vec<Inconsistency>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWRelationWindow {
+        const NAME: &'static str = "TWRelationWindow";
+        type ParentType = Window;
+        type Type = super::TWRelationWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWRelationWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            RelationKind::ALL
+                .iter()
+                .for_each(|kind| self.kinds.append(kind.as_str()));
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWRelationWindow {}
+
+    impl WindowImpl for TWRelationWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWRelationWindow(ObjectSubclass<imp::TWRelationWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWRelationWindow {
+    /// Returns a new instance of [`TWRelationWindow`] for the lore of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let relations = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        relations.imp().lore.replace(Some(window.imp().lore.clone()));
+        relations.imp().window.set(Some(window));
+
+        window.imp().lore_sidebar.connect_local(
+            LORE_CHANGED,
+            false,
+            clone!(@weak relations => @default-return None, move |_| {
+                relations.refresh();
+                None
+            }),
+        );
+        relations.refresh();
+
+        relations
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.kind
+            .connect_selected_notify(clone!(@weak self as relations => move |_| {
+                relations.refresh();
+            }));
+        imp.at
+            .connect_changed(clone!(@weak self as relations => move |_| relations.refresh()));
+        imp.issues
+            .connect_row_activated(clone!(@weak self as relations => move |_, row| {
+                let entity = relations
+                    .imp()
+                    .inconsistencies
+                    .borrow()
+                    .get(row.index() as usize)
+                    .and_then(|inconsistency| inconsistency.entities().first().copied());
+                if let Some(entity) = entity {
+                    relations.show_entity(entity);
+                }
+            }));
+
+        imp.graph
+            .set_draw_func(clone!(@weak self as relations => move |_, cr, width, _| {
+                let imp = relations.imp();
+                let Some(lore) = imp.lore.borrow().clone() else { return };
+                let flagged = imp
+                    .inconsistencies
+                    .borrow()
+                    .iter()
+                    .flat_map(Inconsistency::entities)
+                    .collect::<BTreeSet<EntityId>>();
+                let layout = imp.layout.borrow();
+                if let Err(err) = graph::draw(cr, width, &layout, &lore.borrow(), &flagged) {
+                    warn!("Cannot draw the relationship graph: {err}");
+                }
+            }));
+        let click = GestureClick::new();
+        click.connect_released(clone!(@weak self as relations => move |_, _, x, y| {
+            let width = relations.imp().graph.width();
+            let entity = graph::entity_at(&relations.imp().layout.borrow(), width, x, y);
+            if let Some(entity) = entity {
+                relations.show_entity(entity);
+            }
+        }));
+        imp.graph.add_controller(click);
+    }
+
+    /// To lay out the graph again with the chosen kind and time, and check the lore.
+    fn refresh(&self) {
+        let imp = self.imp();
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let lore = lore.borrow();
+        let kind = (imp.kind.selected() as usize)
+            .checked_sub(1_usize)
+            .and_then(|index| RelationKind::ALL.get(index))
+            .copied();
+        let at = match imp.at.text().trim() {
+            "" => None,
+            text => match text.parse::<StoryTime>() {
+                Ok(at) => Some(at),
+                Err(_) => {
+                    imp.status.set_label("The time has to be a whole number.");
+                    return;
+                }
+            },
+        };
+
+        let layout = RelationGraph::new(&lore, kind, at);
+        let (width, height) = graph::size(&layout);
+        imp.graph.set_content_width(width);
+        imp.graph.set_content_height(height);
+        let status = match layout.edges.len() {
+            0_usize => "No relationships to show. They are added on the lore cards.".to_string(),
+            1_usize => "1 relationship.".to_string(),
+            count => format!("{count} relationships."),
+        };
+        imp.status.set_label(status.as_str());
+        imp.layout.replace(layout);
+        imp.graph.queue_draw();
+
+        while let Some(row) = imp.issues.row_at_index(0_i32) {
+            imp.issues.remove(&row);
+        }
+        let calendar = imp.window.upgrade().and_then(|window| window.calendar());
+        let inconsistencies = check(&lore, calendar.as_ref());
+        for inconsistency in inconsistencies.iter() {
+            let label = Label::new(Some(inconsistency.describe(&lore).as_str()));
+            label.set_xalign(0_f32);
+            label.set_wrap(true);
+            imp.issues.append(&label);
+        }
+        if inconsistencies.is_empty() {
+            let label = Label::new(Some("No inconsistencies found."));
+            label.set_xalign(0_f32);
+            imp.issues.append(&label);
+        }
+        imp.inconsistencies.replace(inconsistencies);
+    }
+
+    /// To open the card of `entity` in the window.
+    fn show_entity(&self, entity: EntityId) {
+        if let Some(window) = self.imp().window.upgrade() {
+            window.show_entity(entity);
+        }
+    }
+}
+
+impl Default for TWRelationWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWRelationWindow object")
+    }
+}
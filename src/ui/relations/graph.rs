@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Graph module.
+//!
+//! To draw a [`RelationGraph`] with a box for every entity and a line for every relation,
+//! coloured by its kind. Relations that only read one way, such as parentage, end in an arrow.
+//! Relations within a row arch above it.
+
+use gtk::cairo::{Context, Error, FontSlant, FontWeight};
+use lore::{EntityId, Registry, RelationGraph, RelationKind};
+use std::collections::BTreeSet;
+use std::f64::consts::PI;
+
+/// Size of the box of an entity in pixels.
+const NODE_WIDTH: f64 = 120_f64;
+const NODE_HEIGHT: f64 = 28_f64;
+/// Space taken by every entity in a row, and by every row.
+const COLUMN: f64 = 140_f64;
+const ROW: f64 = 90_f64;
+/// Space around the graph.
+const MARGIN: f64 = 20_f64;
+const FONT_SIZE: f64 = 12_f64;
+const ARROW: f64 = 8_f64;
+/// Colours of the boxes, their border, and the border of entities with an inconsistency.
+const NODE: (f64, f64, f64) = (0.93_f64, 0.93_f64, 0.90_f64);
+const BORDER: (f64, f64, f64) = (0.45_f64, 0.45_f64, 0.42_f64);
+const FLAGGED: (f64, f64, f64) = (0.88_f64, 0.42_f64, 0.46_f64);
+const TEXT: (f64, f64, f64) = (0.15_f64, 0.15_f64, 0.15_f64);
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// Returns the width and height needed to draw `graph`.
+pub fn size(graph: &RelationGraph) -> (i32, i32) {
+    let columns = graph.rows.iter().map(Vec::len).max().unwrap_or_default();
+    (
+        (columns as f64 * COLUMN + MARGIN * 2_f64) as i32,
+        (graph.rows.len() as f64 * ROW + MARGIN * 2_f64) as i32,
+    )
+}
+
+/// Returns the colour of the lines of relations of `kind`.
+pub fn colour(kind: RelationKind) -> (f64, f64, f64) {
+    match kind {
+        RelationKind::Related => (0.50_f64, 0.52_f64, 0.56_f64),
+        RelationKind::Parent => (0.38_f64, 0.69_f64, 0.94_f64),
+        RelationKind::Spouse => (0.78_f64, 0.47_f64, 0.87_f64),
+        RelationKind::Sibling => (0.34_f64, 0.71_f64, 0.76_f64),
+        RelationKind::Ally => (0.60_f64, 0.76_f64, 0.47_f64),
+        RelationKind::Enemy => (0.88_f64, 0.42_f64, 0.46_f64),
+        RelationKind::MemberOf => (0.90_f64, 0.75_f64, 0.48_f64),
+        RelationKind::PartOf => (0.64_f64, 0.55_f64, 0.44_f64),
+    }
+}
+
+/// Draws `graph` centred in `width`, with the names of `registry`. Entities in `flagged` get a
+/// border in a warning colour.
+pub fn draw(
+    cr: &Context,
+    width: i32,
+    graph: &RelationGraph,
+    registry: &Registry,
+    flagged: &BTreeSet<EntityId>,
+) -> Result<(), Error> {
+    cr.set_line_width(1.5_f64);
+    for edge in graph.edges.iter() {
+        let (Some(from), Some(to)) = (graph.position(edge.from), graph.position(edge.to)) else {
+            continue;
+        };
+        let (x1, y1) = centre(graph, width, from);
+        let (x2, y2) = centre(graph, width, to);
+        let (red, green, blue) = colour(edge.kind);
+        cr.set_source_rgb(red, green, blue);
+
+        // The point the line comes from as it reaches the target, for the arrow.
+        let (x0, y0, x2, y2) = if from.0 == to.0 {
+            let (y1, y2) = (y1 - NODE_HEIGHT / 2_f64, y2 - NODE_HEIGHT / 2_f64);
+            let lift = ROW / 3_f64;
+            cr.move_to(x1, y1);
+            cr.curve_to(x1, y1 - lift, x2, y2 - lift, x2, y2);
+            (x2, y2 - lift, x2, y2)
+        } else {
+            let side = if from.0 < to.0 { 1_f64 } else { -1_f64 };
+            let (y1, y2) = (y1 + side * NODE_HEIGHT / 2_f64, y2 - side * NODE_HEIGHT / 2_f64);
+            cr.move_to(x1, y1);
+            cr.line_to(x2, y2);
+            (x1, y1, x2, y2)
+        };
+        cr.stroke()?;
+
+        if !edge.kind.is_symmetric() {
+            let angle = (y2 - y0).atan2(x2 - x0);
+            cr.move_to(x2, y2);
+            cr.line_to(
+                x2 - ARROW * (angle - PI / 6_f64).cos(),
+                y2 - ARROW * (angle - PI / 6_f64).sin(),
+            );
+            cr.line_to(
+                x2 - ARROW * (angle + PI / 6_f64).cos(),
+                y2 - ARROW * (angle + PI / 6_f64).sin(),
+            );
+            cr.close_path();
+            cr.fill()?;
+        }
+    }
+
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(FONT_SIZE);
+    for (row, ids) in graph.rows.iter().enumerate() {
+        for (column, id) in ids.iter().enumerate() {
+            let (x, y) = centre(graph, width, (row, column));
+            let (left, top) = (x - NODE_WIDTH / 2_f64, y - NODE_HEIGHT / 2_f64);
+            cr.rectangle(left, top, NODE_WIDTH, NODE_HEIGHT);
+            cr.set_source_rgb(NODE.0, NODE.1, NODE.2);
+            cr.fill_preserve()?;
+            let (red, green, blue) = if flagged.contains(id) { FLAGGED } else { BORDER };
+            cr.set_source_rgb(red, green, blue);
+            cr.set_line_width(if flagged.contains(id) { 3_f64 } else { 1_f64 });
+            cr.stroke()?;
+
+            let name = registry
+                .get(*id)
+                .map(|entity| entity.name.as_str())
+                .unwrap_or_default();
+            let name = fit(cr, name, NODE_WIDTH - 10_f64)?;
+            let extents = cr.text_extents(name.as_str())?;
+            cr.set_source_rgb(TEXT.0, TEXT.1, TEXT.2);
+            cr.move_to(
+                x - extents.width() / 2_f64 - extents.x_bearing(),
+                y - extents.height() / 2_f64 - extents.y_bearing(),
+            );
+            cr.show_text(name.as_str())?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the entity drawn at `x` and `y` when `graph` is drawn in `width`.
+pub fn entity_at(graph: &RelationGraph, width: i32, x: f64, y: f64) -> Option<EntityId> {
+    graph.rows.iter().enumerate().find_map(|(row, ids)| {
+        ids.iter().enumerate().find_map(|(column, id)| {
+            let (centre_x, centre_y) = centre(graph, width, (row, column));
+            let inside = (x - centre_x).abs() <= NODE_WIDTH / 2_f64
+                && (y - centre_y).abs() <= NODE_HEIGHT / 2_f64;
+            inside.then_some(*id)
+        })
+    })
+}
+
+/// Returns the centre of the box at `row` and `column`. Every row is centred in `width`.
+fn centre(graph: &RelationGraph, width: i32, (row, column): (usize, usize)) -> (f64, f64) {
+    let columns = graph.rows.get(row).map(Vec::len).unwrap_or_default() as f64;
+    let left = ((width as f64 - columns * COLUMN) / 2_f64).max(MARGIN);
+    (
+        left + (column as f64 + 0.5_f64) * COLUMN,
+        MARGIN + row as f64 * ROW + NODE_HEIGHT / 2_f64,
+    )
+}
+
+/// Returns `name`, shortened with an ellipsis if it is wider than `width`.
+fn fit(cr: &Context, name: &str, width: f64) -> Result<String, Error> {
+    let mut fitted = name.to_string();
+    while cr.text_extents(fitted.as_str())?.width() > width && fitted.chars().count() > 1_usize {
+        fitted = fitted.chars().take(fitted.chars().count() - 2_usize).collect();
+        fitted.push('…');
+    }
+    Ok(fitted)
+}
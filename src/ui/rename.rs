@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Rename module.
+//!
+//! Creates and returns a [`TWRenameWindow`] to rename a lore entity along with every mention of it
+//! in the opened documents.
+
+use crate::ui::{page::TWBuffer, window::TWApplicationWindow};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*,
+    prelude::*, subclass::prelude::*, Button, CompositeTemplate, DropDown, Entry, Label, ListBox,
+    StringList, Window,
+};
+use lore::{EntityId, RenamePlan};
+#[allow(unused_imports)]
+use log::*;
+use std::cell::RefCell;
+use std::default::Default;
+use tree::{DocumentTree, NodeId};
+
+/// A change to apply to a buffer as the character range and the new text.
+type BufferChange = (i32, i32, String);
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/rename.ui")]
+    /// The rename window struct.
+    pub struct TWRenameWindow {
+        /// To choose the entity to rename.
+        #[template_child]
+        pub entity: TemplateChild<DropDown>,
+        /// Names of the entities in the [`DropDown`].
+        #[template_child]
+        pub entities: TemplateChild<StringList>,
+        #[template_child]
+        pub old_name: TemplateChild<Entry>,
+        #[template_child]
+        pub new_name: TemplateChild<Entry>,
+        /// To list every change before it is applied.
+        #[template_child]
+        pub preview: TemplateChild<ListBox>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        #[template_child]
+        pub apply: TemplateChild<Button>,
+        /// Ids of the entities in the same order as `entities`.
+        pub ids: RefCell<Vec<EntityId>>,
+        /// The plan that is previewed.
+        pub plan: RefCell<Option<RenamePlan>>,
+        /// The window whose documents and lore are renamed.
+        pub window: WeakRef<TWApplicationWindow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWRenameWindow {
+        const NAME: &'static str = "TWRenameWindow";
+        type ParentType = Window;
+        type Type = super::TWRenameWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWRenameWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWRenameWindow {}
+
+    impl WindowImpl for TWRenameWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWRenameWindow(ObjectSubclass<imp::TWRenameWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWRenameWindow {
+    /// Returns a new instance of [`TWRenameWindow`] for the lore and documents of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let rename = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        rename.imp().window.set(Some(window));
+        rename.load_entities();
+
+        rename
+    }
+
+    /// To select the entity with `id` in the [`DropDown`].
+    pub fn select(&self, id: EntityId) {
+        let ids = self.imp().ids.borrow().clone();
+        if let Some(index) = ids.iter().position(|known| *known == id) {
+            self.imp().entity.set_selected(index as u32);
+        }
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.entity.connect_selected_notify(clone!(@weak self as rename => move |_| {
+            rename.fill_old_name();
+            rename.refresh();
+        }));
+        imp.old_name
+            .connect_changed(clone!(@weak self as rename => move |_| rename.refresh()));
+        imp.new_name
+            .connect_changed(clone!(@weak self as rename => move |_| rename.refresh()));
+        imp.apply
+            .connect_clicked(clone!(@weak self as rename => move |_| rename.apply()));
+    }
+
+    /// To fill the [`DropDown`] with the entities of the lore registry.
+    fn load_entities(&self) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        let registry = window.imp().lore.borrow();
+
+        let mut ids = Vec::new();
+        for entity in registry.iter() {
+            self.imp()
+                .entities
+                .append(format!("{} ({})", entity.name, entity.kind).as_str());
+            ids.push(entity.id());
+        }
+        self.imp().ids.replace(ids);
+        self.fill_old_name();
+    }
+
+    /// Returns the id of the selected entity.
+    fn selected(&self) -> Option<EntityId> {
+        let index = self.imp().entity.selected() as usize;
+        self.imp().ids.borrow().get(index).copied()
+    }
+
+    /// To put the name of the selected entity in the old name [`Entry`].
+    fn fill_old_name(&self) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        let Some(id) = self.selected() else { return };
+        let Some(entity) = window.imp().lore.borrow().get(id).cloned() else { return };
+
+        self.imp().old_name.set_text(entity.name.as_str());
+    }
+
+    /// To plan the rename again and list every change in the preview.
+    fn refresh(&self) {
+        let imp = self.imp();
+        imp.plan.replace(None);
+        imp.apply.set_sensitive(false);
+        while let Some(row) = imp.preview.row_at_index(0_i32) {
+            imp.preview.remove(&row);
+        }
+
+        let Some(window) = imp.window.upgrade() else { return };
+        let Some(id) = self.selected() else {
+            imp.status.set_label("There are no lore entities to rename.");
+            return
+        };
+        let (tree, _) = window.imp().tabs.document_tree();
+        let plan = RenamePlan::new(
+            &window.imp().lore.borrow(),
+            &tree,
+            id,
+            imp.old_name.text().as_str(),
+            imp.new_name.text().as_str(),
+        );
+        let plan = match plan {
+            Ok(plan) => plan,
+            Err(err) => {
+                imp.status.set_label(err.to_string().as_str());
+                return
+            }
+        };
+
+        for preview in plan.preview(&tree) {
+            let chapter = tree
+                .chapter_of(preview.scene)
+                .and_then(|chapter| tree.node(chapter))
+                .map(|chapter| chapter.title.clone())
+                .unwrap_or_default();
+            let label = Label::new(Some(
+                format!("{chapter}:\x20{}\n\t→\x20{}", preview.before, preview.after).as_str(),
+            ));
+            label.set_xalign(0_f32);
+            imp.preview.append(&label);
+        }
+
+        imp.status
+            .set_label(format!("{} mentions will be renamed.", plan.edits.len()).as_str());
+        imp.apply.set_sensitive(true);
+        imp.plan.replace(Some(plan));
+    }
+
+    /// To apply the planned rename to the documents and the lore registry. The plan is checked
+    /// against the current text first so that nothing is changed if a document was edited after
+    /// the preview.
+    fn apply(&self) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        let Some(plan) = self.imp().plan.borrow().clone() else { return };
+        let (mut tree, chapters) = window.imp().tabs.document_tree();
+
+        // Offsets have to be taken before the tree text is changed.
+        let changes = chapters
+            .iter()
+            .map(|(chapter, buffer)| (buffer.clone(), Self::changes(&tree, &plan, *chapter)))
+            .collect::<Vec<(TWBuffer, Vec<BufferChange>)>>();
+
+        if let Err(err) = plan.apply(&mut tree, &mut window.imp().lore.borrow_mut()) {
+            self.imp().status.set_label(err.to_string().as_str());
+            return
+        }
+
+        for (buffer, changes) in changes.iter().filter(|(_, changes)| !changes.is_empty()) {
+            buffer.untyped_action(|| {
+                for (start, end, text) in changes.iter().rev() {
+                    let mut start = buffer.iter_at_offset(*start);
+                    let mut end = buffer.iter_at_offset(*end);
+                    buffer.delete(&mut start, &mut end);
+                    buffer.insert(&mut start, text.as_str());
+                }
+            });
+        }
+        window.sync_lore_names();
+        info!("Renamed {:?} to {:?} in {} places", plan.old, plan.new, plan.edits.len());
+        self.close();
+    }
+
+    /// Returns the buffer changes of `plan` that fall in `chapter`, in the order they appear.
+    fn changes(tree: &DocumentTree, plan: &RenamePlan, chapter: NodeId) -> Vec<BufferChange> {
+        let mut changes = plan
+            .edits
+            .iter()
+            .filter(|edit| tree.chapter_of(edit.node) == Some(chapter))
+            .filter_map(|edit| {
+                let node = tree.node(edit.node)?;
+                let chars = edit.chars(&node.text);
+                Some((
+                    (node.offset + chars.start) as i32,
+                    (node.offset + chars.end) as i32,
+                    edit.new.clone(),
+                ))
+            })
+            .collect::<Vec<BufferChange>>();
+        changes.sort_by_key(|(start, _, _)| *start);
+
+        changes
+    }
+}
+
+impl Default for TWRenameWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWRenameWindow object")
+    }
+}
@@ -0,0 +1,375 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Scenes module.
+//!
+//! Creates and returns a [`TWSceneWindow`] that lists the scenes of the opened documents with
+//! their point of view, date, setting and status. The list can be filtered and exported, and the
+//! metadata of the chosen scene is edited below it and written to the scene annotation of its
+//! text.
+
+use crate::ui::{export::export_text, page::TWBuffer, window::TWApplicationWindow};
+use format::scenes::{scenes_to_csv, write_scene_metadata};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, DropDown, Entry, Label, ListBox, StringList,
+    Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::cell::RefCell;
+use std::default::Default;
+use tree::{DocumentTree, NodeId, SceneFilter, SceneMetadata, SceneStatus};
+
+/// File name suggested when exporting.
+const EXPORT_NAME: &str = "scenes.csv";
+/// The choice of the status filter that picks every status.
+const ANY_STATUS: &str = "Any status";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/scenes.ui")]
+    /// The scenes window struct.
+    pub struct TWSceneWindow {
+        /// The values the listed scenes must have.
+        #[template_child]
+        pub pov_filter: TemplateChild<Entry>,
+        #[template_child]
+        pub location_filter: TemplateChild<Entry>,
+        #[template_child]
+        pub status_filter: TemplateChild<DropDown>,
+        #[template_child]
+        pub filter_statuses: TemplateChild<StringList>,
+        /// A row for every scene the filter picks.
+        #[template_child]
+        pub scenes: TemplateChild<ListBox>,
+        /// The chosen scene and its metadata.
+        #[template_child]
+        pub scene: TemplateChild<Label>,
+        #[template_child]
+        pub pov: TemplateChild<Entry>,
+        #[template_child]
+        pub date: TemplateChild<Entry>,
+        #[template_child]
+        pub location: TemplateChild<Entry>,
+        #[template_child]
+        pub status_choice: TemplateChild<DropDown>,
+        #[template_child]
+        pub statuses: TemplateChild<StringList>,
+        #[template_child]
+        pub reveal: TemplateChild<Button>,
+        #[template_child]
+        pub apply: TemplateChild<Button>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        #[template_child]
+        pub export: TemplateChild<Button>,
+        /// The window whose documents are listed.
+        pub window: WeakRef<TWApplicationWindow>,
+        /// The opened documents with their metadata, and the buffer of every chapter.
+        pub tree: RefCell<Option<DocumentTree>>,
+        pub chapters: RefCell<Vec<(NodeId, TWBuffer)>>,
+        /// The scene of every row.
+        pub listed: RefCell<Vec<NodeId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWSceneWindow {
+        const NAME: &'static str = "TWSceneWindow";
+        type ParentType = Window;
+        type Type = super::TWSceneWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWSceneWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWSceneWindow {}
+
+    impl WindowImpl for TWSceneWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWSceneWindow(ObjectSubclass<imp::TWSceneWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWSceneWindow {
+    /// Returns a new instance of [`TWSceneWindow`] for the documents of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let scenes = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        scenes.imp().window.set(Some(window));
+
+        let imp = scenes.imp();
+        let names = SceneStatus::ALL
+            .iter()
+            .map(SceneStatus::to_string)
+            .collect::<Vec<String>>();
+        let names = names.iter().map(String::as_str).collect::<Vec<&str>>();
+        imp.statuses.splice(0_u32, imp.statuses.n_items(), &names);
+        let choices = [ANY_STATUS].into_iter().chain(names).collect::<Vec<&str>>();
+        imp.filter_statuses
+            .splice(0_u32, imp.filter_statuses.n_items(), &choices);
+        imp.status_filter.set_selected(0_u32);
+
+        let (tree, chapters) = window.document_tree();
+        imp.tree.replace(Some(tree));
+        imp.chapters.replace(chapters);
+        scenes.list();
+
+        scenes
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        for filter in [&imp.pov_filter, &imp.location_filter] {
+            filter.connect_changed(clone!(@weak self as scenes => move |_| scenes.list()));
+        }
+        imp.status_filter
+            .connect_selected_notify(clone!(@weak self as scenes => move |_| scenes.list()));
+        imp.scenes
+            .connect_row_selected(clone!(@weak self as scenes => move |_, _| scenes.show_scene()));
+        for entry in [&imp.pov, &imp.date, &imp.location] {
+            entry.connect_activate(clone!(@weak self as scenes => move |_| scenes.apply()));
+        }
+        imp.apply
+            .connect_clicked(clone!(@weak self as scenes => move |_| scenes.apply()));
+        imp.reveal
+            .connect_clicked(clone!(@weak self as scenes => move |_| scenes.reveal()));
+        imp.export
+            .connect_clicked(clone!(@weak self as scenes => move |_| scenes.export()));
+    }
+
+    /// Returns the filter set above the list.
+    fn filter(&self) -> SceneFilter {
+        let imp = self.imp();
+        let text = |entry: &Entry| {
+            let text = entry.text().trim().to_string();
+            (!text.is_empty()).then_some(text)
+        };
+        SceneFilter {
+            pov: text(&imp.pov_filter),
+            location: text(&imp.location_filter),
+            status: (imp.status_filter.selected() as usize)
+                .checked_sub(1_usize)
+                .and_then(|index| SceneStatus::ALL.get(index).copied()),
+        }
+    }
+
+    /// Returns the scene of the chosen row.
+    fn chosen(&self) -> Option<NodeId> {
+        let imp = self.imp();
+        let row = imp.scenes.selected_row()?;
+        imp.listed.borrow().get(row.index() as usize).copied()
+    }
+
+    /// To list the scenes the filter picks, keeping the chosen one chosen.
+    fn list(&self) {
+        let imp = self.imp();
+        let chosen = self.chosen();
+        let tree = imp.tree.borrow();
+        let Some(tree) = tree.as_ref() else { return };
+        let listed = tree.scenes_where(&self.filter());
+
+        while let Some(row) = imp.scenes.row_at_index(0_i32) {
+            imp.scenes.remove(&row);
+        }
+        for scene in listed.iter() {
+            let label = Label::new(None);
+            label.set_markup(Self::describe(tree, *scene).as_str());
+            label.set_xalign(0_f32);
+            imp.scenes.append(&label);
+        }
+
+        let total = tree.scenes().len();
+        let status = match (listed.len(), total) {
+            (_, 0_usize) => "There are no scenes in the opened documents.".to_string(),
+            (shown, total) if shown == total => format!("{total} scenes."),
+            (shown, total) => format!("{shown} of {total} scenes."),
+        };
+        imp.status.set_label(status.as_str());
+        imp.export.set_sensitive(!listed.is_empty());
+
+        let index = chosen.and_then(|chosen| listed.iter().position(|scene| *scene == chosen));
+        imp.listed.replace(listed);
+        match index {
+            Some(index) => imp
+                .scenes
+                .select_row(imp.scenes.row_at_index(index as i32).as_ref()),
+            None => self.show_scene(),
+        }
+    }
+
+    /// Returns the markup of the row of `scene` of `tree`.
+    fn describe(tree: &DocumentTree, scene: NodeId) -> String {
+        let Some(node) = tree.node(scene) else {
+            return String::new();
+        };
+        let chapter = tree
+            .chapter_of(scene)
+            .and_then(|chapter| tree.node(chapter))
+            .map(|chapter| chapter.title.as_str())
+            .unwrap_or_default();
+        let metadata = &node.metadata;
+        let details = [&metadata.pov, &metadata.date, &metadata.location]
+            .into_iter()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| markup_escape_text(value).to_string())
+            .collect::<Vec<String>>();
+        let details = match details.is_empty() {
+            true => String::new(),
+            false => format!(" — {}", details.join(", ")),
+        };
+        format!(
+            "<b>{}: {}</b>{details}\x20<small>({})</small>",
+            markup_escape_text(chapter),
+            markup_escape_text(node.title.as_str()),
+            metadata.status
+        )
+    }
+
+    /// To show the metadata of the chosen scene to be edited.
+    fn show_scene(&self) {
+        let imp = self.imp();
+        let tree = imp.tree.borrow();
+        let node = self.chosen().and_then(|scene| tree.as_ref()?.node(scene));
+        let metadata = node.map(|node| node.metadata.clone()).unwrap_or_default();
+        let title = match node {
+            Some(node) => node.title.clone(),
+            None => "Choose a scene to edit its metadata.".to_string(),
+        };
+
+        imp.scene.set_label(title.as_str());
+        imp.pov.set_text(metadata.pov.as_str());
+        imp.date.set_text(metadata.date.as_str());
+        imp.location.set_text(metadata.location.as_str());
+        let status = SceneStatus::ALL
+            .iter()
+            .position(|status| *status == metadata.status)
+            .unwrap_or_default();
+        imp.status_choice.set_selected(status as u32);
+        for widget in [
+            imp.pov.upcast_ref::<gtk::Widget>(),
+            imp.date.upcast_ref(),
+            imp.location.upcast_ref(),
+            imp.status_choice.upcast_ref(),
+            imp.apply.upcast_ref(),
+            imp.reveal.upcast_ref(),
+        ] {
+            widget.set_sensitive(node.is_some());
+        }
+    }
+
+    /// To give the chosen scene the metadata being edited, writing it to the scene annotation of
+    /// its text.
+    fn apply(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let Some(scene) = self.chosen() else { return };
+        let metadata = SceneMetadata {
+            pov: imp.pov.text().trim().to_string(),
+            date: imp.date.text().trim().to_string(),
+            location: imp.location.text().trim().to_string(),
+            status: SceneStatus::ALL
+                .get(imp.status_choice.selected() as usize)
+                .copied()
+                .unwrap_or_default(),
+        };
+
+        {
+            let mut tree = imp.tree.borrow_mut();
+            let Some(tree) = tree.as_mut() else { return };
+            if let Err(err) = tree.set_metadata(scene, metadata) {
+                error!("{err}");
+                return;
+            }
+            let Some(edit) = write_scene_metadata(tree, scene) else { return };
+            let (Some(start), Some(end), Some(buffer)) = (
+                tree.source_offset(scene, edit.range.start),
+                tree.source_offset(scene, edit.range.end),
+                self.buffer_of(tree, scene),
+            ) else {
+                return;
+            };
+            buffer.untyped_action(|| {
+                let mut start = buffer.iter_at_offset(start as i32);
+                let mut end = buffer.iter_at_offset(end as i32);
+                buffer.delete(&mut start, &mut end);
+                buffer.insert(&mut start, edit.new.as_str());
+            });
+        }
+
+        // The offsets of the scenes after the edited one have moved.
+        let (tree, chapters) = window.document_tree();
+        imp.tree.replace(Some(tree));
+        imp.chapters.replace(chapters);
+        self.list();
+    }
+
+    /// Returns the buffer of the chapter that holds `scene` of `tree`.
+    fn buffer_of(&self, tree: &DocumentTree, scene: NodeId) -> Option<TWBuffer> {
+        let chapter = tree.chapter_of(scene)?;
+        self.imp()
+            .chapters
+            .borrow()
+            .iter()
+            .find(|(id, _)| *id == chapter)
+            .map(|(_, buffer)| buffer.clone())
+    }
+
+    /// To select the start of the chosen scene in its tab.
+    fn reveal(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let Some(scene) = self.chosen() else { return };
+        let tree = imp.tree.borrow();
+        let Some(tree) = tree.as_ref() else { return };
+        let Some(offset) = tree.source_offset(scene, 0_usize) else { return };
+        if let Some(buffer) = self.buffer_of(tree, scene) {
+            window
+                .imp()
+                .tabs
+                .reveal(&buffer, offset as i32, offset as i32);
+        }
+    }
+
+    /// To ask where to export the listed scenes and write them there as CSV.
+    fn export(&self) {
+        let imp = self.imp();
+        let Some(tree) = imp.tree.borrow().clone() else { return };
+        let listed = imp.listed.borrow().clone();
+        export_text(self, "Export Scenes", EXPORT_NAME, &imp.status, move || {
+            scenes_to_csv(&tree, &listed).unwrap_or_else(|err| {
+                error!("{err}");
+                String::new()
+            })
+        });
+    }
+}
+
+impl Default for TWSceneWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWSceneWindow object")
+    }
+}
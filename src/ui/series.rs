@@ -0,0 +1,569 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Series module.
+//!
+//! Creates and returns a [`TWSeriesWindow`] to make the project a book of a series that shares
+//! its lore with other books, to choose what the book keeps for itself and to list the problems
+//! the lore has in any book of the series.
+
+use crate::ui::{lore::LORE_CHANGED, window::TWApplicationWindow};
+use format::series::{Series, SeriesLink};
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*, prelude::*,
+    subclass::prelude::*, Button, CompositeTemplate, DropDown, Entry, FileChooserAction,
+    FileChooserNative, FileFilter, Label, ListBox, ResponseType, StringList, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::{EntityId, EntityOverride, Incoming, OverrideField, Registry};
+use std::cell::RefCell;
+use std::default::Default;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Name suggested for a new series file.
+const SERIES_NAME: &str = "series.toml";
+/// Name of a book that was not named.
+const FIRST_BOOK: &str = "Book 1";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/series.ui")]
+    /// The series window struct.
+    pub struct TWSeriesWindow {
+        /// The series and the book the project is.
+        #[template_child]
+        pub series: TemplateChild<Label>,
+        #[template_child]
+        pub create: TemplateChild<Button>,
+        #[template_child]
+        pub join: TemplateChild<Button>,
+        #[template_child]
+        pub leave: TemplateChild<Button>,
+        /// The name of the book the project is.
+        #[template_child]
+        pub book: TemplateChild<Entry>,
+        #[template_child]
+        pub switch: TemplateChild<Button>,
+        /// A row for every book of the series.
+        #[template_child]
+        pub books: TemplateChild<ListBox>,
+        /// The entity whose values may be kept.
+        #[template_child]
+        pub entity: TemplateChild<DropDown>,
+        #[template_child]
+        pub entities: TemplateChild<StringList>,
+        /// The value of the entity to keep or share.
+        #[template_child]
+        pub field: TemplateChild<DropDown>,
+        #[template_child]
+        pub fields: TemplateChild<StringList>,
+        #[template_child]
+        pub keep: TemplateChild<Button>,
+        #[template_child]
+        pub share: TemplateChild<Button>,
+        /// A row for every value the book keeps.
+        #[template_child]
+        pub overrides: TemplateChild<ListBox>,
+        /// A row for every problem of the lore of the series.
+        #[template_child]
+        pub issues: TemplateChild<ListBox>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        /// The window whose project is a book of the series.
+        pub window: WeakRef<TWApplicationWindow>,
+        /// The ids of the listed entities, then the listed fields of the chosen one.
+        pub entity_ids: RefCell<Vec<EntityId>>,
+        pub field_list: RefCell<Vec<OverrideField>>,
+        /// The names of the listed books.
+        pub book_names: RefCell<Vec<String>>,
+        /// The entity of every row of the kept values, then of every problem.
+        pub override_targets: RefCell<Vec<EntityId>>,
+        pub issue_targets: RefCell<Vec<Option<EntityId>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWSeriesWindow {
+        const NAME: &'static str = "TWSeriesWindow";
+        type ParentType = Window;
+        type Type = super::TWSeriesWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWSeriesWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWSeriesWindow {}
+
+    impl WindowImpl for TWSeriesWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWSeriesWindow(ObjectSubclass<imp::TWSeriesWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWSeriesWindow {
+    /// Returns a new instance of [`TWSeriesWindow`] for the project of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let series = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        series.imp().window.set(Some(window));
+        if let Some(link) = window.imp().project.borrow().series.as_ref() {
+            series.imp().book.set_text(link.book.as_str());
+        }
+
+        window.imp().lore_sidebar.connect_local(
+            LORE_CHANGED,
+            false,
+            clone!(@weak series => @default-return None, move |_| {
+                series.refresh();
+                None
+            }),
+        );
+        series.refresh();
+
+        series
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.create
+            .connect_clicked(clone!(@weak self as series => move |_| series.choose_file(true)));
+        imp.join
+            .connect_clicked(clone!(@weak self as series => move |_| series.choose_file(false)));
+        imp.leave
+            .connect_clicked(clone!(@weak self as series => move |_| series.leave()));
+        imp.switch
+            .connect_clicked(clone!(@weak self as series => move |_| series.switch_book()));
+        imp.book
+            .connect_activate(clone!(@weak self as series => move |_| series.switch_book()));
+        imp.books
+            .connect_row_activated(clone!(@weak self as series => move |_, row| {
+                let name = series.imp().book_names.borrow().get(row.index() as usize).cloned();
+                let Some(name) = name else { return };
+                series.imp().book.set_text(name.as_str());
+                series.switch_book();
+            }));
+        imp.entity
+            .connect_selected_notify(clone!(@weak self as series => move |_| {
+                series.list_fields();
+            }));
+        imp.keep
+            .connect_clicked(clone!(@weak self as series => move |_| series.edit_override(true)));
+        imp.share
+            .connect_clicked(clone!(@weak self as series => move |_| series.edit_override(false)));
+        imp.overrides
+            .connect_row_activated(clone!(@weak self as series => move |_, row| {
+                let entity = series
+                    .imp()
+                    .override_targets
+                    .borrow()
+                    .get(row.index() as usize)
+                    .copied();
+                series.show_entity(entity);
+            }));
+        imp.issues
+            .connect_row_activated(clone!(@weak self as series => move |_, row| {
+                let entity = series
+                    .imp()
+                    .issue_targets
+                    .borrow()
+                    .get(row.index() as usize)
+                    .copied()
+                    .flatten();
+                series.show_entity(entity);
+            }));
+    }
+
+    /// Returns where the series of the project is kept, and which book the project is.
+    fn link(&self) -> Option<SeriesLink> {
+        let window = self.imp().window.upgrade()?;
+        let link = window.imp().project.borrow().series.clone();
+        link
+    }
+
+    /// Returns the name of the book in the entry, or the name of the first book.
+    fn book_name(&self) -> String {
+        match self.imp().book.text().trim() {
+            "" => FIRST_BOOK.to_string(),
+            name => name.to_string(),
+        }
+    }
+
+    /// To open the card of `entity`, if there is one.
+    fn show_entity(&self, entity: Option<EntityId>) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        if let Some(entity) = entity {
+            window.show_entity(entity);
+        }
+    }
+
+    /// To ask where to start a new series if `create`, else for a series to join.
+    fn choose_file(&self, create: bool) {
+        let (title, action, accept) = match create {
+            true => ("New Series", FileChooserAction::Save, "_Create"),
+            false => ("Join Series", FileChooserAction::Open, "_Join"),
+        };
+        let chooser = FileChooserNative::new(
+            Some(title),
+            Some(self),
+            action,
+            Some(accept),
+            Some("_Cancel"),
+        );
+        let filter = FileFilter::new();
+        filter.set_name(Some("Series"));
+        filter.add_suffix("toml");
+        chooser.add_filter(&filter);
+        if create {
+            chooser.set_current_name(SERIES_NAME);
+        }
+
+        // The chooser has to be kept alive until it answers.
+        let kept = Rc::new(RefCell::new(Some(chooser.clone())));
+        chooser.connect_response(clone!(@weak self as series => move |chooser, response| {
+            kept.replace(None);
+            if response != ResponseType::Accept { return }
+            let Some(path) = chooser.file().and_then(|file| file.path()) else { return };
+
+            match create {
+                true => series.create(path),
+                false => series.join(path),
+            }
+        }));
+
+        chooser.show();
+    }
+
+    /// To start a series at `path` with the lore of the project as its first book.
+    fn create(&self, path: PathBuf) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let book = self.book_name();
+
+        let mut series = Series::new(name.as_str());
+        series.absorb(book.as_str(), &window.imp().lore.borrow(), &Registry::new());
+        if !self.enter(&window, series, SeriesLink { path, book }) {
+            return;
+        }
+        self.imp()
+            .status
+            .set_label("Started the series with the lore of this book.");
+    }
+
+    /// To join the series at `path`, merging the lore of the project into it.
+    fn join(&self, path: PathBuf) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let mut series = match Series::load(&path) {
+            Ok(series) => series,
+            Err(err) => {
+                error!("{}: {err}", path.display());
+                imp.status.set_label(err.to_string().as_str());
+                return;
+            }
+        };
+        let book = self.book_name();
+
+        let incoming = window
+            .imp()
+            .lore
+            .borrow()
+            .iter()
+            .cloned()
+            .map(Incoming::from)
+            .collect();
+        let report = series.lore.merge(incoming);
+        series.book_mut(book.as_str());
+        let message = format!(
+            "Joined {}: {} added, {} updated, {} values of the series kept over those of this \
+             book.",
+            series.name,
+            report.added.len(),
+            report.updated.len(),
+            report.conflicts.len(),
+        );
+        if !self.enter(&window, series, SeriesLink { path, book }) {
+            return;
+        }
+        imp.status.set_label(message.as_str());
+    }
+
+    /// To write `series` and make the project the book of `link` in it, showing its lore. The lore
+    /// is replaced before saving, as saving shares the lore of the book with the series. Returns
+    /// false if the series cannot be written.
+    fn enter(&self, window: &TWApplicationWindow, series: Series, link: SeriesLink) -> bool {
+        if let Err(err) = series.save(&link.path) {
+            error!("{}: {err}", link.path.display());
+            self.imp().status.set_label(err.to_string().as_str());
+            return false;
+        }
+        let lore = series.lore_of(link.book.as_str());
+        self.imp().book.set_text(link.book.as_str());
+        window.imp().project.borrow_mut().series = Some(link);
+        window.imp().series.replace(Some(series));
+        window.replace_series_lore(lore);
+        window.save_project();
+        self.refresh();
+        true
+    }
+
+    /// To stop sharing the lore with the series. The project keeps the lore of the book.
+    fn leave(&self) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        window.save_project();
+        window.imp().project.borrow_mut().series = None;
+        window.imp().series.replace(None);
+        window.save_project();
+        self.refresh();
+        self.imp()
+            .status
+            .set_label("Left the series, the lore is kept in this book only.");
+    }
+
+    /// To make the project the book named in the entry, saving what the current book changed.
+    fn switch_book(&self) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        let Some(mut link) = self.link() else { return };
+        window.save_project();
+        let Some(mut series) = window.imp().series.borrow().clone() else { return };
+
+        link.book = self.book_name();
+        let message = format!("Writing {} of {}.", link.book, series.name);
+        series.book_mut(link.book.as_str());
+        if !self.enter(&window, series, link) {
+            return;
+        }
+        self.imp().status.set_label(message.as_str());
+    }
+
+    /// To keep the chosen value of the chosen entity for this book if `keep`, else to share it
+    /// with the series again.
+    fn edit_override(&self, keep: bool) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let Some(link) = self.link() else { return };
+        let (entity_index, field_index) = (imp.entity.selected(), imp.field.selected());
+        let Some(id) = imp.entity_ids.borrow().get(entity_index as usize).copied() else {
+            return;
+        };
+        let Some(field) = imp.field_list.borrow().get(field_index as usize).cloned() else {
+            return;
+        };
+        let Some(entity) = window.imp().lore.borrow().get(id).cloned() else { return };
+        window.save_project();
+
+        let lore = {
+            let mut series = window.imp().series.borrow_mut();
+            let Some(series) = series.as_mut() else { return };
+            let overrides = &mut series.book_mut(link.book.as_str()).overrides;
+            let index = match overrides.iter().position(|kept| kept.entity == id) {
+                Some(index) => index,
+                None => {
+                    overrides.push(EntityOverride::new(id));
+                    overrides.len() - 1_usize
+                }
+            };
+            match keep {
+                true => overrides[index].keep(&entity, &field),
+                false => overrides[index].share(&field),
+            }
+            overrides.retain(|kept| !kept.is_empty());
+            series.lore_of(link.book.as_str())
+        };
+        window.replace_series_lore(lore);
+        window.save_project();
+
+        let message = match keep {
+            true => format!("{field} of {} is kept for this book.", entity.name),
+            false => format!("{field} of {} is shared with the series.", entity.name),
+        };
+        imp.status.set_label(message.as_str());
+    }
+
+    /// To show the series and list its books, the entities, the kept values and the problems.
+    fn refresh(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let link = self.link();
+        let series = window.imp().series.borrow().clone();
+
+        let label = match (link.as_ref(), series.as_ref()) {
+            (Some(link), Some(series)) => format!(
+                "{} of {}, kept in {}.",
+                link.book,
+                series.name,
+                link.path.display()
+            ),
+            (Some(link), None) => format!("The series at {} cannot be read.", link.path.display()),
+            (None, _) => "This book is not part of a series.".to_string(),
+        };
+        imp.series.set_label(label.as_str());
+        let joined = link.is_some() && series.is_some();
+        for widget in [&*imp.leave, &*imp.switch, &*imp.keep, &*imp.share] {
+            widget.set_sensitive(joined);
+        }
+
+        self.list_entities();
+        let (Some(link), Some(mut series)) = (link, series) else {
+            imp.book_names.replace(Vec::new());
+            Self::fill(&imp.books, Vec::new());
+            Self::fill(&imp.overrides, Vec::new());
+            Self::fill(&imp.issues, Vec::new());
+            return;
+        };
+        // The series is checked with the lore as this book has it now, saved or not.
+        let lore = window.imp().lore.borrow().clone();
+        series.absorb(
+            link.book.as_str(),
+            &lore,
+            &window.imp().series_lore.borrow(),
+        );
+
+        let names = series
+            .books
+            .iter()
+            .map(|book| book.name.clone())
+            .collect::<Vec<String>>();
+        Self::fill(
+            &imp.books,
+            names
+                .iter()
+                .map(|name| match *name == link.book {
+                    true => format!("{name} (this book)"),
+                    false => name.clone(),
+                })
+                .collect(),
+        );
+        imp.book_names.replace(names);
+
+        let mut rows = Vec::new();
+        let mut targets = Vec::new();
+        for kept in series.overrides(link.book.as_str()) {
+            let Some(entity) = lore.get(kept.entity) else { continue };
+            for field in kept.fields() {
+                rows.push(format!("{}: {field}", entity.name));
+                targets.push(kept.entity);
+            }
+        }
+        if rows.is_empty() {
+            rows.push("This book shares every value with the series.".to_string());
+        }
+        Self::fill(&imp.overrides, rows);
+        imp.override_targets.replace(targets);
+
+        let mut rows = Vec::new();
+        let mut targets = Vec::new();
+        for issue in series.check(window.calendar().as_ref()) {
+            let books = match issue.books.is_empty() {
+                true => "Every book".to_string(),
+                false => issue.books.join(", "),
+            };
+            rows.push(format!("{books}: {}", issue.inconsistency.describe(&lore)));
+            targets.push(issue.inconsistency.entities().first().copied());
+        }
+        if rows.is_empty() {
+            rows.push("No problems found.".to_string());
+            targets.push(None);
+        }
+        Self::fill(&imp.issues, rows);
+        imp.issue_targets.replace(targets);
+    }
+
+    /// To list the entities of the lore, keeping the chosen one chosen.
+    fn list_entities(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let chosen = imp
+            .entity_ids
+            .borrow()
+            .get(imp.entity.selected() as usize)
+            .copied();
+        let lore = window.imp().lore.borrow();
+        let ids = lore
+            .iter()
+            .map(|entity| entity.id())
+            .collect::<Vec<EntityId>>();
+        let names = lore
+            .iter()
+            .map(|entity| format!("{} ({})", entity.name, entity.kind))
+            .collect::<Vec<String>>();
+        drop(lore);
+
+        let index = chosen
+            .and_then(|chosen| ids.iter().position(|id| *id == chosen))
+            .unwrap_or_default();
+        imp.entity_ids.replace(ids);
+        imp.entities.splice(
+            0_u32,
+            imp.entities.n_items(),
+            &names.iter().map(String::as_str).collect::<Vec<&str>>(),
+        );
+        imp.entity.set_selected(index as u32);
+        self.list_fields();
+    }
+
+    /// To list the values the chosen entity has that the book could keep.
+    fn list_fields(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let id = imp.entity_ids.borrow().get(imp.entity.selected() as usize).copied();
+        let fields = id
+            .and_then(|id| window.imp().lore.borrow().get(id).map(OverrideField::of))
+            .unwrap_or_default();
+        let names = fields
+            .iter()
+            .map(OverrideField::to_string)
+            .collect::<Vec<String>>();
+        imp.field_list.replace(fields);
+        imp.fields.splice(
+            0_u32,
+            imp.fields.n_items(),
+            &names.iter().map(String::as_str).collect::<Vec<&str>>(),
+        );
+    }
+
+    /// To replace the rows of `list` with a label for each of `rows`.
+    fn fill(list: &ListBox, rows: Vec<String>) {
+        while let Some(row) = list.row_at_index(0_i32) {
+            list.remove(&row);
+        }
+        for text in rows {
+            let label = Label::new(Some(text.as_str()));
+            label.set_xalign(0_f32);
+            label.set_wrap(true);
+            list.append(&label);
+        }
+    }
+}
+
+impl Default for TWSeriesWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWSeriesWindow object")
+    }
+}
@@ -6,13 +6,18 @@
 //! To handle status bar to display [`TWPage`] status.
 //! ['TWPage'] : [`crate::ui::page::TWPage`]
 
+mod goals;
+mod statistics;
 mod wordcount;
 
 use gtk::{
     glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, prelude::*,
-    subclass::prelude::*, CompositeTemplate,
+    subclass::prelude::*, CompositeTemplate, Label,
 };
+pub use goals::{GoalsProgress, TWGoals};
+pub use statistics::TWStatistics;
 use std::default::Default;
+use syntax::statistics::Statistics;
 pub use wordcount::TWWordCount;
 
 /*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
@@ -28,6 +33,15 @@ mod imp {
         /// To keep track word count in [`TWPage`].
         #[template_child]
         pub word_count: TemplateChild<TWWordCount>,
+        /// To show the outcome of the last action such as an export.
+        #[template_child]
+        pub message: TemplateChild<Label>,
+        /// To show the progress towards the writing goals.
+        #[template_child]
+        pub goals: TemplateChild<TWGoals>,
+        /// To show the rest of the [`TWPage`] statistics.
+        #[template_child]
+        pub statistics: TemplateChild<TWStatistics>,
     }
 
     #[glib::object_subclass]
@@ -57,6 +71,31 @@ glib::wrapper! {
         @extends gtk::Widget, gtk::Box;
 }
 
+impl TWStatusBar {
+    /// To show the statistics of the active document. The word count of the selection is added
+    /// next to the document word count if there is one.
+    pub fn show_statistics(
+        &self,
+        document: &Statistics,
+        selection: Option<&Statistics>,
+        words_per_page: f64,
+    ) {
+        let words = match selection {
+            Some(selection) => format!("{} ({} selected)", document.words, selection.words),
+            None => format!("{}", document.words),
+        };
+        self.imp().word_count.imp().label.set_label(words.as_str());
+        self.imp()
+            .statistics
+            .show(selection.unwrap_or(document), words_per_page);
+    }
+
+    /// To show the progress towards the writing goals next to the word count.
+    pub fn show_goals(&self, progress: &GoalsProgress) {
+        self.imp().goals.show(progress);
+    }
+}
+
 impl Default for TWStatusBar {
     fn default() -> Self {
         Object::new::<Self>()
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Goals module.
+//!
+//! To show the progress towards the writing goals next to the word count. Goals without a target
+//! are hidden.
+
+use format::goals::Progress;
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, prelude::*,
+    subclass::prelude::*, CompositeTemplate, Label, LevelBar,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::default::Default;
+
+/// The progress of every goal shown by [`TWGoals`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GoalsProgress {
+    pub session: Option<Progress>,
+    pub daily: Option<Progress>,
+    pub project: Option<Progress>,
+    /// Words to write every day to meet the deadline.
+    pub per_day: Option<usize>,
+}
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/goals.ui")]
+    /// Struct to handle the goal progress labels.
+    pub struct TWGoals {
+        /// Fills up as the daily target is reached.
+        #[template_child]
+        pub daily_bar: TemplateChild<LevelBar>,
+        #[template_child]
+        pub session: TemplateChild<Label>,
+        #[template_child]
+        pub daily: TemplateChild<Label>,
+        #[template_child]
+        pub project: TemplateChild<Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWGoals {
+        const NAME: &'static str = "TWGoals";
+        type ParentType = gtk::Box;
+        type Type = super::TWGoals;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWGoals {}
+
+    impl WidgetImpl for TWGoals {}
+
+    impl BoxImpl for TWGoals {}
+}
+
+glib::wrapper! {
+    pub struct TWGoals(ObjectSubclass<imp::TWGoals>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl TWGoals {
+    /// To show `progress`, hiding the goals that have no target.
+    pub fn show(&self, progress: &GoalsProgress) {
+        let imp = self.imp();
+
+        Self::show_label(
+            &imp.session,
+            progress
+                .session
+                .map(|session| format!("Session: {}/{}", session.written, session.target)),
+        );
+        Self::show_label(
+            &imp.daily,
+            progress
+                .daily
+                .map(|daily| format!("Today: {}/{}", daily.written, daily.target)),
+        );
+        Self::show_label(
+            &imp.project,
+            progress.project.map(|project| {
+                let percent = project.fraction() * 100_f64;
+                match progress.per_day {
+                    Some(words) => format!("Project: {percent:.0}% ({words}/day)"),
+                    None => format!("Project: {percent:.0}%"),
+                }
+            }),
+        );
+
+        imp.daily_bar.set_visible(progress.daily.is_some());
+        if let Some(daily) = progress.daily {
+            imp.daily_bar.set_value(daily.fraction());
+        }
+    }
+
+    fn show_label(label: &Label, text: Option<String>) {
+        label.set_visible(text.is_some());
+        if let Some(text) = text {
+            label.set_label(text.as_str());
+        }
+    }
+}
+
+impl Default for TWGoals {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWGoals object")
+    }
+}
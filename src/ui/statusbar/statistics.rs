@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Statistics module.
+//!
+//! To handle the labels that show the document statistics next to the word count.
+
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, prelude::*,
+    subclass::prelude::*, CompositeTemplate, Label,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::default::Default;
+use syntax::statistics::Statistics;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/statistics.ui")]
+    /// Struct to handle the statistics labels.
+    pub struct TWStatistics {
+        /// Characters with and without spaces.
+        #[template_child]
+        pub characters: TemplateChild<Label>,
+        #[template_child]
+        pub paragraphs: TemplateChild<Label>,
+        /// Estimated pages of a standard manuscript.
+        #[template_child]
+        pub pages: TemplateChild<Label>,
+        /// Estimated reading time.
+        #[template_child]
+        pub reading: TemplateChild<Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWStatistics {
+        const NAME: &'static str = "TWStatistics";
+        type ParentType = gtk::Box;
+        type Type = super::TWStatistics;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWStatistics {}
+
+    impl WidgetImpl for TWStatistics {}
+
+    impl BoxImpl for TWStatistics {}
+}
+
+glib::wrapper! {
+    pub struct TWStatistics(ObjectSubclass<imp::TWStatistics>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl TWStatistics {
+    /// To show `statistics` with the page estimate for pages that hold `words_per_page`.
+    pub fn show(&self, statistics: &Statistics, words_per_page: f64) {
+        let imp = self.imp();
+        imp.characters.set_label(
+            format!(
+                "Characters: {} ({} without spaces)",
+                statistics.characters, statistics.characters_no_spaces,
+            )
+            .as_str(),
+        );
+        imp.paragraphs
+            .set_label(format!("Paragraphs: {}", statistics.paragraphs).as_str());
+        imp.pages
+            .set_label(format!("Pages: {:.1}", statistics.pages(words_per_page)).as_str());
+        imp.reading.set_label(
+            format!("Reading time: {:.0} min", statistics.reading_minutes().ceil()).as_str(),
+        );
+    }
+}
+
+impl Default for TWStatistics {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWStatistics object")
+    }
+}
@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Tab view module.
+//!
+//! To handle multiple [`TWPage`] documents opened side by side as tabs. Only the active tab is
+//! attached to the [`TWStatusBar`]. Each tab is a [`TWSplitView`] that can
+//! show its document in a second pane.
+
+mod split;
+
+use crate::ui::{
+    page::{
+        SharedLore, SharedSpellChecker, TWBuffer, TWPage, ENTITY_ADDED, MENTION_ACTIVATED,
+        TEXT_EDITED,
+    },
+    statusbar::TWStatusBar,
+};
+use format::project::Document;
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, glib::*,
+    prelude::*, subclass::prelude::*, Button, CompositeTemplate, Label, Notebook, Orientation,
+    Widget,
+};
+#[allow(unused_imports)]
+use log::*;
+use once_cell::sync::Lazy;
+use std::cell::{Cell, RefCell};
+use std::default::Default;
+pub use split::TWSplitView;
+use tree::{DocumentTree, NodeId};
+
+/// Prefix for the title of documents that are not yet saved to a file.
+const UNTITLED: &str = "Untitled";
+/// Title of the book in the [`DocumentTree`] made from the opened documents.
+const MANUSCRIPT: &str = "Manuscript";
+/// Marker added to the tab title when the document has unsaved changes.
+const DIRTY_MARKER: &str = "\x20•";
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/tabview.ui")]
+    /// Struct that holds every opened [`TWPage`] as a tab.
+    pub struct TWTabView {
+        /// The [`Notebook`] that holds the tabs.
+        #[template_child]
+        pub notebook: TemplateChild<Notebook>,
+        /// Reference to the [`TWStatusBar`] to hand over to the active [`TWPage`].
+        pub statusbar: WeakRef<TWStatusBar>,
+        /// To number the untitled documents.
+        pub untitled: Cell<usize>,
+        /// The spell checker handed to the [`TWBuffer`] of every tab.
+        pub spell: RefCell<Option<SharedSpellChecker>>,
+        /// The lore handed to the [`TWBuffer`] of every tab.
+        pub lore: RefCell<Option<SharedLore>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWTabView {
+        const NAME: &'static str = "TWTabView";
+        type ParentType = gtk::Box;
+        type Type = super::TWTabView;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWTabView {
+        /// [`TEXT_EDITED`] is emitted again for the [`TWBuffer`] of every tab along with the
+        /// title of its document, and [`MENTION_ACTIVATED`] and [`ENTITY_ADDED`] as they are.
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder(TEXT_EDITED)
+                        .param_types([String::static_type(), i64::static_type()])
+                        .build(),
+                    Signal::builder(MENTION_ACTIVATED)
+                        .param_types([u32::static_type()])
+                        .build(),
+                    Signal::builder(ENTITY_ADDED)
+                        .param_types([u32::static_type()])
+                        .build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let tabview = self.obj().downgrade();
+            self.notebook.connect_switch_page(move |_, child, _| {
+                let Some(tabview) = tabview.upgrade() else { return };
+                tabview.activate_page(child);
+            });
+        }
+    }
+
+    impl WidgetImpl for TWTabView {}
+
+    impl BoxImpl for TWTabView {}
+}
+
+glib::wrapper! {
+    pub struct TWTabView(ObjectSubclass<imp::TWTabView>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl TWTabView {
+    /// To set the [`TWStatusBar`] that the active [`TWPage`] should update.
+    pub fn set_statusbar(&self, statusbar: &TWStatusBar) {
+        self.imp().statusbar.set(Some(statusbar));
+        if let Some(page) = self.current_page() {
+            page.attach_statusbar(statusbar);
+        }
+    }
+
+    /// To check the spelling of every opened document with `spell`.
+    pub fn set_spell_checker(&self, spell: &SharedSpellChecker) {
+        self.imp().spell.replace(Some(spell.clone()));
+        self.pages()
+            .iter()
+            .for_each(|page| page.tw_buffer().set_spell_checker(spell));
+    }
+
+    /// To recognise the entities of `lore` in every opened document.
+    pub fn set_lore(&self, lore: &SharedLore) {
+        self.imp().lore.replace(Some(lore.clone()));
+        self.pages()
+            .iter()
+            .for_each(|page| page.tw_buffer().set_lore(lore));
+    }
+
+    /// To search every opened document for mentions again, such as after the lore changed.
+    pub fn refresh_mentions(&self) {
+        self.pages()
+            .iter()
+            .for_each(|page| page.tw_buffer().refresh_mentions());
+    }
+
+    /// To check the spelling of every opened document again, such as after the accepted words
+    /// changed.
+    pub fn check_spelling(&self) {
+        self.pages()
+            .iter()
+            .for_each(|page| page.tw_buffer().check_spelling());
+    }
+
+    /// Opens a new untitled [`TWPage`] in a new tab, switches to it and returns it.
+    pub fn new_tab(&self) -> TWPage {
+        let untitled = self.imp().untitled.get() + 1_usize;
+        self.imp().untitled.set(untitled);
+
+        self.append_page(TWPage::new(), format!("{UNTITLED} {untitled}").as_str())
+    }
+
+    /// Adds the given [`TWPage`] as a new tab with `title`, switches to it and returns it.
+    pub fn append_page(&self, page: TWPage, title: &str) -> TWPage {
+        let notebook = &self.imp().notebook;
+
+        page.add_css_class("main-page");
+        page.set_document_title(title);
+        let split = TWSplitView::new(&page);
+        if let Some(spell) = self.imp().spell.borrow().as_ref() {
+            page.tw_buffer().set_spell_checker(spell);
+        }
+        if let Some(lore) = self.imp().lore.borrow().as_ref() {
+            page.tw_buffer().set_lore(lore);
+        }
+        for signal in [MENTION_ACTIVATED, ENTITY_ADDED] {
+            page.tw_buffer().connect_local(
+                signal,
+                false,
+                clone!(@weak self as tabview => @default-return None, move |values| {
+                    let entity = values[1_usize].get::<u32>().ok()?;
+                    tabview.emit_by_name::<()>(signal, &[&entity]);
+                    None
+                }),
+            );
+        }
+        page.tw_buffer().connect_local(
+            TEXT_EDITED,
+            false,
+            clone!(@weak self as tabview, @weak page => @default-return None, move |values| {
+                let words = values[1_usize].get::<i64>().ok()?;
+                tabview.emit_by_name::<()>(TEXT_EDITED, &[&page.document_title(), &words]);
+                None
+            }),
+        );
+
+        let label = self.tab_label(&split, &page, title);
+        let index = notebook.append_page(&split, Some(&label));
+        notebook.set_tab_reorderable(&split, true);
+        notebook.set_current_page(Some(index));
+        page.grab_focus();
+
+        page
+    }
+
+    /// To close the tab at `index`. The last tab is replaced by a new untitled document so that
+    /// there is always a page to type in.
+    pub fn close_tab(&self, index: u32) {
+        let notebook = &self.imp().notebook;
+        notebook.remove_page(Some(index));
+
+        if notebook.n_pages() == 0_u32 {
+            self.new_tab();
+        }
+    }
+
+    /// Returns every opened document in the tab order, to be saved with the project.
+    pub fn documents(&self) -> Vec<Document> {
+        self.pages()
+            .iter()
+            .map(|page| Document {
+                title: page.document_title(),
+                text: page.text(),
+            })
+            .collect()
+    }
+
+    /// To close every tab and open `documents` in their place. A new untitled document is opened
+    /// if there are none.
+    pub fn open_documents(&self, documents: &[Document]) {
+        let notebook = &self.imp().notebook;
+        while notebook.n_pages() > 0_u32 {
+            notebook.remove_page(None);
+        }
+
+        for document in documents {
+            let page = self.append_page(TWPage::new(), document.title.as_str());
+            let buffer = page.tw_buffer();
+            // Opening a document is neither typed nor undone.
+            buffer.untyped_action(|| {
+                buffer.begin_irreversible_action();
+                buffer.set_text(document.text.as_str());
+                buffer.end_irreversible_action();
+            });
+            buffer.place_cursor(&buffer.start_iter());
+            buffer.set_modified(false);
+        }
+        if notebook.n_pages() == 0_u32 {
+            self.new_tab();
+        }
+    }
+
+    /// To mark every opened document as saved.
+    pub fn mark_saved(&self) {
+        self.pages()
+            .iter()
+            .for_each(|page| page.buffer().set_modified(false));
+    }
+
+    /// Returns the [`TWPage`] of the currently active tab.
+    pub fn current_page(&self) -> Option<TWPage> {
+        self.current_split().and_then(|split| split.primary())
+    }
+
+    /// Returns the [`TWSplitView`] of the currently active tab.
+    pub fn current_split(&self) -> Option<TWSplitView> {
+        let notebook = &self.imp().notebook;
+        notebook
+            .current_page()
+            .and_then(|index| notebook.nth_page(Some(index)))
+            .and_then(|child| child.downcast::<TWSplitView>().ok())
+    }
+
+    /// Returns every opened [`TWPage`] in the tab order.
+    pub fn pages(&self) -> Vec<TWPage> {
+        let notebook = &self.imp().notebook;
+        (0_u32..notebook.n_pages())
+            .filter_map(|index| notebook.nth_page(Some(index)))
+            .filter_map(|child| Self::page_of(&child))
+            .collect()
+    }
+
+    /// Returns the number of words in every opened document.
+    pub fn total_words(&self) -> usize {
+        self.pages()
+            .iter()
+            .map(|page| page.tw_buffer().statistics().words)
+            .sum()
+    }
+
+    /// Returns a [`DocumentTree`] with every opened document as a chapter in tab order, along with
+    /// the [`TWBuffer`] each chapter was read from.
+    pub fn document_tree(&self) -> (DocumentTree, Vec<(NodeId, TWBuffer)>) {
+        let mut tree = DocumentTree::new(MANUSCRIPT);
+        let chapters = self
+            .pages()
+            .iter()
+            .map(|page| {
+                let chapter = tree.add_chapter_text(&page.document_title(), &page.text());
+                (chapter, page.tw_buffer())
+            })
+            .collect();
+
+        (tree, chapters)
+    }
+
+    /// To switch to the tab that shows `buffer`, select the characters `start..end` and scroll
+    /// them into view.
+    pub fn reveal(&self, buffer: &TWBuffer, start: i32, end: i32) {
+        let notebook = &self.imp().notebook;
+        let tab = (0_u32..notebook.n_pages()).find_map(|index| {
+            let page = Self::page_of(&notebook.nth_page(Some(index))?)?;
+            (page.tw_buffer() == *buffer).then_some((index, page))
+        });
+        let Some((index, page)) = tab else { return };
+
+        notebook.set_current_page(Some(index));
+        // Focusing the page restores its own cursor, so the selection has to come after.
+        page.grab_focus();
+        buffer.select_range(&buffer.iter_at_offset(start), &buffer.iter_at_offset(end));
+        page.scroll_to_mark(&buffer.get_insert(), 0_f64, true, 0.5_f64, 0.5_f64);
+    }
+
+    /// To get the primary [`TWPage`] held inside a tab child.
+    fn page_of(child: &Widget) -> Option<TWPage> {
+        child
+            .downcast_ref::<TWSplitView>()
+            .and_then(|split| split.primary())
+    }
+
+    /// Attaches the status bar to the [`TWPage`] in `child` and detaches it from the rest.
+    fn activate_page(&self, child: &Widget) {
+        let Some(active) = Self::page_of(child) else {
+            warn!("Switched to a tab that does not hold a TWPage");
+            return
+        };
+        self.pages()
+            .iter()
+            .filter(|page| **page != active)
+            .for_each(|page| page.detach_statusbar());
+        // Words may have been accepted in another tab.
+        active.tw_buffer().check_spelling();
+
+        let Some(statusbar) = self.imp().statusbar.upgrade() else { return };
+        active.attach_statusbar(&statusbar);
+    }
+
+    /// Builds the tab label with a title that tracks the dirty state of the buffer and a close
+    /// button.
+    fn tab_label(&self, split: &TWSplitView, page: &TWPage, title: &str) -> gtk::Box {
+        let label = Label::new(Some(title));
+        let close = Button::from_icon_name("window-close-symbolic");
+        close.set_has_frame(false);
+
+        let title = title.to_string();
+        page.buffer()
+            .connect_modified_changed(clone!(@weak label => move |buffer| {
+                match buffer.is_modified() {
+                    true => label.set_label(format!("{title}{DIRTY_MARKER}").as_str()),
+                    false => label.set_label(title.as_str()),
+                }
+            }));
+
+        close.connect_clicked(clone!(@weak self as tabview, @weak split => move |_| {
+            let Some(index) = tabview.imp().notebook.page_num(&split) else { return };
+            tabview.close_tab(index);
+        }));
+
+        let tab = gtk::Box::new(Orientation::Horizontal, 4_i32);
+        tab.append(&label);
+        tab.append(&close);
+        tab
+    }
+}
+
+impl Default for TWTabView {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWTabView object")
+    }
+}
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Split view module.
+//!
+//! To show the same [`TWBuffer`] in two [`TWPage`] panes, each with its own scroll position and
+//! cursor.
+
+use crate::ui::page::TWPage;
+use gtk::{
+    glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, prelude::*,
+    subclass::prelude::*, CompositeTemplate, Orientation, Paned, PolicyType, ScrolledWindow,
+};
+#[allow(unused_imports)]
+use log::*;
+use std::default::Default;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/splitview.ui")]
+    /// Struct that holds the primary [`TWPage`] of a tab and the optional second pane.
+    pub struct TWSplitView {
+        /// The [`Paned`] that separates the two panes.
+        #[template_child]
+        pub paned: TemplateChild<Paned>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWSplitView {
+        const NAME: &'static str = "TWSplitView";
+        type ParentType = gtk::Box;
+        type Type = super::TWSplitView;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWSplitView {}
+
+    impl WidgetImpl for TWSplitView {}
+
+    impl BoxImpl for TWSplitView {}
+}
+
+glib::wrapper! {
+    pub struct TWSplitView(ObjectSubclass<imp::TWSplitView>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl TWSplitView {
+    /// Returns a new [`TWSplitView`] with `page` as the only pane.
+    pub fn new(page: &TWPage) -> Self {
+        let split = Object::new::<Self>();
+        split.imp().paned.set_start_child(Some(&Self::scrolled(page)));
+
+        split
+    }
+
+    /// Returns the [`TWPage`] that owns the document of this tab.
+    pub fn primary(&self) -> Option<TWPage> {
+        Self::page_in(self.imp().paned.start_child())
+    }
+
+    /// Returns the second [`TWPage`] if the view is split.
+    pub fn secondary(&self) -> Option<TWPage> {
+        Self::page_in(self.imp().paned.end_child())
+    }
+
+    /// Returns the [`TWPage`] that has the keyboard focus, or the primary one if neither has.
+    pub fn focused(&self) -> Option<TWPage> {
+        self.secondary()
+            .filter(|page| page.has_focus())
+            .or_else(|| self.primary())
+    }
+
+    /// To split the view into two panes viewing the same buffer. The panes are placed side by
+    /// side for [`Orientation::Horizontal`] and stacked for [`Orientation::Vertical`]. If the
+    /// view is already split then only the orientation is changed.
+    pub fn split(&self, orientation: Orientation) {
+        let paned = &self.imp().paned;
+        paned.set_orientation(orientation);
+        if self.secondary().is_some() { return }
+
+        let Some(primary) = self.primary() else {
+            error!("Cannot split a TWSplitView without a primary TWPage");
+            return
+        };
+        let secondary = TWPage::with_buffer(&primary.tw_buffer());
+        secondary.add_css_class("main-page");
+        paned.set_end_child(Some(&Self::scrolled(&secondary)));
+
+        let size = match orientation {
+            Orientation::Horizontal => self.width(),
+            _ => self.height(),
+        };
+        paned.set_position(size / 2_i32);
+        secondary.grab_focus();
+    }
+
+    /// To close the second pane and give the focus back to the primary [`TWPage`].
+    pub fn unsplit(&self) {
+        self.imp().paned.set_end_child(None::<&gtk::Widget>);
+        if let Some(primary) = self.primary() {
+            primary.grab_focus();
+        }
+    }
+
+    /// To move the keyboard focus to the other pane.
+    pub fn focus_next(&self) {
+        let (Some(primary), Some(secondary)) = (self.primary(), self.secondary()) else { return };
+        match primary.has_focus() {
+            true => secondary.grab_focus(),
+            false => primary.grab_focus(),
+        };
+    }
+
+    /// To wrap a [`TWPage`] in its own [`ScrolledWindow`] so that panes scroll independently.
+    fn scrolled(page: &TWPage) -> ScrolledWindow {
+        ScrolledWindow::builder()
+            .has_frame(true)
+            .hexpand(true)
+            .vexpand(true)
+            .hscrollbar_policy(PolicyType::Never)
+            .min_content_height(250_i32)
+            .min_content_width(500_i32)
+            .child(page)
+            .build()
+    }
+
+    /// To get the [`TWPage`] inside a pane.
+    fn page_in(child: Option<gtk::Widget>) -> Option<TWPage> {
+        child
+            .and_then(|scrolled| scrolled.downcast::<ScrolledWindow>().ok())
+            .and_then(|scrolled| scrolled.child())
+            .and_then(|page| page.downcast::<TWPage>().ok())
+    }
+}
+
+impl Default for TWSplitView {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWSplitView object")
+    }
+}
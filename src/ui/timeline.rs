@@ -0,0 +1,427 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Timeline module.
+//!
+//! Creates and returns a [`TWTimelineWindow`] that draws the dated lore events and scenes of a
+//! window in chronological order, with a lane for every character and location. Clicking an event
+//! shows where it happens in the manuscript, or opens its card if it is not mentioned. Journeys
+//! between scenes that are too fast for the routes between their locations, and attributes that
+//! do not hold at the date of their scene, are listed below.
+
+mod chart;
+
+use crate::ui::{
+    lore::LORE_CHANGED,
+    page::{SharedLore, TWBuffer},
+    window::TWApplicationWindow,
+};
+use gtk::{
+    gdk::ModifierType, glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object,
+    glib::*, prelude::*, subclass::prelude::*, Button, CompositeTemplate, DrawingArea, DropDown,
+    EventControllerScroll, EventControllerScrollFlags, GestureClick, Label, ListBox,
+    ScrolledWindow, StringList, Window,
+};
+#[allow(unused_imports)]
+use log::*;
+use lore::{check_attributes, check_travel, Calendar, Timeline, TimelineSource};
+use std::cell::{Cell, RefCell};
+use std::default::Default;
+use std::ops::Range;
+use tree::{DocumentTree, NodeId};
+
+/// Name of the choice that shows plain day counts.
+const DAY_NUMBERS: &str = "Day numbers";
+/// Bounds of the zoom in pixels per day, and the step of a zoom.
+const MIN_ZOOM: f64 = 0.0001_f64;
+const MAX_ZOOM: f64 = 64_f64;
+const ZOOM_STEP: f64 = 2_f64;
+
+/// The text shown for an event, if it is mentioned.
+type Target = Option<(TWBuffer, i32, i32)>;
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/github/feohr/ToadWriter/timeline.ui")]
+    /// The timeline window struct.
+    pub struct TWTimelineWindow {
+        /// The calendars of the project, then day numbers.
+        #[template_child]
+        pub calendar: TemplateChild<DropDown>,
+        #[template_child]
+        pub calendars: TemplateChild<StringList>,
+        #[template_child]
+        pub zoom_out: TemplateChild<Button>,
+        #[template_child]
+        pub zoom_in: TemplateChild<Button>,
+        #[template_child]
+        pub fit: TemplateChild<Button>,
+        #[template_child]
+        pub scroll: TemplateChild<ScrolledWindow>,
+        #[template_child]
+        pub chart: TemplateChild<DrawingArea>,
+        /// A row for every journey that is too fast, then every contradicted attribute.
+        #[template_child]
+        pub issues: TemplateChild<ListBox>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        /// The lore of the window.
+        pub lore: RefCell<Option<SharedLore>>,
+        /// The window whose documents and calendars are shown.
+        pub window: WeakRef<TWApplicationWindow>,
+        /// The timeline drawn.
+        pub timeline: RefCell<Timeline>,
+        /// Where every event of the timeline is shown, in the same order.
+        pub targets: RefCell<Vec<Target>>,
+        /// The text of every issue, in the order of the rows.
+        pub issue_targets: RefCell<Vec<Target>>,
+        /// Pixels per day.
+        pub zoom: Cell<f64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TWTimelineWindow {
+        const NAME: &'static str = "TWTimelineWindow";
+        type ParentType = Window;
+        type Type = super::TWTimelineWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TWTimelineWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.zoom.set(1_f64);
+            self.obj().connect_signals();
+        }
+    }
+
+    impl WidgetImpl for TWTimelineWindow {}
+
+    impl WindowImpl for TWTimelineWindow {}
+}
+
+glib::wrapper! {
+    pub struct TWTimelineWindow(ObjectSubclass<imp::TWTimelineWindow>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Buildable;
+}
+
+impl TWTimelineWindow {
+    /// Returns a new instance of [`TWTimelineWindow`] for the lore and documents of `window`.
+    pub fn new(window: &TWApplicationWindow) -> Self {
+        let timeline = Object::builder::<Self>()
+            .property("transient-for", window)
+            .build();
+        timeline.imp().lore.replace(Some(window.imp().lore.clone()));
+        timeline.imp().window.set(Some(window));
+
+        window.imp().lore_sidebar.connect_local(
+            LORE_CHANGED,
+            false,
+            clone!(@weak timeline => @default-return None, move |_| {
+                timeline.refresh();
+                None
+            }),
+        );
+        timeline.list_calendars();
+        timeline.fit();
+
+        timeline
+    }
+
+    fn connect_signals(&self) {
+        let imp = self.imp();
+
+        imp.calendar
+            .connect_selected_notify(clone!(@weak self as timeline => move |_| {
+                timeline.refresh();
+                timeline.fit();
+            }));
+        imp.zoom_in
+            .connect_clicked(clone!(@weak self as timeline => move |_| {
+                timeline.set_zoom(timeline.imp().zoom.get() * ZOOM_STEP);
+            }));
+        imp.zoom_out
+            .connect_clicked(clone!(@weak self as timeline => move |_| {
+                timeline.set_zoom(timeline.imp().zoom.get() / ZOOM_STEP);
+            }));
+        imp.fit
+            .connect_clicked(clone!(@weak self as timeline => move |_| timeline.fit()));
+        imp.issues
+            .connect_row_activated(clone!(@weak self as timeline => move |_, row| {
+                let target = timeline
+                    .imp()
+                    .issue_targets
+                    .borrow()
+                    .get(row.index() as usize)
+                    .cloned()
+                    .flatten();
+                timeline.reveal(target);
+            }));
+
+        imp.chart
+            .set_draw_func(clone!(@weak self as timeline => move |_, cr, width, _| {
+                let imp = timeline.imp();
+                let Some(lore) = imp.lore.borrow().clone() else { return };
+                let calendar = timeline.calendar();
+                let drawn = chart::draw(
+                    cr,
+                    width,
+                    &imp.timeline.borrow(),
+                    imp.zoom.get(),
+                    &lore.borrow(),
+                    calendar.as_ref(),
+                );
+                if let Err(err) = drawn {
+                    warn!("Cannot draw the timeline: {err}");
+                }
+            }));
+        imp.chart.connect_query_tooltip(
+            clone!(@weak self as timeline => @default-return false, move |_, x, y, _, tooltip| {
+                let imp = timeline.imp();
+                let shown = imp.timeline.borrow();
+                let Some(index) = chart::event_at(&shown, imp.zoom.get(), x as f64, y as f64)
+                else {
+                    return false;
+                };
+                let event = &shown.events[index];
+                let date = match timeline.calendar() {
+                    Some(calendar) => calendar.format(calendar.from_day(event.time)),
+                    None => format!("Day {}", event.time),
+                };
+                tooltip.set_text(Some(format!("{}\n{date}", event.title).as_str()));
+                true
+            }),
+        );
+
+        let click = GestureClick::new();
+        click.connect_released(clone!(@weak self as timeline => move |_, _, x, y| {
+            let zoom = timeline.imp().zoom.get();
+            let index = chart::event_at(&timeline.imp().timeline.borrow(), zoom, x, y);
+            if let Some(index) = index {
+                timeline.show_event(index);
+            }
+        }));
+        imp.chart.add_controller(click);
+
+        let scroll = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+        scroll.connect_scroll(
+            clone!(@weak self as timeline => @default-return Inhibit(false), move |scroll, _, dy| {
+                if !scroll.current_event_state().contains(ModifierType::CONTROL_MASK) {
+                    return Inhibit(false);
+                }
+                let zoom = timeline.imp().zoom.get();
+                match dy < 0_f64 {
+                    true => timeline.set_zoom(zoom * ZOOM_STEP),
+                    false => timeline.set_zoom(zoom / ZOOM_STEP),
+                }
+                Inhibit(true)
+            }),
+        );
+        imp.chart.add_controller(scroll);
+    }
+
+    /// Returns the calendar chosen in the [`DropDown`], or [`None`] for day numbers.
+    fn calendar(&self) -> Option<Calendar> {
+        let window = self.imp().window.upgrade()?;
+        let index = self.imp().calendar.selected() as usize;
+        let calendar = window.imp().project.borrow().calendars.get(index).cloned();
+        calendar
+    }
+
+    /// To list the calendars of the project and choose the first.
+    fn list_calendars(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let mut names = window
+            .imp()
+            .project
+            .borrow()
+            .calendars
+            .iter()
+            .map(|calendar| calendar.name.clone())
+            .collect::<Vec<String>>();
+        names.push(DAY_NUMBERS.to_string());
+
+        imp.calendars.splice(
+            0_u32,
+            imp.calendars.n_items(),
+            &names.iter().map(String::as_str).collect::<Vec<&str>>(),
+        );
+        imp.calendar.set_selected(0_u32);
+        self.refresh();
+    }
+
+    /// To build the timeline again from the lore and the opened documents.
+    fn refresh(&self) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let calendar = self.calendar();
+        let (tree, chapters) = window.document_tree();
+        let timeline = Timeline::new(&lore.borrow(), calendar.as_ref(), &tree);
+
+        let target = |(scene, range): (NodeId, Range<usize>)| {
+            let chapter = tree.chapter_of(scene)?;
+            let (_, buffer) = chapters.iter().find(|(id, _)| *id == chapter)?;
+            Some((
+                buffer.clone(),
+                tree.source_offset(scene, range.start)? as i32,
+                tree.source_offset(scene, range.end)? as i32,
+            ))
+        };
+        let targets = timeline
+            .events
+            .iter()
+            .map(|event| event.location.clone().and_then(target))
+            .collect::<Vec<Target>>();
+        self.list_issues(calendar.as_ref(), &tree, target);
+
+        let status = match (timeline.events.len(), calendar.is_some()) {
+            (0_usize, true) => {
+                "Nothing to show. Give events a date attribute, or write dates in the scenes."
+                    .to_string()
+            }
+            (0_usize, false) => {
+                "Nothing to show. Give events a date attribute with a day number.".to_string()
+            }
+            (1_usize, _) => "1 event.".to_string(),
+            (count, _) => format!("{count} events."),
+        };
+        imp.status.set_label(status.as_str());
+        imp.timeline.replace(timeline);
+        imp.targets.replace(targets);
+        self.resize();
+    }
+
+    /// To list the journeys between the scenes of `tree` that are too fast and the attributes
+    /// stated with a value they do not have yet or any more, read with `calendar`. `target` finds
+    /// the text of a problem.
+    fn list_issues(
+        &self,
+        calendar: Option<&Calendar>,
+        tree: &DocumentTree,
+        target: impl Fn((NodeId, Range<usize>)) -> Target,
+    ) {
+        let imp = self.imp();
+        while let Some(row) = imp.issues.row_at_index(0_i32) {
+            imp.issues.remove(&row);
+        }
+        let Some(lore) = imp.lore.borrow().clone() else { return };
+        let lore = lore.borrow();
+        let Some(calendar) = calendar else {
+            let label = Label::new(Some(
+                "Choose a calendar to check the scenes against the lore.",
+            ));
+            label.set_xalign(0_f32);
+            imp.issues.append(&label);
+            imp.issue_targets.replace(Vec::new());
+            return;
+        };
+
+        let journeys = check_travel(&lore, calendar, tree)
+            .into_iter()
+            .map(|journey| (journey.describe(&lore), (journey.arrival, journey.range)));
+        let contradictions =
+            check_attributes(&lore, calendar, tree)
+                .into_iter()
+                .map(|contradiction| {
+                    let text = contradiction.describe(&lore);
+                    (text, (contradiction.scene, contradiction.range))
+                });
+        let issues = journeys
+            .chain(contradictions)
+            .collect::<Vec<(String, (NodeId, Range<usize>))>>();
+        for (text, _) in issues.iter() {
+            let label = Label::new(Some(text.as_str()));
+            label.set_xalign(0_f32);
+            label.set_wrap(true);
+            imp.issues.append(&label);
+        }
+        if issues.is_empty() {
+            let label = Label::new(Some("No travel or attribute contradicts the lore."));
+            label.set_xalign(0_f32);
+            imp.issues.append(&label);
+        }
+        imp.issue_targets.replace(
+            issues
+                .into_iter()
+                .map(|(_, location)| target(location))
+                .collect(),
+        );
+    }
+
+    /// To zoom so that the whole timeline fits the width of the window.
+    fn fit(&self) {
+        let imp = self.imp();
+        let width = match imp.scroll.width() {
+            0_i32 => self.default_width(),
+            width => width,
+        };
+        let zoom = chart::fit(&imp.timeline.borrow(), width);
+        self.set_zoom(zoom);
+    }
+
+    /// To draw the timeline at `zoom` pixels per day, within bounds.
+    fn set_zoom(&self, zoom: f64) {
+        self.imp().zoom.set(zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+        self.resize();
+    }
+
+    /// To size the chart for the timeline and the zoom, and draw it again.
+    fn resize(&self) {
+        let imp = self.imp();
+        let (width, height) = chart::size(&imp.timeline.borrow(), imp.zoom.get());
+        imp.chart.set_content_width(width);
+        imp.chart.set_content_height(height);
+        imp.chart.queue_draw();
+    }
+
+    /// To show the text of the event at `index` in the window, or the card of a lore event that
+    /// is not mentioned.
+    fn show_event(&self, index: usize) {
+        let imp = self.imp();
+        let Some(window) = imp.window.upgrade() else { return };
+        let target = imp.targets.borrow().get(index).cloned().flatten();
+        if target.is_some() {
+            self.reveal(target);
+            return;
+        }
+        let source = imp
+            .timeline
+            .borrow()
+            .events
+            .get(index)
+            .map(|event| event.source);
+        if let Some(TimelineSource::Entity(entity)) = source {
+            window.show_entity(entity);
+        }
+    }
+
+    /// To select `target` in its tab.
+    fn reveal(&self, target: Target) {
+        let Some(window) = self.imp().window.upgrade() else { return };
+        if let Some((buffer, start, end)) = target {
+            window.imp().tabs.reveal(&buffer, start, end);
+        }
+    }
+}
+
+impl Default for TWTimelineWindow {
+    fn default() -> Self {
+        Object::new::<Self>()
+            .downcast()
+            .expect("Error while downcasting TWTimelineWindow object")
+    }
+}
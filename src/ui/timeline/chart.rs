@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright 2023, (Feohr) Mohammed Rehaan and the ToadWriter contributors.
+
+//! Chart module.
+//!
+//! To draw a [`Timeline`] with time running to the right and a lane for every character and
+//! location. An event is a dot in each lane taking part, joined by a line. Events without
+//! characters or locations share a last lane.
+
+use gtk::cairo::{Context, Error, FontSlant, FontWeight};
+use lore::{Calendar, Registry, StoryTime, Timeline, TimelineEvent, TimelineSource};
+
+/// Width of the lane names in pixels.
+const NAMES: f64 = 150_f64;
+/// Height of the axis above the lanes, and of every lane.
+const AXIS: f64 = 40_f64;
+const LANE: f64 = 36_f64;
+/// Space around the chart.
+const MARGIN: f64 = 20_f64;
+/// Radius of an event.
+const DOT: f64 = 6_f64;
+/// Least space between two ticks of the axis.
+const TICK: f64 = 110_f64;
+const FONT_SIZE: f64 = 11_f64;
+/// Name of the lane of events without characters or locations.
+const OTHER: &str = "Other";
+/// Colours of the lanes, the axis, lore events and scenes.
+const STRIPE: (f64, f64, f64) = (0.95_f64, 0.95_f64, 0.93_f64);
+const INK: (f64, f64, f64) = (0.25_f64, 0.25_f64, 0.25_f64);
+const EVENT: (f64, f64, f64) = (0.90_f64, 0.60_f64, 0.30_f64);
+const SCENE: (f64, f64, f64) = (0.38_f64, 0.60_f64, 0.90_f64);
+
+/*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
+
+/// Returns the width and height needed to draw `timeline` at `zoom` pixels per day.
+pub fn size(timeline: &Timeline, zoom: f64) -> (i32, i32) {
+    let (first, last) = timeline.span().unwrap_or_default();
+    (
+        (NAMES + MARGIN * 2_f64 + (last - first) as f64 * zoom) as i32,
+        (AXIS + lanes(timeline) as f64 * LANE + MARGIN) as i32,
+    )
+}
+
+/// Returns the zoom that fits `timeline` in `width`.
+pub fn fit(timeline: &Timeline, width: i32) -> f64 {
+    let (first, last) = timeline.span().unwrap_or_default();
+    (width as f64 - NAMES - MARGIN * 2_f64).max(1_f64) / (last - first).max(1_i64) as f64
+}
+
+/// Draws `timeline` at `zoom` pixels per day with the names of `registry`. The axis shows dates
+/// of `calendar`, or day counts without one.
+pub fn draw(
+    cr: &Context,
+    width: i32,
+    timeline: &Timeline,
+    zoom: f64,
+    registry: &Registry,
+    calendar: Option<&Calendar>,
+) -> Result<(), Error> {
+    let Some((first, last)) = timeline.span() else { return Ok(()) };
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(FONT_SIZE);
+
+    for lane in 0_usize..lanes(timeline) {
+        let top = AXIS + lane as f64 * LANE;
+        if lane % 2_usize == 0_usize {
+            cr.set_source_rgb(STRIPE.0, STRIPE.1, STRIPE.2);
+            cr.rectangle(0_f64, top, width as f64, LANE);
+            cr.fill()?;
+        }
+        let name = timeline
+            .lanes
+            .get(lane)
+            .and_then(|id| registry.get(*id))
+            .map_or(OTHER, |entity| entity.name.as_str());
+        cr.set_source_rgb(INK.0, INK.1, INK.2);
+        cr.move_to(MARGIN, top + LANE / 2_f64 + FONT_SIZE / 3_f64);
+        cr.show_text(name)?;
+    }
+
+    // Ticks fall on multiples of a round number of days.
+    let step = tick_step(zoom);
+    let mut tick = first.div_euclid(step) * step;
+    cr.set_line_width(1_f64);
+    while tick <= last {
+        if tick >= first {
+            let x = x(first, zoom, tick);
+            cr.set_source_rgb(INK.0, INK.1, INK.2);
+            cr.move_to(x, AXIS - 8_f64);
+            cr.line_to(x, AXIS + lanes(timeline) as f64 * LANE);
+            cr.set_source_rgba(INK.0, INK.1, INK.2, 0.25_f64);
+            cr.stroke()?;
+
+            let label = match calendar {
+                Some(calendar) => calendar.format(calendar.from_day(tick)),
+                None => format!("Day {tick}"),
+            };
+            cr.set_source_rgb(INK.0, INK.1, INK.2);
+            cr.move_to(x + 3_f64, AXIS - 14_f64);
+            cr.show_text(label.as_str())?;
+        }
+        tick += step;
+    }
+
+    cr.set_line_width(2_f64);
+    for event in timeline.events.iter() {
+        let (red, green, blue) = match event.source {
+            TimelineSource::Entity(_) => EVENT,
+            TimelineSource::Scene(_) => SCENE,
+        };
+        cr.set_source_rgb(red, green, blue);
+        let x = x(first, zoom, event.time);
+        let ys = event_lanes(timeline, event)
+            .map(|lane| AXIS + (lane as f64 + 0.5_f64) * LANE)
+            .collect::<Vec<f64>>();
+
+        if let (Some(top), Some(bottom)) = (ys.first(), ys.last()) {
+            cr.move_to(x, *top);
+            cr.line_to(x, *bottom);
+            cr.stroke()?;
+        }
+        for y in ys {
+            cr.arc(x, y, DOT, 0_f64, std::f64::consts::TAU);
+            cr.fill()?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the index of the event drawn at `x` and `y`.
+pub fn event_at(timeline: &Timeline, zoom: f64, x: f64, y: f64) -> Option<usize> {
+    let (first, _) = timeline.span()?;
+    timeline.events.iter().position(|event| {
+        let near = (x - self::x(first, zoom, event.time)).abs() <= DOT + 2_f64;
+        near && event_lanes(timeline, event)
+            .any(|lane| (y - AXIS - (lane as f64 + 0.5_f64) * LANE).abs() <= DOT + 2_f64)
+    })
+}
+
+/// Returns the number of lanes, counting the lane for events without any.
+fn lanes(timeline: &Timeline) -> usize {
+    let other = timeline.events.iter().any(|event| event.lanes.is_empty());
+    timeline.lanes.len() + other as usize
+}
+
+/// Returns the lanes `event` is drawn in, from the top.
+fn event_lanes<'a>(
+    timeline: &'a Timeline,
+    event: &'a TimelineEvent,
+) -> impl Iterator<Item = usize> + 'a {
+    let other = event.lanes.is_empty().then_some(timeline.lanes.len());
+    timeline
+        .lanes
+        .iter()
+        .enumerate()
+        .filter(|(_, id)| event.lanes.contains(id))
+        .map(|(lane, _)| lane)
+        .chain(other)
+}
+
+/// Returns the position of `time` when the chart starts at `first`.
+fn x(first: StoryTime, zoom: f64, time: StoryTime) -> f64 {
+    NAMES + MARGIN + (time - first) as f64 * zoom
+}
+
+/// Returns the smallest number of days of 1, 2 or 5 times a power of ten that is at least
+/// [`TICK`] pixels wide at `zoom`.
+fn tick_step(zoom: f64) -> StoryTime {
+    let mut power = 1_i64;
+    loop {
+        for factor in [1_i64, 2_i64, 5_i64] {
+            let step = factor * power;
+            if step as f64 * zoom >= TICK || step >= i64::MAX / 10_i64 {
+                return step;
+            }
+        }
+        power *= 10_i64;
+    }
+}
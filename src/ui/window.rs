@@ -6,21 +6,73 @@
 //! The main application window.
 
 use crate::ui::{
+    export::export_text,
+    findbar::TWFindBar,
+    dashboard::TWDashboardWindow,
+    dialogue::TWDialogueWindow,
+    goals::TWGoalsWindow,
+    page::{
+        BufferDiagnostic, PaperSize, SharedLore, SharedSpellChecker, TWBuffer, ENTITY_ADDED,
+        MENTION_ACTIVATED, TEXT_EDITED,
+    },
     menubox::TWMenuBox,
-    page::TWPage,
-    statusbar::TWStatusBar,
+    readability::TWReadabilityWindow,
+    statusbar::{GoalsProgress, TWStatusBar},
+    tabview::TWTabView,
     toolbar::TWToolBar,
     app::TWApplication,
+    appearances::TWAppearanceWindow,
+    calendars::TWCalendarWindow,
+    interchange::TWInterchangeWindow,
     license::TWLicenseWindow,
+    lint::TWLintWindow,
+    lore::{TWLoreSidebar, LORE_CHANGED},
+    relations::TWRelationWindow,
+    rename::TWRenameWindow,
+    scenes::TWSceneWindow,
+    series::TWSeriesWindow,
+    timeline::TWTimelineWindow,
 };
 use crate::config;
+use format::{
+    project::Project,
+    scenes::read_scene_metadata,
+    series::{Series, SeriesError},
+};
 use gtk::{
     glib, glib::subclass::object::ObjectImpl, glib::subclass::*, glib::Object, prelude::*,
     subclass::prelude::*, ApplicationWindow, CompositeTemplate, gio::SimpleAction, glib::*,
+    FileChooserAction, FileChooserNative, FileFilter, Orientation, ResponseType,
 };
 #[allow(unused_imports)]
 use log::*;
+use lore::{Calendar, EntityId, Registry};
+use std::cell::{Cell, RefCell};
 use std::default::Default;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use syntax::{annotation::strip_annotations, lint::Linter, spell::Dictionary};
+use time::{Date, Month};
+use tree::{DocumentTree, NodeId};
+
+/// Name suggested for a new project file.
+const PROJECT_NAME: &str = "project.toml";
+/// Folder in the user data folder that holds the Hunspell dictionaries of the user.
+const DICTIONARY_FOLDER: &str = "dictionaries";
+/// Folders searched for Hunspell dictionaries after the one in the user data folder.
+const DICTIONARY_DIRS: [&str; 3_usize] = [
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+];
+/// A longer pause between two edits is not counted as time spent typing.
+const TYPING_PAUSE: Duration = Duration::from_secs(30_u64);
+/// How long typing has to pause before the project is saved.
+const SAVE_DELAY: Duration = Duration::from_secs(5_u64);
+/// How long typing has to pause before the documents are linted again.
+const LINT_DELAY: Duration = Duration::from_millis(500_u64);
 
 /*▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇▇*/
 
@@ -37,13 +89,42 @@ mod imp {
         /// Toolbar object.
         #[template_child(id = "main_toolbar")]
         pub toolbar: TemplateChild<TWToolBar>,
-        /// Main [`TextView`] object.
-        /// [`TextView`] : [`gtk::TextView`]
-        #[template_child(id = "main_page")]
-        pub page: TemplateChild<TWPage>,
+        /// Tabs that hold the opened [`TWPage`] documents.
+        /// [`TWPage`] : [`crate::ui::page::TWPage`]
+        #[template_child]
+        pub tabs: TemplateChild<TWTabView>,
+        /// Find and replace bar for the opened documents.
+        #[template_child]
+        pub findbar: TemplateChild<TWFindBar>,
         /// Main Statusbar object.
         #[template_child]
         pub statusbar: TemplateChild<TWStatusBar>,
+        /// Lists the lore entities and edits them.
+        #[template_child]
+        pub lore_sidebar: TemplateChild<TWLoreSidebar>,
+        /// The lore entities of the opened documents.
+        pub lore: SharedLore,
+        /// The goals and writing history of the opened documents.
+        pub project: RefCell<Project>,
+        /// The file of the project, [`None`] until the project is opened or saved as one.
+        pub project_path: RefCell<Option<PathBuf>>,
+        /// The save scheduled after the last edit.
+        pub save_source: RefCell<Option<SourceId>>,
+        /// The series the project is a book of, as last read or saved.
+        pub series: RefCell<Option<Series>>,
+        /// The lore of the book as last read from or written to the series, to tell what the book
+        /// changed since.
+        pub series_lore: RefCell<Registry>,
+        /// Words written since the window was opened.
+        pub session: Cell<i64>,
+        /// Checks the spelling of every opened document.
+        pub spell: SharedSpellChecker,
+        /// When the documents were last edited, to measure the time spent typing.
+        pub last_edit: Cell<Option<Instant>>,
+        /// The prose linter run over every opened document.
+        pub linter: Linter,
+        /// The lint run scheduled after the last edit.
+        pub lint_source: RefCell<Option<SourceId>>,
     }
 
     #[glib::object_subclass]
@@ -65,7 +146,12 @@ mod imp {
 
     impl WidgetImpl for TWApplicationWindow {}
 
-    impl WindowImpl for TWApplicationWindow {}
+    impl WindowImpl for TWApplicationWindow {
+        fn close_request(&self) -> Inhibit {
+            self.obj().save_project();
+            self.parent_close_request()
+        }
+    }
 
     impl ApplicationWindowImpl for TWApplicationWindow {}
 }
@@ -86,10 +172,614 @@ impl TWApplicationWindow {
 
         let action_open = Self::create_actions();
         window.add_action(&action_open);
+        window.create_document_actions();
+        window.create_view_actions();
+        window.create_lore_actions();
+        window.create_goals_actions();
+        window.create_analysis_actions();
+        window.load_spelling();
+
+        let tabs = &window.imp().tabs;
+        tabs.set_statusbar(&window.imp().statusbar);
+        tabs.set_spell_checker(&window.imp().spell);
+        tabs.set_lore(&window.imp().lore);
+        tabs.connect_local(
+            MENTION_ACTIVATED,
+            false,
+            clone!(@weak window => @default-return None, move |values| {
+                let entity = values[1_usize].get::<u32>().ok()?;
+                window.show_entity(EntityId::from_value(entity));
+                None
+            }),
+        );
+        tabs.connect_local(
+            ENTITY_ADDED,
+            false,
+            clone!(@weak window => @default-return None, move |values| {
+                let entity = values[1_usize].get::<u32>().ok()?;
+                window.sync_lore_names();
+                window.show_entity(EntityId::from_value(entity));
+                None
+            }),
+        );
+        tabs.connect_local(
+            TEXT_EDITED,
+            false,
+            clone!(@weak window => @default-return None, move |values| {
+                let document = values[1_usize].get::<String>().ok()?;
+                let words = values[2_usize].get::<i64>().ok()?;
+                window.record_edit(document.as_str(), words);
+                window.queue_lint();
+                window.queue_save();
+                None
+            }),
+        );
+        tabs.new_tab();
+        window.imp().findbar.set_tabs(tabs);
+        window.refresh_goals();
 
         window
     }
 
+    /// To add the actions that handle the opened documents.
+    fn create_document_actions(&self) {
+        let action_new = SimpleAction::new("document.new", None);
+        action_new.connect_activate(clone!(@weak self as window => move |_, _| {
+            window.imp().tabs.new_tab();
+        }));
+        self.add_action(&action_new);
+
+        let action_close = SimpleAction::new("document.close", None);
+        action_close.connect_activate(clone!(@weak self as window => move |_, _| {
+            let tabs = &window.imp().tabs;
+            let Some(index) = tabs.imp().notebook.current_page() else { return };
+            tabs.close_tab(index);
+        }));
+        self.add_action(&action_close);
+
+        let action_export = SimpleAction::new("document.export", None);
+        action_export.connect_activate(clone!(@weak self as window => move |_, _| {
+            let Some(page) = window.imp().tabs.current_page() else { return };
+            let name = format!("{}.txt", page.document_title());
+            let status = window.imp().statusbar.imp().message.get();
+            export_text(&window, "Export Clean Text", name.as_str(), &status, move || {
+                strip_annotations(page.text().as_str())
+            });
+        }));
+        self.add_action(&action_export);
+
+        // The paper size belongs to the document, so the action follows the active tab.
+        let paper = PaperSize::default().as_str().to_variant();
+        let action_paper =
+            SimpleAction::new_stateful("document.paper", Some(VariantTy::STRING), &paper);
+        action_paper.connect_change_state(clone!(@weak self as window => move |action, state| {
+            let Some(name) = state.and_then(|state| state.get::<String>()) else { return };
+            let Some(paper) = PaperSize::named(name.as_str()) else { return };
+            if let Some(page) = window.imp().tabs.current_page() {
+                page.set_paper(paper);
+            }
+            action.set_state(&name.to_variant());
+        }));
+        self.add_action(&action_paper);
+        self.imp().tabs.imp().notebook.connect_page_notify(
+            clone!(@weak self as window, @weak action_paper => move |_| {
+                let Some(page) = window.imp().tabs.current_page() else { return };
+                action_paper.set_state(&page.tw_buffer().paper().as_str().to_variant());
+            }),
+        );
+
+        let action_open = SimpleAction::new("project.open", None);
+        action_open.connect_activate(clone!(@weak self as window => move |_, _| {
+            window.choose_project(false);
+        }));
+        self.add_action(&action_open);
+
+        let action_save_as = SimpleAction::new("project.save-as", None);
+        action_save_as.connect_activate(clone!(@weak self as window => move |_, _| {
+            window.choose_project(true);
+        }));
+        self.add_action(&action_save_as);
+
+        let action_find = SimpleAction::new("find.open", None);
+        action_find.connect_activate(clone!(@weak self as window => move |_, _| {
+            window.imp().findbar.open();
+        }));
+        self.add_action(&action_find);
+    }
+
+    /// To add the actions that work on the lore registry.
+    fn create_lore_actions(&self) {
+        let sidebar = &self.imp().lore_sidebar;
+        sidebar.set_lore(&self.imp().lore);
+        sidebar.connect_local(
+            LORE_CHANGED,
+            false,
+            clone!(@weak self as window => @default-return None, move |_| {
+                window.sync_lore_names();
+                None
+            }),
+        );
+
+        let action_sidebar = SimpleAction::new_stateful("lore.sidebar", None, &true.to_variant());
+        action_sidebar.connect_activate(|action, _| {
+            let shown = action.state().and_then(|state| state.get::<bool>()).unwrap_or(true);
+            action.change_state(&(!shown).to_variant());
+        });
+        action_sidebar.connect_change_state(clone!(@weak self as window => move |action, state| {
+            let Some(shown) = state.and_then(|state| state.get::<bool>()) else { return };
+            window.imp().lore_sidebar.set_visible(shown);
+            action.set_state(&shown.to_variant());
+        }));
+        self.add_action(&action_sidebar);
+
+        let action_rename = SimpleAction::new("lore.rename", None);
+        action_rename.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWRenameWindow::new(&window).present();
+        }));
+        self.add_action(&action_rename);
+
+        let action_relations = SimpleAction::new("lore.relations", None);
+        action_relations.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWRelationWindow::new(&window).present();
+        }));
+        self.add_action(&action_relations);
+
+        let action_calendars = SimpleAction::new("lore.calendars", None);
+        action_calendars.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWCalendarWindow::new(&window).present();
+        }));
+        self.add_action(&action_calendars);
+
+        let action_timeline = SimpleAction::new("lore.timeline", None);
+        action_timeline.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWTimelineWindow::new(&window).present();
+        }));
+        self.add_action(&action_timeline);
+
+        let action_transfer = SimpleAction::new("lore.transfer", None);
+        action_transfer.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWInterchangeWindow::new(&window).present();
+        }));
+        self.add_action(&action_transfer);
+
+        let action_series = SimpleAction::new("lore.series", None);
+        action_series.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWSeriesWindow::new(&window).present();
+        }));
+        self.add_action(&action_series);
+    }
+
+    /// To add the actions that edit the writing goals.
+    fn create_goals_actions(&self) {
+        let action_goals = SimpleAction::new("goals.edit", None);
+        action_goals.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWGoalsWindow::new(&window).present();
+        }));
+        self.add_action(&action_goals);
+
+        let action_dashboard = SimpleAction::new("goals.dashboard", None);
+        action_dashboard.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWDashboardWindow::new(&window).present();
+        }));
+        self.add_action(&action_dashboard);
+    }
+
+    /// To add the actions that analyse the prose of the opened documents.
+    fn create_analysis_actions(&self) {
+        let action_lint = SimpleAction::new("lint.settings", None);
+        action_lint.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWLintWindow::new(&window).present();
+        }));
+        self.add_action(&action_lint);
+
+        let action_readability = SimpleAction::new("report.readability", None);
+        action_readability.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWReadabilityWindow::new(&window).present();
+        }));
+        self.add_action(&action_readability);
+
+        let action_dialogue = SimpleAction::new("report.dialogue", None);
+        action_dialogue.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWDialogueWindow::new(&window).present();
+        }));
+        self.add_action(&action_dialogue);
+
+        let action_appearances = SimpleAction::new("report.appearances", None);
+        action_appearances.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWAppearanceWindow::new(&window).present();
+        }));
+        self.add_action(&action_appearances);
+
+        let action_scenes = SimpleAction::new("report.scenes", None);
+        action_scenes.connect_activate(clone!(@weak self as window => move |_, _| {
+            TWSceneWindow::new(&window).present();
+        }));
+        self.add_action(&action_scenes);
+    }
+
+    /// To open the card of the entity with `id` in the lore sidebar, showing the sidebar if it is
+    /// hidden.
+    pub fn show_entity(&self, id: EntityId) {
+        if !self.imp().lore_sidebar.is_visible() {
+            self.change_action_state("lore.sidebar", &true.to_variant());
+        }
+        self.imp().lore_sidebar.show_entity(id);
+    }
+
+    /// To ask for a project file to open, or to save the project as if `save`.
+    fn choose_project(&self, save: bool) {
+        let (title, action, accept) = match save {
+            true => ("Save Project As", FileChooserAction::Save, "_Save"),
+            false => ("Open Project", FileChooserAction::Open, "_Open"),
+        };
+        let chooser = FileChooserNative::new(
+            Some(title),
+            Some(self),
+            action,
+            Some(accept),
+            Some("_Cancel"),
+        );
+        let filter = FileFilter::new();
+        filter.set_name(Some("Projects"));
+        filter.add_suffix("toml");
+        chooser.add_filter(&filter);
+        if save {
+            chooser.set_current_name(PROJECT_NAME);
+        }
+
+        // The chooser has to be kept alive until it answers.
+        let kept = Rc::new(RefCell::new(Some(chooser.clone())));
+        chooser.connect_response(clone!(@weak self as window => move |chooser, response| {
+            kept.replace(None);
+            if response != ResponseType::Accept { return }
+            let Some(path) = chooser.file().and_then(|file| file.path()) else { return };
+
+            match save {
+                true => {
+                    window.imp().project_path.replace(Some(path));
+                    window.save_project();
+                    window.show_project_name();
+                }
+                false => window.load_project(path),
+            }
+        }));
+
+        chooser.show();
+    }
+
+    /// To save the current project and read the project at `path` instead, opening its documents
+    /// in place of the current ones. The lore moves to the shared registry and is copied back when
+    /// saving. A book of a series reads its lore from the series, falling back to the copy in the
+    /// project.
+    fn load_project(&self, path: PathBuf) {
+        let mut project = match Project::load(&path) {
+            Ok(project) => project,
+            Err(err) => {
+                error!("{err}");
+                self.imp()
+                    .statusbar
+                    .imp()
+                    .message
+                    .set_label(err.to_string().as_str());
+                return;
+            }
+        };
+        self.save_project();
+
+        let mut series = None;
+        if let Some(link) = project.series.as_ref() {
+            match Series::load(&link.path) {
+                Ok(loaded) => {
+                    project.lore = loaded.lore_of(link.book.as_str());
+                    series = Some(loaded);
+                }
+                Err(err) => error!("{}: {err}", link.path.display()),
+            }
+        }
+        let lore = std::mem::take(&mut project.lore);
+        self.imp().tabs.open_documents(&project.documents);
+        self.imp()
+            .spell
+            .borrow_mut()
+            .set_personal(project.dictionary.clone());
+        self.imp().project.replace(project);
+        self.imp().project_path.replace(Some(path));
+        self.imp().series.replace(series);
+        self.replace_series_lore(lore);
+        self.show_project_name();
+        self.refresh_goals();
+    }
+
+    /// To show the name of the project file in the title of the window.
+    fn show_project_name(&self) {
+        let name = self
+            .imp()
+            .project_path
+            .borrow()
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned());
+        match name {
+            Some(name) => self.set_title(Some(format!("{name} — {}", config::APP_NAME).as_str())),
+            None => self.set_title(Some(config::APP_NAME)),
+        }
+    }
+
+    /// To save the project once typing pauses, so that the history is kept without waiting for
+    /// the window to close. Every edit starts the wait again.
+    fn queue_save(&self) {
+        if let Some(source) = self.imp().save_source.take() {
+            source.remove();
+        }
+        let source = glib::timeout_add_local_once(
+            SAVE_DELAY,
+            clone!(@weak self as window => move || {
+                window.imp().save_source.replace(None);
+                window.save_project();
+            }),
+        );
+        self.imp().save_source.replace(Some(source));
+    }
+
+    /// To write the project file with the opened documents if the project has one, and the
+    /// series with the changes the book made to its lore.
+    pub fn save_project(&self) {
+        if let Some(lore) = self.save_series() {
+            if lore == *self.imp().lore.borrow() {
+                self.imp().series_lore.replace(lore);
+            } else {
+                self.replace_series_lore(lore);
+            }
+        }
+
+        let mut project = self.imp().project.borrow_mut();
+        project.dictionary = self.imp().spell.borrow().personal().clone();
+        project.lore = self.imp().lore.borrow().clone();
+        project.documents = self.imp().tabs.documents();
+        if let Some(path) = self.imp().project_path.borrow().as_ref() {
+            match project.save(path) {
+                Ok(()) => self.imp().tabs.mark_saved(),
+                Err(err) => error!("{}: {err}", path.display()),
+            }
+        }
+    }
+
+    /// To write the changes the book made to its lore over the series, and return the lore of the
+    /// book as the series has it now. Entities the book added get new ids in the series, so the
+    /// book has to take that lore in place of its own.
+    fn save_series(&self) -> Option<Registry> {
+        let project = self.imp().project.borrow();
+        let mut cached = self.imp().series.borrow_mut();
+        let (Some(link), Some(cached)) = (project.series.as_ref(), cached.as_mut()) else {
+            return None;
+        };
+        // Other books may have changed the series since it was read, so only the changes of this
+        // book are written over the series as it is now. The overrides of a book are only changed
+        // by the book itself.
+        let mut series = match Series::load(&link.path) {
+            Ok(series) => series,
+            Err(SeriesError::Io(err)) if err.kind() == ErrorKind::NotFound => cached.clone(),
+            Err(err) => {
+                error!("{}: {err}", link.path.display());
+                return None;
+            }
+        };
+        series.book_mut(link.book.as_str()).overrides = cached.overrides(&link.book).to_vec();
+        let report = series.absorb(
+            link.book.as_str(),
+            &self.imp().lore.borrow(),
+            &self.imp().series_lore.borrow(),
+        );
+        for conflict in report.conflicts.iter() {
+            warn!(
+                "{}: another book changed the {} to {:?}, kept over {:?}",
+                series
+                    .lore
+                    .get(conflict.entity)
+                    .map_or(String::new(), |entity| entity.name.clone()),
+                conflict.field,
+                conflict.current,
+                conflict.incoming,
+            );
+        }
+        if let Err(err) = series.save(&link.path) {
+            error!("{}: {err}", link.path.display());
+            return None;
+        }
+        *cached = series;
+        Some(cached.lore_of(link.book.as_str()))
+    }
+
+    /// Returns a [`DocumentTree`] of the opened documents as [`TWTabView::document_tree`] does,
+    /// with the metadata of every scene read from its text.
+    pub fn document_tree(&self) -> (DocumentTree, Vec<(NodeId, TWBuffer)>) {
+        let (mut tree, chapters) = self.imp().tabs.document_tree();
+        read_scene_metadata(&mut tree);
+        (tree, chapters)
+    }
+
+    /// Returns the first calendar of the project, which lore times such as births are read with.
+    pub fn calendar(&self) -> Option<Calendar> {
+        self.imp().project.borrow().calendars.first().cloned()
+    }
+
+    /// To replace the lore of the opened documents with `lore`, the lore of the book as the series
+    /// has it now.
+    pub fn replace_series_lore(&self, lore: Registry) {
+        self.imp().series_lore.replace(lore.clone());
+        self.replace_lore(lore);
+    }
+
+    /// To replace the lore of the opened documents with `lore`, such as the lore of another book.
+    pub fn replace_lore(&self, lore: Registry) {
+        self.imp().lore.replace(lore);
+        self.imp()
+            .lore_sidebar
+            .emit_by_name::<()>(LORE_CHANGED, &[]);
+    }
+
+    /// To load the Hunspell dictionary of the user language and the words accepted in the
+    /// project. Spell checking stays off if there is no dictionary for the language.
+    fn load_spelling(&self) {
+        let mut dirs = vec![glib::user_data_dir()
+            .join(config::APP_NAME)
+            .join(DICTIONARY_FOLDER)];
+        dirs.extend(DICTIONARY_DIRS.iter().map(PathBuf::from));
+
+        let dictionary = glib::language_names()
+            .iter()
+            .map(|language| language.split('.').next().unwrap_or_default().to_string())
+            .find_map(|language| {
+                dirs.iter()
+                    .find_map(|dir| Self::load_dictionary(dir, &language))
+            });
+        if dictionary.is_none() {
+            warn!("No Hunspell dictionary found, spell checking is disabled");
+        }
+
+        let mut spell = self.imp().spell.borrow_mut();
+        spell.set_dictionary(dictionary);
+        spell.set_personal(self.imp().project.borrow().dictionary.clone());
+        drop(spell);
+        self.sync_lore_names();
+    }
+
+    /// Returns the dictionary for `language` in `dir` if there is one that can be read.
+    fn load_dictionary(dir: &Path, language: &str) -> Option<Dictionary> {
+        let (aff, dic) = (
+            dir.join(format!("{language}.aff")),
+            dir.join(format!("{language}.dic")),
+        );
+        if !aff.exists() || !dic.exists() {
+            return None;
+        }
+        Dictionary::load(&aff, &dic)
+            .map_err(|err| error!("{}: {err}", dic.display()))
+            .ok()
+    }
+
+    /// To accept every name and alias of the lore as correctly spelled, mark their mentions and
+    /// list the entities in the sidebar again.
+    pub fn sync_lore_names(&self) {
+        let lore = self.imp().lore.borrow();
+        self.imp()
+            .spell
+            .borrow_mut()
+            .set_names(lore.iter().flat_map(|entity| entity.names()));
+        drop(lore);
+        self.imp().tabs.check_spelling();
+        self.imp().tabs.refresh_mentions();
+        self.imp().lore_sidebar.refresh();
+    }
+
+    /// To record an edit of `document` that added `words` in the session and in the history of
+    /// today. The time since the previous edit counts as typing unless it was a long pause.
+    fn record_edit(&self, document: &str, words: i64) {
+        let imp = self.imp();
+        let (today, now) = (today(), Instant::now());
+        let mut project = imp.project.borrow_mut();
+
+        if let Some(last) = imp.last_edit.replace(Some(now)) {
+            let typing = now.duration_since(last);
+            if typing < TYPING_PAUSE {
+                project.history.add_typing(today, typing.as_millis() as u64);
+            }
+        }
+        if words == 0_i64 {
+            return
+        }
+
+        imp.session.set(imp.session.get() + words);
+        project.history.add_words(today, document, words);
+        drop(project);
+        self.refresh_goals();
+    }
+
+    /// To show the progress towards the goals on the [`TWStatusBar`].
+    pub fn refresh_goals(&self) {
+        let imp = self.imp();
+        let project = imp.project.borrow();
+        let (today, total) = (today(), imp.tabs.total_words());
+
+        imp.statusbar.show_goals(&GoalsProgress {
+            session: project.goals.session_progress(imp.session.get()),
+            daily: project.goals.daily_progress(&project.history, today),
+            project: project.goals.project_progress(total),
+            per_day: project.goals.words_per_day(total, today),
+        });
+    }
+
+    /// To lint the documents once typing pauses instead of on every keystroke.
+    pub fn queue_lint(&self) {
+        if let Some(source) = self.imp().lint_source.take() {
+            source.remove();
+        }
+        let source = glib::timeout_add_local_once(
+            LINT_DELAY,
+            clone!(@weak self as window => move || {
+                window.imp().lint_source.replace(None);
+                window.lint();
+            }),
+        );
+        self.imp().lint_source.replace(Some(source));
+    }
+
+    /// To run the prose linter over every opened document and underline what it finds. The
+    /// documents are linted together so that rules such as spelling variants see all of them.
+    fn lint(&self) {
+        let imp = self.imp();
+        let (tree, chapters) = imp.tabs.document_tree();
+        let diagnostics = imp.linter.lint(&tree, &imp.project.borrow().lint);
+
+        for (chapter, buffer) in chapters {
+            let marks = diagnostics
+                .iter()
+                .filter(|diagnostic| tree.chapter_of(diagnostic.scene) == Some(chapter))
+                .filter_map(|diagnostic| {
+                    Some(BufferDiagnostic {
+                        start: tree.source_offset(diagnostic.scene, diagnostic.range.start)? as i32,
+                        end: tree.source_offset(diagnostic.scene, diagnostic.range.end)? as i32,
+                        severity: diagnostic.severity,
+                        message: diagnostic.message.clone(),
+                    })
+                })
+                .collect();
+            buffer.set_diagnostics(marks);
+        }
+    }
+
+    /// To add the actions that split the active tab into panes and move between them.
+    /// `split-horizontal` stacks the panes with a horizontal divider whereas `split-vertical`
+    /// places them side by side.
+    fn create_view_actions(&self) {
+        let splits = [
+            ("view.split-horizontal", Orientation::Vertical),
+            ("view.split-vertical", Orientation::Horizontal),
+        ];
+        for (name, orientation) in splits {
+            let action = SimpleAction::new(name, None);
+            action.connect_activate(clone!(@weak self as window => move |_, _| {
+                let Some(split) = window.imp().tabs.current_split() else { return };
+                split.split(orientation);
+            }));
+            self.add_action(&action);
+        }
+
+        let action_unsplit = SimpleAction::new("view.unsplit", None);
+        action_unsplit.connect_activate(clone!(@weak self as window => move |_, _| {
+            let Some(split) = window.imp().tabs.current_split() else { return };
+            split.unsplit();
+        }));
+        self.add_action(&action_unsplit);
+
+        let action_focus = SimpleAction::new("view.focus-next", None);
+        action_focus.connect_activate(clone!(@weak self as window => move |_, _| {
+            let Some(split) = window.imp().tabs.current_split() else { return };
+            split.focus_next();
+        }));
+        self.add_action(&action_focus);
+    }
+
     fn create_actions() -> SimpleAction {
         let license_win = WeakRef::new();
 
@@ -107,6 +797,15 @@ impl TWApplicationWindow {
     }
 }
 
+/// Returns the local date.
+pub(crate) fn today() -> Date {
+    let now = glib::DateTime::now_local().expect("Cannot get the local time");
+    let month = Month::try_from(now.month() as u8).expect("GLib months are always valid");
+
+    Date::from_calendar_date(now.year(), month, now.day_of_month() as u8)
+        .expect("GLib dates are always valid")
+}
+
 impl Default for TWApplicationWindow {
     fn default() -> Self {
         TWApplication::default()